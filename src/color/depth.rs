@@ -0,0 +1,272 @@
+use super::Rgba;
+
+// how many colors the terminal can show
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ColorDepth {
+    TrueColor,
+    // the xterm 256 color palette
+    Indexed256,
+    // the 16 ansi colors
+    Ansi16,
+    // no colors at all, only attributes
+    Monochrome,
+}
+
+impl ColorDepth {
+    // guesses from the environment, the way most terminal programs do
+    pub fn detect() -> Self {
+        Self::from_env(|key| std::env::var(key).ok())
+    }
+
+    fn from_env(env: impl Fn(&str) -> Option<String>) -> Self {
+        // https://no-color.org
+        if env("NO_COLOR").is_some_and(|s| !s.is_empty()) {
+            return Self::Monochrome;
+        }
+
+        let var = |key| env(key).unwrap_or_default().to_ascii_lowercase();
+
+        let colorterm = var("COLORTERM");
+        if colorterm == "truecolor" || colorterm == "24bit" {
+            return Self::TrueColor;
+        }
+
+        let term = var("TERM");
+        if ["truecolor", "24bit", "direct"]
+            .iter()
+            .any(|s| term.contains(s))
+        {
+            return Self::TrueColor;
+        }
+
+        // these set TERM to something plain but support truecolor
+        if env("WT_SESSION").is_some()
+            || matches!(&*var("TERM_PROGRAM"), "iterm.app" | "wezterm" | "vscode")
+        {
+            return Self::TrueColor;
+        }
+
+        if term.contains("256") {
+            return Self::Indexed256;
+        }
+
+        match &*term {
+            "" if cfg!(windows) => Self::TrueColor,
+            "" | "linux" | "ansi" | "cons25" | "dumb" => Self::Ansi16,
+            term if term.starts_with("vt") => Self::Ansi16,
+            _ => Self::Indexed256,
+        }
+    }
+
+    // the closest color the terminal can show
+    pub fn quantize(&self, rgba: Rgba) -> Rgba {
+        match self {
+            Self::TrueColor | Self::Monochrome => rgba,
+            Self::Indexed256 => palette_color(to_ansi256(rgba)),
+            Self::Ansi16 => ANSI16[to_ansi16(rgba) as usize],
+        }
+    }
+}
+
+// the levels of each channel in the 6x6x6 cube
+const CUBE: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+// xterm's default colors
+const ANSI16: [Rgba; 16] = [
+    Rgba::new(0, 0, 0),
+    Rgba::new(205, 0, 0),
+    Rgba::new(0, 205, 0),
+    Rgba::new(205, 205, 0),
+    Rgba::new(0, 0, 238),
+    Rgba::new(205, 0, 205),
+    Rgba::new(0, 205, 205),
+    Rgba::new(229, 229, 229),
+    Rgba::new(127, 127, 127),
+    Rgba::new(255, 0, 0),
+    Rgba::new(0, 255, 0),
+    Rgba::new(255, 255, 0),
+    Rgba::new(92, 92, 255),
+    Rgba::new(255, 0, 255),
+    Rgba::new(0, 255, 255),
+    Rgba::new(255, 255, 255),
+];
+
+// the index into the 256 color palette, this never picks the first 16
+// because terminals let users change those
+pub(crate) fn to_ansi256(rgba: Rgba) -> u8 {
+    let level = |c: u8| match c {
+        0..=47 => 0,
+        48..=114 => 1,
+        c => (c - 35) / 40,
+    };
+
+    let (r, g, b) = (level(rgba.0), level(rgba.1), level(rgba.2));
+    let cube = 16 + 36 * r + 6 * g + b;
+
+    let average = (rgba.0 as u32 + rgba.1 as u32 + rgba.2 as u32) / 3;
+    let gray = 232 + (average.saturating_sub(3) / 10).min(23) as u8;
+
+    if distance(rgba, palette_color(gray)) < distance(rgba, palette_color(cube)) {
+        gray
+    } else {
+        cube
+    }
+}
+
+pub(crate) fn to_ansi16(rgba: Rgba) -> u8 {
+    (0..16)
+        .min_by_key(|&i| distance(rgba, ANSI16[i as usize]))
+        .unwrap_or_default()
+}
+
+pub(crate) fn palette_color(index: u8) -> Rgba {
+    match index {
+        0..=15 => ANSI16[index as usize],
+        16..=231 => {
+            let i = index - 16;
+            Rgba::new(
+                CUBE[i as usize / 36],
+                CUBE[(i as usize / 6) % 6],
+                CUBE[i as usize % 6],
+            )
+        }
+        _ => {
+            let level = 8 + 10 * (index - 232);
+            Rgba::new(level, level, level)
+        }
+    }
+}
+
+// "redmean", a cheap approximation of how different two colors look
+fn distance(left: Rgba, right: Rgba) -> u32 {
+    let mean = (left.0 as i32 + right.0 as i32) / 2;
+    let r = left.0 as i32 - right.0 as i32;
+    let g = left.1 as i32 - right.1 as i32;
+    let b = left.2 as i32 - right.2 as i32;
+    ((((512 + mean) * r * r) >> 8) + 4 * g * g + (((767 - mean) * b * b) >> 8)) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detect() {
+        let table: &[(&[(&str, &str)], ColorDepth)] = &[
+            (&[("TERM", "xterm")], ColorDepth::Indexed256),
+            (&[("TERM", "xterm-256color")], ColorDepth::Indexed256),
+            (&[("TERM", "screen-256color")], ColorDepth::Indexed256),
+            (&[("TERM", "linux")], ColorDepth::Ansi16),
+            (&[("TERM", "dumb")], ColorDepth::Ansi16),
+            (&[("TERM", "vt100")], ColorDepth::Ansi16),
+            (&[("TERM", "xterm-direct")], ColorDepth::TrueColor),
+            (
+                &[("TERM", "alacritty"), ("COLORTERM", "truecolor")],
+                ColorDepth::TrueColor,
+            ),
+            (
+                &[("TERM", "xterm-256color"), ("COLORTERM", "24bit")],
+                ColorDepth::TrueColor,
+            ),
+            (
+                &[("TERM", "xterm"), ("COLORTERM", "TrueColor")],
+                ColorDepth::TrueColor,
+            ),
+            // only says that colors work, not how many
+            (
+                &[("TERM", "xterm-256color"), ("COLORTERM", "yes")],
+                ColorDepth::Indexed256,
+            ),
+            (
+                &[("TERM", "xterm-256color"), ("TERM_PROGRAM", "WezTerm")],
+                ColorDepth::TrueColor,
+            ),
+            (
+                &[("TERM", "xterm-256color"), ("WT_SESSION", "")],
+                ColorDepth::TrueColor,
+            ),
+            (
+                &[("TERM", "xterm-direct"), ("NO_COLOR", "1")],
+                ColorDepth::Monochrome,
+            ),
+            (&[("TERM", "linux"), ("NO_COLOR", "")], ColorDepth::Ansi16),
+        ];
+
+        for (env, expected) in table {
+            let lookup = |key: &str| {
+                env.iter()
+                    .find(|(k, _)| *k == key)
+                    .map(|(_, v)| v.to_string())
+            };
+            assert_eq!(ColorDepth::from_env(lookup), *expected, "{env:?}");
+        }
+    }
+
+    #[test]
+    fn ansi256() {
+        let table = [
+            (Rgba::new(0, 0, 0), 16),
+            (Rgba::new(255, 255, 255), 231),
+            (Rgba::new(255, 0, 0), 196),
+            (Rgba::new(0, 255, 0), 46),
+            (Rgba::new(0, 0, 255), 21),
+            (Rgba::new(95, 135, 175), 67),
+            // close to a cube level
+            (Rgba::new(250, 130, 10), 208),
+            // grays are closer to the ramp than to the cube
+            (Rgba::new(8, 8, 8), 232),
+            (Rgba::new(128, 128, 128), 244),
+            (Rgba::new(238, 238, 238), 255),
+        ];
+        for (rgba, index) in table {
+            assert_eq!(to_ansi256(rgba), index, "{rgba:?}");
+        }
+    }
+
+    #[test]
+    fn ansi256_leaves_the_user_colors_alone() {
+        // even the exact default colors go to the cube or the ramp
+        for (i, &rgba) in ANSI16.iter().enumerate() {
+            let index = to_ansi256(rgba);
+            assert!(index >= 16, "{i} became {index}");
+        }
+
+        // every cube and ramp color maps back to itself
+        for index in 16..=255 {
+            assert_eq!(to_ansi256(palette_color(index)), index);
+        }
+    }
+
+    #[test]
+    fn ansi16() {
+        let table = [
+            (Rgba::new(0, 0, 0), 0),
+            (Rgba::new(200, 10, 10), 1),
+            (Rgba::new(255, 0, 0), 9),
+            (Rgba::new(0, 180, 0), 2),
+            (Rgba::new(30, 30, 230), 4),
+            (Rgba::new(120, 120, 120), 8),
+            (Rgba::new(220, 220, 220), 7),
+            (Rgba::new(255, 255, 255), 15),
+        ];
+        for (rgba, index) in table {
+            assert_eq!(to_ansi16(rgba), index, "{rgba:?}");
+        }
+
+        for (i, &rgba) in ANSI16.iter().enumerate() {
+            assert_eq!(to_ansi16(rgba), i as u8);
+        }
+    }
+
+    #[test]
+    fn quantize() {
+        let rgba = Rgba::new(250, 130, 10);
+        assert_eq!(ColorDepth::TrueColor.quantize(rgba), rgba);
+        assert_eq!(ColorDepth::Monochrome.quantize(rgba), rgba);
+        assert_eq!(
+            ColorDepth::Indexed256.quantize(rgba),
+            Rgba::new(255, 135, 0)
+        );
+        assert_eq!(ColorDepth::Ansi16.quantize(rgba), Rgba::new(205, 205, 0));
+    }
+}
@@ -1,4 +1,4 @@
-#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
 pub enum Color {
     Rgba(Rgba),
     Reset,
@@ -24,5 +24,9 @@ pub use rgba::Rgba;
 mod hsl;
 pub use hsl::Hsl;
 
+mod depth;
+pub use depth::ColorDepth;
+pub(crate) use depth::{palette_color, to_ansi16, to_ansi256};
+
 mod gradient;
 pub use gradient::{gradient, Gradient};
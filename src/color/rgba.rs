@@ -125,7 +125,7 @@ impl Rgba {
 
     pub fn as_float(&self) -> [f32; 4] {
         let Self(r, g, b, a) = *self;
-        let scale = |d| (d as f32 / 256.0);
+        let scale = |d| d as f32 / 256.0;
         [scale(r), scale(g), scale(b), scale(a)]
     }
 
@@ -219,7 +219,7 @@ impl Rgba {
 
     pub fn sine_color(n: f32) -> Self {
         let h = n * ((1.0 + 5.0_f32.sqrt()) / 2.0);
-        let h = (h + 0.5) * -1.0;
+        let h = -(h + 0.5);
         let r = (PI * h).sin();
         let g = (PI * (h + 0.3)).sin();
         let b = (PI * (h + 0.6)).sin();
@@ -1,4 +1,4 @@
-use std::collections::HashSet;
+use std::{cell::RefCell, collections::HashSet};
 
 use slotmap::SecondaryMap;
 
@@ -11,6 +11,7 @@ pub struct EventCtx<'a> {
     pub children: &'a [WidgetId],
     pub(crate) computed: &'a SecondaryMap<WidgetId, LayoutNode>,
     pub(crate) hovered: &'a HashSet<WidgetId>,
+    pub(crate) dirty: &'a RefCell<HashSet<WidgetId>>,
 }
 
 impl<'a> EventCtx<'a> {
@@ -21,4 +22,9 @@ impl<'a> EventCtx<'a> {
     pub fn get_rect(&self, id: WidgetId) -> Rect {
         self.computed[id].rect
     }
+
+    // paints this widget again, for retained widgets that changed how they look
+    pub fn invalidate(&self) {
+        self.dirty.borrow_mut().insert(self.current);
+    }
 }
@@ -1,8 +1,11 @@
 use std::{cell::Ref, hash::Hash, time::Duration};
 
-use crate::geom::{vec2, Pos2, Rect};
+use crate::geom::{pos2, vec2, Pos2, Rect};
 use crate::node::{LayoutNode, WidgetId};
-use crate::paint::{shape::Shape, Cell, CroppedSurface as Canvas};
+use crate::paint::{
+    shape::{Pixels, Shape},
+    Attribute, Cell, CroppedSurface as Canvas, CursorShape, Graphics, Image,
+};
 use crate::ui::{Inner, Paint};
 
 pub struct PaintCtx<'a: 'c, 'c> {
@@ -39,6 +42,54 @@ impl<'a: 'c, 'c> PaintCtx<'a, 'c> {
             .draw(cell)
     }
 
+    // shows the terminal's cursor at this position once the frame is drawn,
+    // it's hidden if no widget asks for it
+    pub fn set_cursor(&mut self, pos: Pos2, shape: CursorShape, blinking: bool) {
+        self.canvas.set_cursor(pos, shape, blinking)
+    }
+
+    // draws a shape over `rect`, but only where it overlaps this widget's rect
+    pub fn draw_clipped(&mut self, rect: Rect, shape: impl Shape) {
+        let clip = self.rect;
+        shape.draw(rect.size(), |pos, cell| {
+            let pos = pos + rect.left_top();
+            if clip.contains(pos) {
+                self.put(pos, cell)
+            }
+        })
+    }
+
+    // how images drawn by this widget end up on the screen
+    pub fn graphics(&self) -> Graphics {
+        self.ui.capabilities.borrow().graphics()
+    }
+
+    // stretches the image over this widget's rect
+    pub fn draw_image(&mut self, image: &Image) {
+        let rect = self.get_layout_node(self.current_id).rect;
+        self.draw_image_cropped(rect, image)
+    }
+
+    // images that would be clipped are drawn with half blocks, so only the visible part is drawn
+    pub fn draw_image_cropped(&mut self, rect: Rect, image: &Image) {
+        if self.graphics() == Graphics::HalfBlocks || self.rect.intersection(rect) != rect {
+            return self.draw_clipped(rect, Pixels::new(image));
+        }
+        self.canvas.place_image(image, rect)
+    }
+
+    // adds an attribute to everything already painted in this widget's rect
+    pub fn add_attr(&mut self, attr: Attribute) {
+        let rect = self.rect;
+        for y in rect.top()..=rect.bottom() {
+            for x in rect.left()..=rect.right() {
+                if let Some(cell) = self.canvas.get_mut(pos2(x, y)) {
+                    cell.add_attr(attr)
+                }
+            }
+        }
+    }
+
     pub fn get_layout_node(&self, id: WidgetId) -> Ref<'_, LayoutNode> {
         self.ui.layout_node(id)
     }
@@ -47,6 +98,12 @@ impl<'a: 'c, 'c> PaintCtx<'a, 'c> {
         self.ui.request_repaint()
     }
 
+    // paints this widget again on the next frame, even if it's retained
+    pub fn invalidate(&self) {
+        self.ui.dirty.borrow_mut().insert(self.current_id);
+        self.ui.request_repaint()
+    }
+
     pub fn time(&self) -> Duration {
         self.ui.time()
     }
@@ -59,6 +116,10 @@ impl<'a: 'c, 'c> PaintCtx<'a, 'c> {
         self.paint.debug(text)
     }
 
+    pub fn is_monochrome(&self) -> bool {
+        self.ui.monochrome.get()
+    }
+
     pub fn mouse_over(&self) -> bool {
         self.ui.mouse_over()
     }
@@ -25,7 +25,7 @@ impl Pos2 {
     }
 
     pub const fn is_normalized(&self) -> bool {
-        (self.x == 0 || self.y == 0) || (self.x.is_positive() && self.x.is_positive())
+        self.x >= 0 && self.y >= 0
     }
 
     #[must_use]
@@ -34,17 +34,17 @@ impl Pos2 {
     }
 
     #[must_use]
-    pub fn min(&self, other: Self) -> Self {
+    pub fn min(self, other: Self) -> Self {
         pos2(self.x.min(other.x), self.y.min(other.y))
     }
 
     #[must_use]
-    pub fn max(&self, other: Self) -> Self {
+    pub fn max(self, other: Self) -> Self {
         pos2(self.x.max(other.x), self.y.max(other.y))
     }
 
     #[must_use]
-    pub fn clamp(&self, min: Self, max: Self) -> Self {
+    pub fn clamp(self, min: Self, max: Self) -> Self {
         pos2(self.x.clamp(min.x, max.x), self.y.clamp(min.y, max.y))
     }
 
@@ -134,3 +134,31 @@ impl std::ops::SubAssign<Vec2> for Pos2 {
         *self = *self - rhs;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalized_is_neither_left_nor_above_the_origin() {
+        assert!(pos2(0, 0).is_normalized());
+        assert!(pos2(3, 0).is_normalized());
+        assert!(pos2(0, 3).is_normalized());
+        assert!(!pos2(-3, 0).is_normalized());
+        assert!(!pos2(0, -3).is_normalized());
+        assert!(!pos2(2, -3).is_normalized());
+    }
+
+    #[test]
+    fn min_max_and_clamp_are_per_axis() {
+        let (a, b) = (pos2(1, 5), pos2(4, 2));
+        assert_eq!(a.min(b), pos2(1, 2));
+        assert_eq!(a.max(b), pos2(4, 5));
+        assert_eq!(pos2(-3, 9).clamp(pos2(0, 0), pos2(5, 5)), pos2(0, 5));
+        assert_eq!(pos2(7, 2).clamp(pos2(0, 0), pos2(5, 5)), pos2(5, 2));
+
+        let (a, b) = (vec2(1, 5), vec2(4, 2));
+        assert_eq!(a.min(b), vec2(1, 2));
+        assert_eq!(a.max(b), vec2(4, 5));
+    }
+}
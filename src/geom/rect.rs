@@ -123,11 +123,10 @@ impl Rect {
         Self::from_min_max(self.min.max(other.min), self.max.min(other.max))
     }
 
+    // whether they share a cell, so empty rects never intersect anything
     pub fn intersects(&self, other: Self) -> bool {
-        self.min.x <= other.max.x
-            && other.min.x <= self.max.x
-            && self.min.y <= other.min.y
-            && other.max.y <= self.max.y
+        let rect = self.intersection(other);
+        rect.width() > 0 && rect.height() > 0
     }
 
     #[must_use]
@@ -291,3 +290,34 @@ impl std::ops::SubAssign<Vec2> for Rect {
         *self = *self - rhs
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn intersection_is_per_axis() {
+        let a = Rect::from_min_max(pos2(0, 1), pos2(4, 2));
+        let b = rect(vec2(3, 3));
+        assert_eq!(
+            a.intersection(b),
+            Rect::from_min_max(pos2(0, 1), pos2(3, 2))
+        );
+        assert_eq!(a.union(b), Rect::from_min_max(pos2(0, 0), pos2(4, 3)));
+        assert_eq!(b.clamp(pos2(5, 1)), pos2(3, 1));
+        assert_eq!(vec2(4, 2).min(vec2(3, 3)), vec2(3, 2));
+    }
+
+    #[test]
+    fn intersects_needs_a_shared_cell() {
+        let a = rect(vec2(4, 4));
+        // partly overlapping, on either axis
+        assert!(a.intersects(Rect::from_min_size(pos2(2, 2), vec2(4, 4))));
+        assert!(a.intersects(Rect::from_min_size(pos2(1, -1), vec2(2, 6))));
+        // touching edges don't share a cell
+        assert!(!a.intersects(Rect::from_min_size(pos2(4, 0), vec2(2, 2))));
+        assert!(!a.intersects(Rect::from_min_size(pos2(0, 4), vec2(2, 2))));
+        // and neither do empty rects
+        assert!(!a.intersects(Rect::from_min_size(pos2(1, 1), vec2(0, 2))));
+    }
+}
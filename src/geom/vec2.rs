@@ -25,12 +25,12 @@ impl Vec2 {
     }
 
     #[must_use]
-    pub fn min(&self, other: Self) -> Self {
+    pub fn min(self, other: Self) -> Self {
         self.to_pos2().min(other.to_pos2()).to_vec2()
     }
 
     #[must_use]
-    pub fn max(&self, other: Self) -> Self {
+    pub fn max(self, other: Self) -> Self {
         self.to_pos2().max(other.to_pos2()).to_vec2()
     }
 
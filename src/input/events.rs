@@ -1,7 +1,7 @@
 use crate::geom::{Pos2, Vec2};
-use crate::terminal::event::{Key, Modifiers, MouseButton};
+use crate::terminal::event::{Key, KeyKind, Modifiers, MouseButton};
 
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum Event {
     MouseEnter(MouseMove),
     MouseLeave(MouseMove),
@@ -11,6 +11,8 @@ pub enum Event {
     MouseDrag(MouseDrag),
     MouseScroll(MouseScroll),
     KeyInput(KeyPressed),
+    // text from a bracketed paste, delivered to widgets interested in key input
+    Paste(String),
     FocusGained,
     FocusLost,
 }
@@ -33,6 +35,14 @@ impl Event {
 pub struct KeyPressed {
     pub key: Key,
     pub modifiers: Modifiers,
+    pub kind: KeyKind,
+}
+
+impl KeyPressed {
+    // presses and repeats, which is what most widgets want
+    pub const fn is_down(&self) -> bool {
+        !self.kind.is_release()
+    }
 }
 
 // TODO `inside` | `outside` for these
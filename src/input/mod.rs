@@ -1,12 +1,14 @@
 use slotmap::{SecondaryMap, SlotMap};
-use std::collections::HashSet;
+use std::{cell::RefCell, collections::HashSet};
 
 use crate::context::EventCtx;
 use crate::geom::{Pos2, Vec2};
 use crate::node::{LayoutNode, Node, WidgetId};
 use crate::terminal::event::{Event as CoreEvent, MouseEvent};
 
-pub use crate::terminal::event::{Key, Keybind, Modifiers, MouseButton};
+pub use crate::terminal::event::{
+    Key, KeyKind, Keybind, MediaKey, ModifierKey, Modifiers, MouseButton,
+};
 
 mod events;
 pub use events::{Event, KeyPressed, MouseClick, MouseDrag, MouseHeld, MouseMove, MouseScroll};
@@ -72,42 +74,95 @@ impl Input {
         event: &CoreEvent,
         nodes: &mut SlotMap<WidgetId, Node>,
         layout: &mut SecondaryMap<WidgetId, LayoutNode>,
+        dirty: &RefCell<HashSet<WidgetId>>,
     ) -> Handled {
         self.last_event = Some(event.clone());
         match *event {
             CoreEvent::Mouse(event, pos, modifiers) => {
                 self.modifiers = modifiers;
-                self.mouse_event(event, pos, nodes, layout)
+                self.mouse_event(event, pos, nodes, layout, dirty)
             }
-            CoreEvent::Keyboard(key, modifiers) => {
+            CoreEvent::Keyboard(key, modifiers, kind) => {
                 self.modifiers = modifiers;
                 let event = KeyPressed {
                     key,
                     modifiers: self.modifiers,
+                    kind,
                 };
+                self.keyboard_event(Event::KeyInput(event), nodes, layout, dirty)
+            }
+            CoreEvent::Paste(ref data) => {
+                self.keyboard_event(Event::Paste(data.clone()), nodes, layout, dirty)
+            }
+            CoreEvent::FocusGained => self.focus_event(Event::FocusGained, nodes, layout, dirty),
+            CoreEvent::FocusLost => self.focus_event(Event::FocusLost, nodes, layout, dirty),
+            _ => Handled::Bubble,
+        }
+    }
 
-                let mut resp = Handled::Bubble;
-                for (id, ()) in self.keyboard.layered.iter() {
-                    if resp.is_sink() {
-                        break;
-                    }
-
-                    let node = &mut nodes[*id];
-                    let ctx = EventCtx {
-                        rect: layout[*id].rect,
-                        current: *id,
-                        children: &node.children,
-                        hovered: &self.mouse.mouse_over,
-                        computed: layout,
-                    };
-
-                    resp = node.widget.event(ctx, Event::KeyInput(event));
-                }
+    fn keyboard_event(
+        &mut self,
+        event: Event,
+        nodes: &mut SlotMap<WidgetId, Node>,
+        layout: &mut SecondaryMap<WidgetId, LayoutNode>,
+        dirty: &RefCell<HashSet<WidgetId>>,
+    ) -> Handled {
+        let mut resp = Handled::Bubble;
+        for (id, ()) in self.keyboard.layered.iter() {
+            if resp.is_sink() {
+                break;
+            }
 
-                resp
+            let node = &mut nodes[*id];
+            let ctx = EventCtx {
+                rect: layout[*id].rect,
+                current: *id,
+                children: &node.children,
+                hovered: &self.mouse.mouse_over,
+                computed: layout,
+                dirty,
+            };
+
+            resp = node.widget.event(ctx, event.clone());
+        }
+
+        resp
+    }
+
+    // every interested widget is told about focus changes, they can't be sunk
+    fn focus_event(
+        &mut self,
+        event: Event,
+        nodes: &mut SlotMap<WidgetId, Node>,
+        layout: &mut SecondaryMap<WidgetId, LayoutNode>,
+        dirty: &RefCell<HashSet<WidgetId>>,
+    ) -> Handled {
+        for (id, node) in nodes.iter_mut() {
+            let interest = node.widget.interest();
+            let interested = match event {
+                Event::FocusGained => interest.is_focus_gained(),
+                _ => interest.is_focus_lost(),
+            };
+            if !interested {
+                continue;
             }
-            _ => Handled::Bubble,
+
+            let Some(computed) = layout.get(id) else {
+                continue;
+            };
+
+            let ctx = EventCtx {
+                rect: computed.rect,
+                current: id,
+                children: &node.children,
+                hovered: &self.mouse.mouse_over,
+                computed: layout,
+                dirty,
+            };
+            node.widget.event(ctx, event.clone());
         }
+
+        Handled::Bubble
     }
 
     fn mouse_event(
@@ -116,6 +171,7 @@ impl Input {
         pos: Pos2,
         nodes: &mut SlotMap<WidgetId, Node>,
         layout: &mut SecondaryMap<WidgetId, LayoutNode>,
+        dirty: &RefCell<HashSet<WidgetId>>,
     ) -> Handled {
         self.mouse.pos = pos;
 
@@ -124,6 +180,7 @@ impl Input {
                 MouseContext {
                     nodes,
                     layout,
+                    dirty,
                     mouse: &mut self.mouse,
                     intersections: &mut self.intersections,
                 }
@@ -131,7 +188,7 @@ impl Input {
         }
 
         match event {
-            MouseEvent::Move { .. } => {
+            MouseEvent::Move => {
                 let event = MouseMove { pos };
                 ctx!().mouse_move(event)
             }
@@ -215,6 +272,7 @@ impl Input {
 struct MouseContext<'a> {
     nodes: &'a mut SlotMap<WidgetId, Node>,
     layout: &'a mut SecondaryMap<WidgetId, LayoutNode>,
+    dirty: &'a RefCell<HashSet<WidgetId>>,
     mouse: &'a mut Mouse,
     intersections: &'a mut Intersections,
 }
@@ -234,6 +292,7 @@ impl<'a> MouseContext<'a> {
                     children: &node.children,
                     hovered: &self.mouse.mouse_over,
                     computed: self.layout,
+                    dirty: self.dirty,
                 };
                 node.widget.event(ctx, Event::MouseMove(event));
             }
@@ -260,6 +319,7 @@ impl<'a> MouseContext<'a> {
                     children: &node.children,
                     hovered: &self.mouse.mouse_over,
                     computed: self.layout,
+                    dirty: self.dirty,
                 };
 
                 let resp = node.widget.event(ctx, Event::MouseEnter(event));
@@ -299,6 +359,7 @@ impl<'a> MouseContext<'a> {
                     children: &node.children,
                     hovered: &self.mouse.mouse_over,
                     computed: self.layout,
+                    dirty: self.dirty,
                 };
                 node.widget.event(ctx, Event::MouseLeave(event));
                 inactive.push(hit)
@@ -335,9 +396,10 @@ impl<'a> MouseContext<'a> {
                 children: &node.children,
                 hovered: &self.mouse.mouse_over,
                 computed: self.layout,
+                dirty: self.dirty,
             };
 
-            resp = node.widget.event(ctx, *event);
+            resp = node.widget.event(ctx, event.clone());
             if resp.is_sink() {
                 break;
             }
@@ -374,8 +436,9 @@ impl<'a> MouseContext<'a> {
                 children: &node.children,
                 hovered: &self.mouse.mouse_over,
                 computed: self.layout,
+                dirty: self.dirty,
             };
-            resp = node.widget.event(ctx, *event);
+            resp = node.widget.event(ctx, event.clone());
             if resp.is_sink() {
                 break;
             }
@@ -402,3 +465,54 @@ impl<'a> MouseContext<'a> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        debug::Harness,
+        geom::vec2,
+        widget::{Widget, WidgetExt as _},
+    };
+
+    // remembers the last focus change it was told about
+    #[derive(Debug, Default)]
+    struct FocusWatcher {
+        focused: Option<bool>,
+    }
+
+    impl Widget for FocusWatcher {
+        type Response = Option<bool>;
+        type Props<'a> = ();
+
+        fn update(&mut self, (): Self::Props<'_>) -> Self::Response {
+            self.focused
+        }
+
+        fn interest(&self) -> Interest {
+            Interest::FOCUS
+        }
+
+        fn event(&mut self, _: EventCtx, event: Event) -> Handled {
+            self.focused = Some(matches!(event, Event::FocusGained));
+            Handled::Bubble
+        }
+    }
+
+    #[test]
+    fn focus_changes_reach_interested_widgets() {
+        let mut harness = Harness::new(vec2(4, 1));
+        let mut seen = vec![];
+        let mut app = |ui: &crate::Ui| {
+            seen.push((ui.terminal_focused(), FocusWatcher::show(()).into_inner()));
+        };
+
+        harness.frame(&mut app);
+        harness.focus(false).frame(&mut app);
+        harness.focus(true).frame(&mut app);
+        assert_eq!(
+            seen,
+            [(true, None), (false, Some(false)), (true, Some(true))]
+        );
+    }
+}
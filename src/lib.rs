@@ -1,5 +1,7 @@
 use std::time::{Duration, Instant};
 
+use color::ColorDepth;
+
 pub mod animation;
 pub mod color;
 pub mod context;
@@ -15,20 +17,42 @@ mod queue;
 pub use queue::Queue;
 
 mod terminal;
-pub use terminal::Config;
-use terminal::Terminal;
+pub use terminal::{
+    event, Backend, Capabilities, ChannelBackend, Config, InlineHeight, StreamBackend, TtyBackend,
+};
+use terminal::{
+    event::{EntryKind, Event, Recorder, Recording},
+    Terminal,
+};
 
 mod ui;
-pub use ui::{debug, ui, Command, Ui};
+pub use ui::{debug, ui, Command, Ui, UiHandle};
 
 mod widget;
 pub use widget::{NoResponse, Widget, WidgetExt};
 
+mod base64;
 mod debug_fmt;
 
-pub fn run(config: Config, mut app: impl FnMut(&Ui)) -> std::io::Result<()> {
-    let mut terminal = Terminal::new(config)?;
+pub fn run(config: Config, app: impl FnMut(&Ui)) -> std::io::Result<()> {
+    run_with_backend(TtyBackend, config, app)
+}
+
+pub fn run_with_backend(
+    backend: impl Backend,
+    mut config: Config,
+    mut app: impl FnMut(&Ui),
+) -> std::io::Result<()> {
+    let frame_interval = config.frame_interval();
+    let pause_when_unfocused = config.pause_when_unfocused;
+    let mut recorder = config.record.take().map(Recorder::create).transpose()?;
+
+    let mut terminal = Terminal::new(backend, config)?;
     let ui = Ui::new(terminal.rect());
+    ui.set_capabilities(terminal.capabilities().clone());
+
+    let handle = ui.handle();
+    terminal.listen(move || handle.wake())?;
 
     terminal.set_title(format!(
         "{}x{} ({})",
@@ -37,33 +61,319 @@ pub fn run(config: Config, mut app: impl FnMut(&Ui)) -> std::io::Result<()> {
         terminal.rect().area()
     ))?;
 
-    let start = Instant::now();
+    let mut start = Instant::now();
+    let mut last_frame = None::<Instant>;
+    let mut paused_at = None::<Instant>;
+
+    if let Some(recorder) = &mut recorder {
+        recorder.event(0, 0.0, &Event::Resize(terminal.rect()))?;
+    }
 
     while !ui.quit() {
-        let clock = Instant::now();
+        let mut dirty = last_frame.is_none();
 
         while let Some(ev) = terminal.read_next_event() {
-            if matches!(ev, terminal::event::Event::Quit) {
+            if let Some(recorder) = &mut recorder {
+                let time = start.elapsed().as_secs_f32();
+                recorder.event(ui.current_frame(), time, &ev)?;
+            }
+
+            if matches!(ev, Event::Quit) {
                 ui.set_quit();
             }
 
-            // TODO handle this
-            let _skip = ui.handle_event(&ev);
+            ui.handle_event(&ev);
             ui.handle_external_commands(&mut terminal)?;
+            dirty = true;
+        }
+
+        // the clock is held while paused so animations pick up where they left off
+        let paused = pause_when_unfocused && !ui.terminal_focused();
+        match (paused, paused_at) {
+            (true, None) => paused_at = Some(Instant::now()),
+            (false, Some(at)) => {
+                start += at.elapsed();
+                paused_at = None;
+            }
+            _ => {}
         }
 
-        ui.scope(|| app(&ui))?;
+        let repaint_after = |last: Option<Instant>| {
+            last.filter(|_| !paused)
+                .and_then(|last| ui.repaint_after(last))
+        };
+
+        // messages and repaint requests wait until the terminal has focus again
+        dirty |= !paused && ui.take_pending();
+        dirty |= repaint_after(last_frame) == Some(Duration::ZERO);
+
+        if dirty && !ui.quit() {
+            if let Some(last) = last_frame {
+                let next = last + frame_interval;
+                std::thread::sleep(next.saturating_duration_since(Instant::now()));
+            }
+
+            last_frame = Some(Instant::now());
+            ui.reset_repaint();
 
-        if terminal.is_in_alt_screen() {
-            terminal.paint(|canvas| {
-                canvas.erase();
-                ui.paint(canvas)
-            })?;
+            frame(&ui, &mut terminal, &mut app)?;
+
+            let now = paused_at.unwrap_or_else(Instant::now);
+            let time = now.duration_since(start).as_secs_f32();
+            if let Some(recorder) = &mut recorder {
+                recorder.tick(ui.current_frame(), time)?;
+            }
+            ui.tick(time);
         }
 
-        ui.tick(start.elapsed().as_secs_f32());
-        std::thread::sleep(ui.remaining(clock).min(Duration::from_secs_f32(1.0 / 6.0)));
+        if ui.quit() {
+            break;
+        }
+
+        // sleep until there is input, something was posted to a handle or a repaint is due
+        ui.wait(repaint_after(last_frame));
+    }
+
+    Ok(())
+}
+
+// plays a recorded session back on the terminal, at the speed it was recorded
+pub fn replay(config: Config, recording: &Recording, app: impl FnMut(&Ui)) -> std::io::Result<()> {
+    replay_with_backend(TtyBackend, config, recording, app)
+}
+
+pub fn replay_with_backend(
+    backend: impl Backend,
+    config: Config,
+    recording: &Recording,
+    mut app: impl FnMut(&Ui),
+) -> std::io::Result<()> {
+    let mut terminal = Terminal::new(backend, config)?;
+    let ui = Ui::new(terminal.rect());
+    ui.set_capabilities(terminal.capabilities().clone());
+
+    let handle = ui.handle();
+    terminal.listen(move || handle.wake())?;
+
+    let start = Instant::now();
+    for entry in recording.entries() {
+        // the recording drives the app, the real terminal can only quit or resize it
+        let at = Duration::try_from_secs_f32(entry.time).unwrap_or_default();
+        loop {
+            while let Some(ev) = terminal.read_next_event() {
+                match ev {
+                    Event::Quit => ui.set_quit(),
+                    Event::Resize(..) => ui.handle_event(&ev),
+                    _ => {}
+                }
+            }
+
+            let remaining = at.saturating_sub(start.elapsed());
+            if ui.quit() || remaining.is_zero() {
+                break;
+            }
+            ui.wait(Some(remaining));
+        }
+
+        if ui.quit() {
+            break;
+        }
+
+        match &entry.kind {
+            EntryKind::Event(Event::Resize(rect)) => {
+                let rect = terminal.resize_to(rect.size())?;
+                ui.handle_event(&Event::Resize(rect));
+            }
+            EntryKind::Event(ev) => {
+                if matches!(ev, Event::Quit) {
+                    ui.set_quit();
+                }
+                ui.handle_event(ev);
+                ui.handle_external_commands(&mut terminal)?;
+            }
+            EntryKind::Tick => {
+                frame(&ui, &mut terminal, &mut app)?;
+                ui.tick(entry.time);
+            }
+        }
     }
 
     Ok(())
 }
+
+fn frame<B: Backend>(
+    ui: &Ui,
+    terminal: &mut Terminal<B>,
+    app: &mut impl FnMut(&Ui),
+) -> std::io::Result<()> {
+    ui.set_fit_content(terminal.fits_content());
+    ui.set_monochrome(terminal.color_depth() == ColorDepth::Monochrome);
+    ui.scope(|| app(ui))?;
+    ui.handle_external_commands(terminal)?;
+
+    terminal.fit_content(ui.content_height())?;
+    terminal.paint(|canvas| ui.paint(canvas))?;
+    ui.save_screenshots(terminal);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use geom::vec2;
+
+    #[test]
+    fn replay_follows_the_recorded_resize() {
+        let (backend, _tx) = ChannelBackend::new(std::io::sink(), vec2(20, 10));
+        let recording = Recording::parse("0 0 resize 0 0 8 4\n1 0 tick\n").unwrap();
+
+        let mut sizes = Vec::new();
+        replay_with_backend(backend, Config::default(), &recording, |ui| {
+            sizes.push(ui.size())
+        })
+        .unwrap();
+        assert_eq!(sizes, [vec2(8, 4)]);
+    }
+
+    #[test]
+    fn a_failed_screenshot_does_not_end_the_app() {
+        let (backend, _tx) = ChannelBackend::new(std::io::sink(), vec2(20, 4));
+        let path = std::env::temp_dir().join("dearsum-missing-dir/screenshot.svg");
+
+        let mut frames = 0;
+        run_with_backend(backend, Config::default(), |ui| {
+            frames += 1;
+            match frames {
+                1 => ui.command(Command::Screenshot(path.clone())),
+                _ => ui.command(Command::Quit),
+            }
+        })
+        .unwrap();
+        assert_eq!(frames, 2);
+    }
+
+    // a pipe that stays open until the sender is dropped
+    struct Pipe(std::sync::mpsc::Receiver<&'static [u8]>);
+
+    impl std::io::Read for Pipe {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let Ok(data) = self.0.recv() else {
+                return Ok(0);
+            };
+            buf[..data.len()].copy_from_slice(data);
+            Ok(data.len())
+        }
+    }
+
+    #[test]
+    fn raw_input_from_a_stream_is_decoded() {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let backend = StreamBackend::new(Pipe(rx), std::io::sink(), vec2(10, 2));
+        tx.send(b"\x1b[48;4;12;0;0t").unwrap();
+
+        let mut sizes = vec![];
+        run_with_backend(backend, Config::default(), |ui| {
+            sizes.push(ui.size());
+            if ui.size() == vec2(12, 4) {
+                let _ = tx.send(b"\x03");
+            }
+        })
+        .unwrap();
+        assert_eq!(sizes.last(), Some(&vec2(12, 4)));
+    }
+
+    #[test]
+    fn an_unfocused_app_ignores_repaint_requests() {
+        use std::sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        };
+
+        let (backend, tx) = ChannelBackend::new(std::io::sink(), vec2(10, 2));
+        let config = Config::default().pause_when_unfocused(true);
+        let frames = Arc::new(AtomicUsize::new(0));
+        let (handle_tx, handle_rx) = std::sync::mpsc::channel();
+
+        let driver = std::thread::spawn({
+            let frames = Arc::clone(&frames);
+            move || {
+                let settle = || std::thread::sleep(Duration::from_millis(100));
+                let handle: UiHandle = handle_rx.recv().unwrap();
+                tx.send(Event::FocusLost).unwrap();
+                settle();
+
+                let unfocused = frames.load(Ordering::Acquire);
+                for i in 0..3 {
+                    handle.send(i);
+                    handle.request_repaint();
+                }
+                settle();
+                let after_requests = frames.load(Ordering::Acquire);
+
+                tx.send(Event::FocusGained).unwrap();
+                settle();
+                let focused = frames.load(Ordering::Acquire);
+
+                tx.send(Event::Quit).unwrap();
+                (unfocused, after_requests, focused)
+            }
+        });
+
+        run_with_backend(backend, config, |ui| {
+            if frames.fetch_add(1, Ordering::AcqRel) == 0 {
+                handle_tx.send(ui.handle()).unwrap();
+            }
+        })
+        .unwrap();
+
+        let (unfocused, after_requests, focused) = driver.join().unwrap();
+        assert_eq!(unfocused, after_requests);
+        assert!(focused > after_requests);
+    }
+
+    // runs the app on a channel backend, quitting it after `duration`
+    fn run_for(config: Config, duration: Duration, app: impl FnMut(&Ui)) {
+        let (backend, tx) = ChannelBackend::new(std::io::sink(), vec2(10, 2));
+        let quit = std::thread::spawn(move || {
+            std::thread::sleep(duration);
+            let _ = tx.send(Event::Quit);
+        });
+        run_with_backend(backend, config, app).unwrap();
+        quit.join().unwrap();
+    }
+
+    #[test]
+    fn an_idle_app_is_not_repainted() {
+        let mut frames = 0;
+        run_for(Config::default(), Duration::from_millis(200), |_| {
+            frames += 1
+        });
+        assert_eq!(frames, 1);
+    }
+
+    #[test]
+    fn frames_are_capped() {
+        let config = Config::default().max_frame_rate(20);
+        let mut times = vec![];
+        run_for(config, Duration::from_millis(300), |ui| {
+            times.push(Instant::now());
+            ui.request_repaint();
+        });
+
+        assert!(times.len() > 1);
+        for pair in times.windows(2) {
+            assert!(pair[1] - pair[0] >= Duration::from_millis(49));
+        }
+    }
+
+    #[test]
+    fn replay_can_be_quit() {
+        let (backend, tx) = ChannelBackend::new(std::io::sink(), vec2(20, 10));
+        let recording = Recording::parse("0 0 tick\n1 60 tick\n").unwrap();
+        tx.send(Event::Quit).unwrap();
+
+        let start = Instant::now();
+        replay_with_backend(backend, Config::default(), &recording, |_| {}).unwrap();
+        assert!(start.elapsed() < Duration::from_secs(10));
+    }
+}
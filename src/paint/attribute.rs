@@ -1,5 +1,5 @@
-#[derive(Copy, Clone, Default, PartialEq, Eq)]
-pub struct Attribute(u8);
+#[derive(Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Attribute(u16);
 
 impl Attribute {
     pub const RESET: Self = Self(0);
@@ -10,6 +10,12 @@ impl Attribute {
     pub const BLINK: Self = Self(1 << 4);
     pub const REVERSE: Self = Self(1 << 5);
     pub const STRIKEOUT: Self = Self(1 << 6);
+    // these change the style of the underline, they don't need UNDERLINE as well
+    pub const DOUBLE_UNDERLINE: Self = Self(1 << 7);
+    pub const CURLY_UNDERLINE: Self = Self(1 << 8);
+    pub const DOTTED_UNDERLINE: Self = Self(1 << 9);
+    pub const DASHED_UNDERLINE: Self = Self(1 << 10);
+    pub const OVERLINE: Self = Self(1 << 11);
 }
 
 impl Attribute {
@@ -44,6 +50,31 @@ impl Attribute {
     pub const fn is_strikeout(&self) -> bool {
         self.0 & (1 << 6) != 0
     }
+
+    pub const fn is_double_underline(&self) -> bool {
+        self.0 & (1 << 7) != 0
+    }
+
+    pub const fn is_curly_underline(&self) -> bool {
+        self.0 & (1 << 8) != 0
+    }
+
+    pub const fn is_dotted_underline(&self) -> bool {
+        self.0 & (1 << 9) != 0
+    }
+
+    pub const fn is_dashed_underline(&self) -> bool {
+        self.0 & (1 << 10) != 0
+    }
+
+    pub const fn is_overline(&self) -> bool {
+        self.0 & (1 << 11) != 0
+    }
+
+    // whether any style of underline is set
+    pub const fn is_underlined(&self) -> bool {
+        self.0 & (1 << 3 | 0b1111 << 7) != 0
+    }
 }
 
 impl std::ops::BitAnd for Attribute {
@@ -80,13 +111,13 @@ impl std::ops::Not for Attribute {
 
 impl std::fmt::Binary for Attribute {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:08b}", self.0)
+        write!(f, "{:016b}", self.0)
     }
 }
 
 impl std::fmt::Debug for Attribute {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        const FIELDS: [&str; 7] = [
+        const FIELDS: [&str; 12] = [
             "Bold",
             "Faint",
             "Italic",
@@ -94,6 +125,11 @@ impl std::fmt::Debug for Attribute {
             "Blink",
             "Reverse",
             "Strikeout",
+            "DoubleUnderline",
+            "CurlyUnderline",
+            "DottedUnderline",
+            "DashedUnderline",
+            "Overline",
         ];
 
         let mut seen = false;
@@ -128,6 +164,11 @@ impl std::str::FromStr for Attribute {
                 s if s.eq_ignore_ascii_case("blink") => Self::BLINK,
                 s if s.eq_ignore_ascii_case("reverse") => Self::REVERSE,
                 s if s.eq_ignore_ascii_case("strikeout") => Self::STRIKEOUT,
+                s if s.eq_ignore_ascii_case("double-underline") => Self::DOUBLE_UNDERLINE,
+                s if s.eq_ignore_ascii_case("curly-underline") => Self::CURLY_UNDERLINE,
+                s if s.eq_ignore_ascii_case("dotted-underline") => Self::DOTTED_UNDERLINE,
+                s if s.eq_ignore_ascii_case("dashed-underline") => Self::DASHED_UNDERLINE,
+                s if s.eq_ignore_ascii_case("overline") => Self::OVERLINE,
                 attr => return Err(format!("unknown attribute: {attr}")),
             }
         }
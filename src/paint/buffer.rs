@@ -1,3 +1,5 @@
+use std::ops::Range;
+
 use crate::{
     color::Color,
     geom::{pos2, rect, Pos2, Rect, Vec2},
@@ -61,19 +63,55 @@ impl Buffer {
         self.cells.get_mut(Self::pos_to_index(pos, self.size.x))
     }
 
-    pub fn diff<'a>(&'a mut self, other: &'a Self) -> impl Iterator<Item = (Pos2, &'a Cell)> {
-        self.cells
-            .iter_mut()
-            .zip(other.cells.iter())
-            .enumerate()
-            .filter_map(|(i, (left, right))| {
-                if *left == *right || (right.fg == Color::Reuse && right.bg == Color::Reuse) {
-                    return None;
-                }
-
-                *left = *right;
-                Some((Self::index_to_pos(i, self.size.x), right))
-            })
+    pub fn row(&self, y: i32) -> &[Cell] {
+        let w = self.size.x as usize;
+        &self.cells[y as usize * w..(y as usize + 1) * w]
+    }
+
+    // moves every row up by `rows`, or down if its negative, and fills in the gap with `cell`
+    pub fn scroll(&mut self, rows: i32, cell: Cell) {
+        let n = (rows.unsigned_abs() as usize * self.size.x as usize).min(self.cells.len());
+        if rows > 0 {
+            self.cells.rotate_left(n);
+            let len = self.cells.len();
+            self.cells[len - n..].fill(cell);
+        } else {
+            self.cells.rotate_right(n);
+            self.cells[..n].fill(cell);
+        }
+    }
+
+    // only the cells in `ranges` are compared, they have to be in order and not overlap
+    pub fn diff<'a>(
+        &'a mut self,
+        other: &'a Self,
+        ranges: &[Range<usize>],
+    ) -> impl Iterator<Item = (Pos2, &'a Cell)> {
+        let w = self.size.x;
+
+        let mut rest = &mut self.cells[..];
+        let mut offset = 0;
+        let mut parts = Vec::with_capacity(ranges.len());
+        for range in ranges {
+            let (_, tail) = std::mem::take(&mut rest).split_at_mut(range.start - offset);
+            let (part, tail) = tail.split_at_mut(range.len());
+            parts.push((range.start, part));
+            (rest, offset) = (tail, range.end);
+        }
+
+        parts.into_iter().flat_map(move |(start, part)| {
+            part.iter_mut()
+                .zip(&other.cells[start..])
+                .enumerate()
+                .filter_map(move |(i, (left, right))| {
+                    if *left == *right || (right.fg == Color::Reuse && right.bg == Color::Reuse) {
+                        return None;
+                    }
+
+                    *left = *right;
+                    Some((Self::index_to_pos(start + i, w), right))
+                })
+        })
     }
 
     const fn pos_to_index(pos: Pos2, w: i32) -> usize {
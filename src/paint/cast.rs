@@ -0,0 +1,385 @@
+use std::{borrow::Cow, io::Write};
+
+use super::{Attribute, CursorShape, Image, Link, Renderer, TermRenderer};
+use crate::{
+    color::{ColorDepth, Rgba},
+    geom::{Pos2, Rect, Vec2},
+};
+
+// writes an asciinema v2 cast: a json header, then one json array per chunk of output
+pub struct CastWriter<W: Write> {
+    out: W,
+}
+
+impl<W: Write> CastWriter<W> {
+    // `term` is what TERM was set to, players use it and the color depth to pick a palette
+    pub fn new(
+        mut out: W,
+        size: Vec2,
+        term: Option<&str>,
+        color_depth: ColorDepth,
+    ) -> std::io::Result<Self> {
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+
+        write!(
+            out,
+            r#"{{"version": 2, "width": {}, "height": {}, "timestamp": {timestamp}, "env": {{"#,
+            size.x, size.y
+        )?;
+        let mut env = Vec::new();
+        if let Some(term) = term {
+            env.push(("TERM", term));
+        }
+        match color_depth {
+            ColorDepth::TrueColor => env.push(("COLORTERM", "truecolor")),
+            ColorDepth::Monochrome => env.push(("NO_COLOR", "1")),
+            ColorDepth::Indexed256 | ColorDepth::Ansi16 => {}
+        }
+        for (i, (key, value)) in env.into_iter().enumerate() {
+            if i > 0 {
+                out.write_all(b", ")?;
+            }
+            write!(out, r#""{key}": "#)?;
+            write_string(&mut out, value)?;
+        }
+        writeln!(out, "}}}}")?;
+        Ok(Self { out })
+    }
+
+    // time is in seconds from the start of the cast
+    pub fn output(&mut self, time: f32, data: &[u8]) -> std::io::Result<()> {
+        let data = without_clipboard(data);
+        if data.is_empty() {
+            return Ok(());
+        }
+        self.event(time, 'o', &String::from_utf8_lossy(&data))
+    }
+
+    pub fn resize(&mut self, time: f32, size: Vec2) -> std::io::Result<()> {
+        self.event(time, 'r', &format!("{}x{}", size.x, size.y))
+    }
+
+    fn event(&mut self, time: f32, code: char, data: &str) -> std::io::Result<()> {
+        write!(self.out, r#"[{time:.6}, "{code}", "#)?;
+        write_string(&mut self.out, data)?;
+        writeln!(self.out, "]")?;
+        self.out.flush()
+    }
+}
+
+// a json string, quotes included
+fn write_string(out: &mut impl Write, data: &str) -> std::io::Result<()> {
+    out.write_all(b"\"")?;
+    for ch in data.chars() {
+        match ch {
+            '"' => out.write_all(br#"\""#)?,
+            '\\' => out.write_all(br"\\")?,
+            '\n' => out.write_all(br"\n")?,
+            '\r' => out.write_all(br"\r")?,
+            '\t' => out.write_all(br"\t")?,
+            ch if ch.is_control() => write!(out, r"\u{:04x}", ch as u32)?,
+            ch => write!(out, "{ch}")?,
+        }
+    }
+    out.write_all(b"\"")
+}
+
+// casts get shared, so whatever was copied (or asked for) through OSC 52 stays out of them
+fn without_clipboard(data: &[u8]) -> Cow<'_, [u8]> {
+    const START: &[u8] = b"\x1b]52;";
+    let find = |data: &[u8]| data.windows(START.len()).position(|w| w == START);
+
+    let Some(mut start) = find(data) else {
+        return Cow::Borrowed(data);
+    };
+
+    let mut out = Vec::with_capacity(data.len());
+    let mut rest = data;
+    loop {
+        out.extend_from_slice(&rest[..start]);
+        let body = &rest[start + START.len()..];
+        // an unterminated sequence runs to the end of the chunk
+        let end = match body.iter().position(|&c| c == 0x07 || c == 0x1b) {
+            Some(end) if body[end] == 0x07 => end + 1,
+            Some(end) => (end + 2).min(body.len()),
+            None => body.len(),
+        };
+        rest = &body[end..];
+
+        match find(rest) {
+            Some(next) => start = next,
+            None => break,
+        }
+    }
+    out.extend_from_slice(rest);
+    Cow::Owned(out)
+}
+
+// renders a frame the same way the terminal would, then writes it to the cast as one event
+pub struct CastRenderer<'a, W: Write> {
+    term: TermRenderer<Vec<u8>>,
+    cast: &'a mut CastWriter<W>,
+    time: f32,
+}
+
+impl<'a, W: Write> CastRenderer<'a, W> {
+    pub fn new(cast: &'a mut CastWriter<W>, time: f32) -> Self {
+        Self {
+            term: TermRenderer::new(Vec::new()),
+            cast,
+            time,
+        }
+    }
+}
+
+impl<'a, W: Write> Renderer for CastRenderer<'a, W> {
+    fn begin(&mut self) -> std::io::Result<()> {
+        self.term.begin()
+    }
+
+    fn end(&mut self) -> std::io::Result<()> {
+        self.term.end()?;
+        let data = std::mem::take(self.term.get_mut());
+        self.cast.output(self.time, &data)
+    }
+
+    fn move_to(&mut self, pos: Pos2) -> std::io::Result<()> {
+        self.term.move_to(pos)
+    }
+
+    fn write(&mut self, ch: char) -> std::io::Result<()> {
+        self.term.write(ch)
+    }
+
+    fn write_glyph(&mut self, glyph: &str, width: i32) -> std::io::Result<()> {
+        self.term.write_glyph(glyph, width)
+    }
+
+    fn move_relative(&mut self, from: Pos2, to: Pos2) -> std::io::Result<()> {
+        self.term.move_relative(from, to)
+    }
+
+    fn erase_chars(&mut self, count: usize) -> std::io::Result<()> {
+        self.term.erase_chars(count)
+    }
+
+    fn erase_line(&mut self) -> std::io::Result<()> {
+        self.term.erase_line()
+    }
+
+    fn repeat(&mut self, glyph: &str, width: i32, count: usize) -> std::io::Result<()> {
+        self.term.repeat(glyph, width, count)
+    }
+
+    fn can_scroll(&self) -> bool {
+        self.term.can_scroll()
+    }
+
+    fn scroll(&mut self, top: i32, bottom: i32, rows: i32) -> std::io::Result<()> {
+        self.term.scroll(top, bottom, rows)
+    }
+
+    fn set_fg(&mut self, rgb: Rgba) -> std::io::Result<()> {
+        self.term.set_fg(rgb)
+    }
+
+    fn set_bg(&mut self, rgb: Rgba) -> std::io::Result<()> {
+        self.term.set_bg(rgb)
+    }
+
+    fn set_attr(&mut self, attr: Attribute) -> std::io::Result<()> {
+        self.term.set_attr(attr)
+    }
+
+    fn reset_fg(&mut self) -> std::io::Result<()> {
+        self.term.reset_fg()
+    }
+
+    fn reset_bg(&mut self) -> std::io::Result<()> {
+        self.term.reset_bg()
+    }
+
+    fn reset_attr(&mut self) -> std::io::Result<()> {
+        self.term.reset_attr()
+    }
+
+    fn set_underline_color(&mut self, rgb: Rgba) -> std::io::Result<()> {
+        self.term.set_underline_color(rgb)
+    }
+
+    fn reset_underline_color(&mut self) -> std::io::Result<()> {
+        self.term.reset_underline_color()
+    }
+
+    fn start_link(&mut self, link: Link) -> std::io::Result<()> {
+        self.term.start_link(link)
+    }
+
+    fn end_link(&mut self) -> std::io::Result<()> {
+        self.term.end_link()
+    }
+
+    fn draw_image(&mut self, image: &Image, rect: Rect) -> std::io::Result<()> {
+        self.term.draw_image(image, rect)
+    }
+
+    fn remove_image(&mut self, image: &Image, rect: Rect) -> std::io::Result<()> {
+        self.term.remove_image(image, rect)
+    }
+
+    fn load_image(&mut self, image: &Image) -> std::io::Result<()> {
+        self.term.load_image(image)
+    }
+
+    fn unload_image(&mut self, image: &Image) -> std::io::Result<()> {
+        self.term.unload_image(image)
+    }
+
+    fn show_cursor(&mut self) -> std::io::Result<()> {
+        self.term.show_cursor()
+    }
+
+    fn hide_cursor(&mut self) -> std::io::Result<()> {
+        self.term.hide_cursor()
+    }
+
+    fn set_cursor_shape(&mut self, shape: CursorShape, blinking: bool) -> std::io::Result<()> {
+        self.term.set_cursor_shape(shape, blinking)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc, time::Duration};
+
+    use super::*;
+    use crate::{debug::Harness, geom::vec2, widgets::label};
+
+    fn cast(data: &[u8]) -> String {
+        let mut cast =
+            CastWriter::new(Vec::new(), Vec2::new(10, 4), None, ColorDepth::Ansi16).unwrap();
+        cast.output(0.0, data).unwrap();
+        let out = String::from_utf8(cast.out).unwrap();
+        out.lines().nth(1).unwrap_or_default().to_string()
+    }
+
+    #[test]
+    fn clipboard_is_left_out() {
+        assert_eq!(
+            cast(b"a\x1b]52;c;c2VjcmV0\x07b"),
+            r#"[0.000000, "o", "ab"]"#
+        );
+        assert_eq!(cast(b"a\x1b]52;c;?\x1b\\b"), r#"[0.000000, "o", "ab"]"#);
+        assert_eq!(
+            cast(b"\x1b]52;c;YQ==\x07x\x1b]52;c;?\x07"),
+            r#"[0.000000, "o", "x"]"#
+        );
+        assert_eq!(cast(b"\x1b]52;c;?\x07"), "");
+    }
+
+    #[test]
+    fn other_output_is_kept() {
+        assert_eq!(
+            cast(b"\x1b]0;title\x07hi"),
+            r#"[0.000000, "o", "\u001b]0;title\u0007hi"]"#
+        );
+    }
+
+    #[test]
+    fn header() {
+        // the timestamp changes every run
+        let header = |term, depth| {
+            let cast = CastWriter::new(Vec::new(), vec2(10, 4), term, depth).unwrap();
+            let out = String::from_utf8(cast.out).unwrap();
+            let (start, rest) = out.split_once(r#""timestamp": "#).unwrap();
+            let (_, end) = rest.split_once(", ").unwrap();
+            format!("{start}{end}")
+        };
+
+        let table = [
+            (
+                Some("xterm-kitty"),
+                ColorDepth::TrueColor,
+                r#"{"env": {"TERM": "xterm-kitty", "COLORTERM": "truecolor"}}"#,
+            ),
+            (
+                Some("screen-256color"),
+                ColorDepth::Indexed256,
+                r#"{"env": {"TERM": "screen-256color"}}"#,
+            ),
+            (
+                Some("linux"),
+                ColorDepth::Ansi16,
+                r#"{"env": {"TERM": "linux"}}"#,
+            ),
+            (
+                None,
+                ColorDepth::Monochrome,
+                r#"{"env": {"NO_COLOR": "1"}}"#,
+            ),
+            (None, ColorDepth::Ansi16, r#"{"env": {}}"#),
+            (
+                Some("weird\"term"),
+                ColorDepth::Ansi16,
+                r#"{"env": {"TERM": "weird\"term"}}"#,
+            ),
+        ];
+
+        for (term, depth, env) in table {
+            let expected = format!(r#"{{"version": 2, "width": 10, "height": 4, {}"#, &env[1..]);
+            assert_eq!(
+                header(term, depth),
+                format!("{expected}\n"),
+                "{term:?} {depth:?}"
+            );
+        }
+    }
+
+    #[derive(Clone, Default)]
+    struct Shared(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn frames_and_resizes_are_timed_events() {
+        let out = Shared::default();
+        let mut harness = Harness::new(vec2(4, 1))
+            .frame_time(Duration::from_millis(250))
+            .cast(out.clone())
+            .unwrap();
+
+        harness.frame(|_| _ = label("hi"));
+        // nothing changed, so nothing is written
+        harness.frame(|_| _ = label("hi"));
+        harness.resize(vec2(5, 1)).frame(|_| _ = label("bye"));
+
+        let out = String::from_utf8(out.0.take()).unwrap();
+        let events = out.lines().skip(1).collect::<Vec<_>>();
+
+        assert_eq!(events.len(), 3, "{events:#?}");
+        assert!(
+            events[0].starts_with(r#"[0.000000, "o", ""#),
+            "{}",
+            events[0]
+        );
+        assert!(events[0].contains("hi"), "{}", events[0]);
+        assert_eq!(events[1], r#"[0.500000, "r", "5x1"]"#);
+        assert!(
+            events[2].starts_with(r#"[0.500000, "o", ""#),
+            "{}",
+            events[2]
+        );
+        assert!(events[2].contains("bye"), "{}", events[2]);
+    }
+}
@@ -1,13 +1,75 @@
+use unicode_segmentation::UnicodeSegmentation as _;
+use unicode_width::{UnicodeWidthChar as _, UnicodeWidthStr as _};
+
 use crate::color::Color;
 
-use super::Attribute;
+use super::{
+    intern::{Interner, Strings},
+    Attribute, Link,
+};
+
+pub(super) static CLUSTERS: Interner = Interner::new();
+
+// what a cell shows
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub(crate) enum Glyph {
+    Char(char),
+    // a grapheme cluster made of more than one char
+    Cluster { id: u32, width: u8 },
+    // the right half of the wide glyph to its left
+    Continuation,
+}
+
+impl Glyph {
+    pub(crate) fn new(grapheme: &str) -> Self {
+        let mut chars = grapheme.chars();
+        match (chars.next(), chars.next()) {
+            (Some(ch), None) => Self::Char(ch),
+            (None, _) => Self::Char(' '),
+            _ => Self::Cluster {
+                id: CLUSTERS.intern(grapheme),
+                // terminals don't agree on sequences of wide chars, but they're one glyph
+                width: grapheme.width().min(2) as u8,
+            },
+        }
+    }
+
+    // how many columns this takes up. continuations take up none, their glyph counts them
+    pub(crate) fn width(&self) -> i32 {
+        match self {
+            Self::Char(ch) => ch.width().unwrap_or(0).min(2) as i32,
+            Self::Cluster { width, .. } => *width as i32,
+            Self::Continuation => 0,
+        }
+    }
+
+    pub(crate) fn with_str<R>(&self, f: impl FnOnce(&str) -> R) -> R {
+        match self {
+            Self::Char(ch) => f(ch.encode_utf8(&mut [0; 4])),
+            Self::Cluster { .. } => self.with_str_in(&CLUSTERS.lock(), f),
+            Self::Continuation => f(""),
+        }
+    }
+
+    // a cluster that was freed while something still held on to it is left blank
+    pub(crate) fn with_str_in<R>(&self, clusters: &Strings, f: impl FnOnce(&str) -> R) -> R {
+        match self {
+            Self::Char(ch) => f(ch.encode_utf8(&mut [0; 4])),
+            Self::Cluster { id, width } => f(clusters.get(*id).unwrap_or(&"  "[..*width as usize])),
+            Self::Continuation => f(""),
+        }
+    }
+}
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub struct Cell {
-    pub(crate) char: char,
+    pub(crate) glyph: Glyph,
     pub(crate) fg: Color,
     pub(crate) bg: Color,
     pub(crate) attr: CellAttr,
+    // reset uses the foreground color
+    pub(crate) underline: Color,
+    pub(crate) link: Option<Link>,
 }
 
 impl Default for Cell {
@@ -24,21 +86,39 @@ impl From<char> for Cell {
 
 impl Cell {
     pub const EMPTY: Self = Self {
-        char: ' ',
+        glyph: Glyph::Char(' '),
         fg: Color::Reset,
         bg: Color::Reset,
         attr: CellAttr::Reset,
+        underline: Color::Reset,
+        link: None,
     };
 
     pub const fn new(char: char) -> Self {
         Self {
-            char,
+            glyph: Glyph::Char(char),
             fg: Color::Reset,
             bg: Color::Reuse,
             attr: CellAttr::Reset,
+            underline: Color::Reset,
+            link: None,
+        }
+    }
+
+    // the first grapheme cluster of the string, which can be more than one char
+    pub fn grapheme(grapheme: &str) -> Self {
+        let grapheme = grapheme.graphemes(true).next().unwrap_or(" ");
+        Self {
+            glyph: Glyph::new(grapheme),
+            ..Self::new(' ')
         }
     }
 
+    // the number of columns this takes up on the screen
+    pub fn width(&self) -> usize {
+        self.glyph.width() as usize
+    }
+
     pub fn fg(mut self, fg: impl Into<Color>) -> Self {
         self.fg = fg.into();
         self
@@ -49,13 +129,30 @@ impl Cell {
         self
     }
 
+    pub fn underline_color(mut self, underline: impl Into<Color>) -> Self {
+        self.underline = underline.into();
+        self
+    }
+
+    pub fn link(mut self, link: impl Into<Option<Link>>) -> Self {
+        self.link = link.into();
+        self
+    }
+
     pub fn attr(mut self, attr: impl Into<Option<Attribute>>) -> Self {
         self.attr = attr.into().map(CellAttr::Attr).unwrap_or(CellAttr::Reset);
         self
     }
+
+    pub(crate) fn add_attr(&mut self, attr: Attribute) {
+        self.attr = match self.attr {
+            CellAttr::Attr(old) => CellAttr::Attr(old | attr),
+            CellAttr::Reset => CellAttr::Attr(attr),
+        }
+    }
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub enum CellAttr {
     Reset,
     Attr(Attribute),
@@ -0,0 +1,30 @@
+use crate::geom::Pos2;
+
+// how the terminal draws its cursor
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum CursorShape {
+    #[default]
+    Block,
+    Bar,
+    Underline,
+}
+
+impl CursorShape {
+    // the parameter for DECSCUSR
+    pub(crate) const fn decscusr(&self, blinking: bool) -> u8 {
+        let n = match self {
+            Self::Block => 1,
+            Self::Underline => 3,
+            Self::Bar => 5,
+        };
+        n + !blinking as u8
+    }
+}
+
+// where a widget asked for the cursor to be shown this frame
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Cursor {
+    pub(crate) pos: Pos2,
+    pub(crate) shape: CursorShape,
+    pub(crate) blinking: bool,
+}
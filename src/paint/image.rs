@@ -0,0 +1,337 @@
+use std::{
+    io::Write,
+    path::Path,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    },
+};
+
+use crate::{
+    color::{palette_color, to_ansi256, Rgba},
+    geom::{pos2, vec2, Pos2, Rect, Vec2},
+};
+
+mod ppm;
+mod qoi;
+
+// rgba pixels, 4 bytes each. clones share the pixels and the id,
+// which is how the renderer knows it already sent an image
+#[derive(Clone)]
+pub struct Image {
+    id: u32,
+    size: Vec2,
+    pixels: Arc<[u8]>,
+}
+
+impl std::fmt::Debug for Image {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Image")
+            .field("id", &self.id)
+            .field("size", &self.size)
+            .finish()
+    }
+}
+
+impl PartialEq for Image {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Image {
+    // panics if there aren't 4 bytes for every pixel, `try_new` returns an error instead
+    pub fn new(width: u32, height: u32, pixels: impl Into<Arc<[u8]>>) -> Self {
+        match Self::try_new(width, height, pixels) {
+            Ok(image) => image,
+            Err(err) => panic!("{err}"),
+        }
+    }
+
+    pub fn try_new(width: u32, height: u32, pixels: impl Into<Arc<[u8]>>) -> std::io::Result<Self> {
+        static NEXT: AtomicU32 = AtomicU32::new(1);
+
+        if width > i32::MAX as u32 || height > i32::MAX as u32 {
+            return Err(invalid("the image is too large"));
+        }
+
+        let pixels = pixels.into();
+        let len = (width as usize)
+            .checked_mul(height as usize)
+            .and_then(|count| count.checked_mul(4));
+        if len != Some(pixels.len()) {
+            return Err(invalid("an image needs 4 bytes for every pixel"));
+        }
+
+        Ok(Self {
+            id: NEXT.fetch_add(1, Ordering::Relaxed),
+            size: vec2(width as i32, height as i32),
+            pixels,
+        })
+    }
+
+    pub fn from_fn(width: u32, height: u32, mut pixel: impl FnMut(u32, u32) -> Rgba) -> Self {
+        let mut pixels = Vec::with_capacity(width as usize * height as usize * 4);
+        for y in 0..height {
+            for x in 0..width {
+                let rgba = pixel(x, y);
+                pixels.extend([rgba.red(), rgba.green(), rgba.blue(), rgba.alpha()]);
+            }
+        }
+        Self::new(width, height, pixels)
+    }
+
+    // a ppm, pgm or qoi image, going by its contents rather than its name
+    pub fn decode(data: &[u8]) -> std::io::Result<Self> {
+        let (width, height, pixels) = match data {
+            [b'q', b'o', b'i', b'f', ..] => qoi::decode(data)?,
+            [b'P', b'2' | b'3' | b'5' | b'6', ..] => ppm::decode(data)?,
+            _ => return Err(invalid("only ppm, pgm and qoi images are supported")),
+        };
+        Self::try_new(width, height, pixels)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        Self::decode(&std::fs::read(path)?)
+    }
+
+    pub const fn size(&self) -> Vec2 {
+        self.size
+    }
+
+    pub const fn width(&self) -> u32 {
+        self.size.x as u32
+    }
+
+    pub const fn height(&self) -> u32 {
+        self.size.y as u32
+    }
+
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    pub fn pixel(&self, x: u32, y: u32) -> Rgba {
+        let i = (y as usize * self.width() as usize + x as usize) * 4;
+        let [r, g, b, a] = self.pixels[i..i + 4] else {
+            unreachable!()
+        };
+        Rgba::new(r, g, b).with_alpha(a)
+    }
+
+    // the pixel that ends up at `pos` when the image is stretched over `size`
+    pub(crate) fn sample(&self, pos: Pos2, size: Vec2) -> Rgba {
+        let x = pos.x as i64 * self.size.x as i64 / size.x.max(1) as i64;
+        let y = pos.y as i64 * self.size.y as i64 / size.y.max(1) as i64;
+        self.pixel(x as u32, y as u32)
+    }
+
+    pub(crate) const fn id(&self) -> u32 {
+        self.id
+    }
+}
+
+// how images get to the screen
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Graphics {
+    Kitty,
+    Sixel,
+    // drawn as colored cells
+    #[default]
+    HalfBlocks,
+}
+
+// an image covering a rect of cells, in surface coordinates
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Placement {
+    pub image: Image,
+    pub rect: Rect,
+}
+
+impl Image {
+    // the pixels are sent once, then placed as many times as they're needed
+    pub(crate) fn write_kitty(&self, out: &mut impl Write) -> std::io::Result<()> {
+        const CHUNK: usize = 4096;
+
+        let data = crate::base64::encode(&self.pixels);
+        let mut chunks = data.as_bytes().chunks(CHUNK).peekable();
+        let (w, h, id) = (self.size.x, self.size.y, self.id);
+
+        // errors aren't reported back to us
+        write!(out, "\x1b_Ga=t,f=32,s={w},v={h},i={id},q=2")?;
+        if chunks.peek().is_none() {
+            return out.write_all(b"\x1b\\");
+        }
+
+        let mut first = true;
+        while let Some(chunk) = chunks.next() {
+            let more = chunks.peek().is_some() as u8;
+            if first {
+                write!(out, ",m={more};")?;
+                first = false;
+            } else {
+                write!(out, "\x1b_Gm={more};")?;
+            }
+            out.write_all(chunk)?;
+            out.write_all(b"\x1b\\")?;
+        }
+        Ok(())
+    }
+
+    // kitty scales the image to fit the cells, and the cursor stays put.
+    // placing it in the same rect again replaces what was there
+    pub(crate) fn place_kitty(&self, out: &mut impl Write, rect: Rect) -> std::io::Result<()> {
+        let (id, p) = (self.id, placement_id(rect));
+        let (c, r) = (rect.width(), rect.height());
+        write!(out, "\x1b_Ga=p,i={id},p={p},c={c},r={r},C=1,q=2\x1b\\")
+    }
+
+    // only this placement goes away, the pixels are kept for the others
+    pub(crate) fn remove_kitty(&self, out: &mut impl Write, rect: Rect) -> std::io::Result<()> {
+        let (id, p) = (self.id, placement_id(rect));
+        write!(out, "\x1b_Ga=d,d=i,i={id},p={p},q=2\x1b\\")
+    }
+
+    pub(crate) fn free_kitty(&self, out: &mut impl Write) -> std::io::Result<()> {
+        write!(out, "\x1b_Ga=d,d=I,i={},q=2\x1b\\", self.id)
+    }
+
+    // stretched to `pixels` and mapped onto the 256 color palette
+    pub(crate) fn write_sixel(&self, out: &mut impl Write, pixels: Vec2) -> std::io::Result<()> {
+        let (w, h) = (pixels.x.max(0) as usize, pixels.y.max(0) as usize);
+        let indices: Vec<Option<u8>> = (0..w * h)
+            .map(|i| {
+                let rgba = self.sample(pos2((i % w) as i32, (i / w) as i32), pixels);
+                (rgba.alpha() >= 128).then(|| to_ansi256(rgba))
+            })
+            .collect();
+
+        // transparent pixels are left alone
+        write!(out, "\x1bP0;1;0q\"1;1;{w};{h}")?;
+
+        let mut used = [false; 256];
+        for &index in indices.iter().flatten() {
+            used[index as usize] = true;
+        }
+        for (index, _) in used.iter().enumerate().filter(|(_, used)| **used) {
+            let rgba = palette_color(index as u8);
+            let percent = |c: u8| c as u32 * 100 / 255;
+            let (r, g, b) = (
+                percent(rgba.red()),
+                percent(rgba.green()),
+                percent(rgba.blue()),
+            );
+            write!(out, "#{index};2;{r};{g};{b}")?;
+        }
+
+        // each band is 6 rows, drawn once for every color in it
+        for top in (0..h).step_by(6) {
+            let rows = top..(top + 6).min(h);
+            let mut colors = [false; 256];
+            for y in rows.clone() {
+                for &index in indices[y * w..(y + 1) * w].iter().flatten() {
+                    colors[index as usize] = true;
+                }
+            }
+
+            for (color, _) in colors.iter().enumerate().filter(|(_, used)| **used) {
+                write!(out, "#{color}")?;
+                let mut run = (0u8, 0usize);
+                for x in 0..w {
+                    let bits = rows.clone().fold(0u8, |bits, y| {
+                        let set = indices[y * w + x] == Some(color as u8);
+                        bits | ((set as u8) << (y - top))
+                    });
+                    if bits != run.0 && run.1 > 0 {
+                        write_sixel_run(out, run)?;
+                        run.1 = 0;
+                    }
+                    run = (bits, run.1 + 1);
+                }
+                write_sixel_run(out, run)?;
+                out.write_all(b"$")?;
+            }
+            out.write_all(b"-")?;
+        }
+
+        out.write_all(b"\x1b\\")
+    }
+}
+
+// an image is only placed once in a rect, so that's enough to tell its placements apart
+fn placement_id(rect: Rect) -> u32 {
+    use std::hash::{Hash as _, Hasher as _};
+    let mut hasher = std::hash::DefaultHasher::new();
+    rect.hash(&mut hasher);
+    (hasher.finish() as u32).max(1)
+}
+
+fn write_sixel_run(out: &mut impl Write, (bits, count): (u8, usize)) -> std::io::Result<()> {
+    let ch = (b'?' + bits) as char;
+    match count {
+        0 => Ok(()),
+        1..=3 => (0..count).try_for_each(|_| write!(out, "{ch}")),
+        _ => write!(out, "!{count}{ch}"),
+    }
+}
+
+fn invalid(error: &str) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const END: [u8; 8] = [0, 0, 0, 0, 0, 0, 0, 1];
+
+    fn qoi(width: u32, height: u32, body: &[u8]) -> Vec<u8> {
+        let mut data = b"qoif".to_vec();
+        data.extend(width.to_be_bytes());
+        data.extend(height.to_be_bytes());
+        data.extend([4, 0]);
+        data.extend(body);
+        data.extend(END);
+        data
+    }
+
+    #[test]
+    fn decode_qoi() {
+        let image = Image::decode(&qoi(2, 1, &[0xff, 1, 2, 3, 4, 0xc0])).unwrap();
+        assert_eq!(image.size(), vec2(2, 1));
+        assert_eq!(image.pixels(), [1, 2, 3, 4, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn decode_ppm() {
+        let image = Image::decode(b"P3\n# a comment\n2 1 255\n255 0 0  0 0 255\n").unwrap();
+        assert_eq!(image.pixels(), [255, 0, 0, 255, 0, 0, 255, 255]);
+
+        let image = Image::decode(b"P5 1 1 65535\n\xff\xff").unwrap();
+        assert_eq!(image.pixels(), [255, 255, 255, 255]);
+    }
+
+    #[test]
+    fn wrong_pixel_count() {
+        assert!(Image::try_new(2, 2, vec![0; 16]).is_ok());
+        let err = Image::try_new(2, 2, vec![0; 12]).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        assert!(Image::try_new(u32::MAX, u32::MAX, vec![]).is_err());
+    }
+
+    #[test]
+    fn oversized_headers_are_rejected() {
+        let err = Image::decode(&qoi(65535, 65535, &[])).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+
+        // small enough for the limit, but there's nothing to decode it from
+        let err = Image::decode(&qoi(20_000, 20_000, &[])).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+
+        let err = Image::decode(b"P6 4294967295 4294967295 255\n").unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+
+        let err = Image::decode(b"P3 4294967295 4294967295 255\n1 2 3").unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+}
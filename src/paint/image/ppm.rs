@@ -0,0 +1,91 @@
+use super::invalid;
+
+// the netpbm formats with color (P3, P6) or gray (P2, P5) pixels, in ascii or binary
+pub(super) fn decode(data: &[u8]) -> std::io::Result<(u32, u32, Vec<u8>)> {
+    let (channels, binary) = match data.get(..2) {
+        Some(b"P2") => (1, false),
+        Some(b"P3") => (3, false),
+        Some(b"P5") => (1, true),
+        Some(b"P6") => (3, true),
+        _ => return Err(invalid("not a ppm or pgm image")),
+    };
+
+    let mut header = Header { data, pos: 2 };
+    let width = header.number()?;
+    let height = header.number()?;
+    let max = header.number()?;
+    if max == 0 || max > u16::MAX as u32 {
+        return Err(invalid(
+            "the max value of a ppm image must be between 1 and 65535",
+        ));
+    }
+
+    let count = (width as usize)
+        .checked_mul(height as usize)
+        .and_then(|pixels| pixels.checked_mul(channels))
+        .ok_or_else(|| invalid("the ppm image is too large"))?;
+    let samples: Vec<u32> = if binary {
+        // exactly one whitespace byte separates the header from the pixels
+        let body = data.get(header.pos + 1..).unwrap_or_default();
+        let wide = max > 255;
+        let size = if wide { 2 } else { 1 };
+        if body.len() / size < count {
+            return Err(invalid("the ppm image ended early"));
+        }
+        match wide {
+            true => body[..count * 2]
+                .chunks_exact(2)
+                .map(|c| u16::from_be_bytes([c[0], c[1]]) as u32)
+                .collect(),
+            false => body[..count].iter().map(|&c| c as u32).collect(),
+        }
+    } else {
+        (0..count)
+            .map(|_| header.number())
+            .collect::<Result<_, _>>()?
+    };
+
+    let scale = |sample: u32| (sample.min(max) * 255 / max) as u8;
+    let mut pixels = Vec::with_capacity(samples.len() / channels * 4);
+    for pixel in samples.chunks_exact(channels) {
+        match *pixel {
+            [gray] => pixels.extend([scale(gray), scale(gray), scale(gray), 255]),
+            [r, g, b] => pixels.extend([scale(r), scale(g), scale(b), 255]),
+            _ => unreachable!(),
+        }
+    }
+    Ok((width, height, pixels))
+}
+
+struct Header<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl Header<'_> {
+    // numbers are separated by whitespace, and comments run to the end of the line
+    fn number(&mut self) -> std::io::Result<u32> {
+        loop {
+            match self.data.get(self.pos) {
+                Some(c) if c.is_ascii_whitespace() => self.pos += 1,
+                Some(b'#') => {
+                    while !matches!(self.data.get(self.pos), Some(b'\n') | None) {
+                        self.pos += 1
+                    }
+                }
+                Some(..) => break,
+                None => return Err(invalid("the ppm image ended early")),
+            }
+        }
+
+        let start = self.pos;
+        while self.data.get(self.pos).is_some_and(u8::is_ascii_digit) {
+            self.pos += 1;
+        }
+
+        std::str::from_utf8(&self.data[start..self.pos])
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .ok_or_else(|| invalid("expected a number in the ppm image"))
+    }
+}
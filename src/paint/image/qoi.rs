@@ -0,0 +1,71 @@
+use super::invalid;
+
+// https://qoiformat.org/qoi-specification.pdf
+pub(super) fn decode(data: &[u8]) -> std::io::Result<(u32, u32, Vec<u8>)> {
+    const RGB: u8 = 0xfe;
+    const RGBA: u8 = 0xff;
+    // the limit the specification puts on decoders
+    const MAX_PIXELS: u64 = 400_000_000;
+    const MAX_RUN: usize = 62;
+
+    let [b'q', b'o', b'i', b'f', w0, w1, w2, w3, h0, h1, h2, h3, _channels, _colorspace, body @ ..] =
+        data
+    else {
+        return Err(invalid("not a qoi image"));
+    };
+
+    let width = u32::from_be_bytes([*w0, *w1, *w2, *w3]);
+    let height = u32::from_be_bytes([*h0, *h1, *h2, *h3]);
+    let count = width as u64 * height as u64;
+    if count > MAX_PIXELS {
+        return Err(invalid("the qoi image is too large"));
+    }
+    let count = count as usize;
+
+    // the header can claim anything, but a byte can't make more than a run's worth of pixels
+    let mut pixels = Vec::with_capacity(count.min(body.len().saturating_mul(MAX_RUN)) * 4);
+    let mut index = [[0u8; 4]; 64];
+    let mut px = [0, 0, 0, 255];
+    let mut run = 0;
+
+    let mut bytes = body.iter().copied();
+    let mut next = || {
+        bytes
+            .next()
+            .ok_or_else(|| invalid("the qoi image ended early"))
+    };
+
+    while pixels.len() < count * 4 {
+        if run > 0 {
+            run -= 1;
+        } else {
+            match next()? {
+                RGB => px = [next()?, next()?, next()?, px[3]],
+                RGBA => px = [next()?, next()?, next()?, next()?],
+                op => match op >> 6 {
+                    0b00 => px = index[op as usize & 0x3f],
+                    0b01 => {
+                        let d = |shift: u8| ((op >> shift) & 0x03).wrapping_sub(2);
+                        px[0] = px[0].wrapping_add(d(4));
+                        px[1] = px[1].wrapping_add(d(2));
+                        px[2] = px[2].wrapping_add(d(0));
+                    }
+                    0b10 => {
+                        let dg = (op & 0x3f).wrapping_sub(32);
+                        let rest = next()?;
+                        px[0] = px[0].wrapping_add(dg.wrapping_add(rest >> 4).wrapping_sub(8));
+                        px[1] = px[1].wrapping_add(dg);
+                        px[2] = px[2].wrapping_add(dg.wrapping_add(rest & 0x0f).wrapping_sub(8));
+                    }
+                    _ => run = op & 0x3f,
+                },
+            }
+
+            let [r, g, b, a] = px.map(|c| c as usize);
+            index[(r * 3 + g * 5 + b * 7 + a * 11) % 64] = px;
+        }
+        pixels.extend(px);
+    }
+
+    Ok((width, height, pixels))
+}
@@ -0,0 +1,194 @@
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock};
+
+// strings that cells refer to by id, so the cells can stay Copy.
+//
+// nothing owns an id, so once there are enough strings every surface is asked which ones
+// it still holds the next time it renders. whatever none of them held, and that wasn't
+// interned again in the meantime, is freed. ids aren't reused, a freed one just isn't found
+pub(crate) struct Interner(OnceLock<Mutex<Strings>>);
+
+#[derive(Default)]
+pub(crate) struct Strings {
+    // each string, with the collection it was last seen in
+    list: ahash::HashMap<u32, (Arc<str>, u64)>,
+    ids: ahash::HashMap<Arc<str>, u32>,
+    next: u32,
+    epoch: u64,
+    // the surfaces, and whether they've said what they hold for this collection
+    holders: ahash::HashMap<u64, bool>,
+    collecting: bool,
+    // how many were left after the last collection
+    kept: usize,
+}
+
+impl Interner {
+    // nothing is freed below this many strings, and after that not until there are twice as many
+    const MIN_COLLECT: usize = 1024;
+
+    pub(crate) const fn new() -> Self {
+        Self(OnceLock::new())
+    }
+
+    pub(crate) fn intern(&self, s: &str) -> u32 {
+        let mut strings = self.lock();
+        if let Some(&id) = strings.ids.get(s) {
+            let epoch = strings.epoch;
+            if let Some((_, seen)) = strings.list.get_mut(&id) {
+                *seen = epoch;
+            }
+            return id;
+        }
+
+        if !strings.collecting && strings.list.len() >= Self::MIN_COLLECT.max(strings.kept * 2) {
+            strings.start_collecting();
+        }
+
+        let s: Arc<str> = Arc::from(s);
+        let id = strings.next;
+        strings.next = id.wrapping_add(1);
+        let epoch = strings.epoch;
+        strings.list.insert(id, (Arc::clone(&s), epoch));
+        strings.ids.insert(s, id);
+        id
+    }
+
+    pub(crate) fn get(&self, id: u32) -> Option<Arc<str>> {
+        self.lock().list.get(&id).map(|(s, _)| Arc::clone(s))
+    }
+
+    // for looking up a lot of them at once
+    pub(crate) fn lock(&self) -> MutexGuard<'_, Strings> {
+        let strings = self.0.get_or_init(Mutex::default);
+        strings.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    pub(crate) fn add_holder(&self, holder: u64) {
+        // cells made before it can still end up on it, so it has to say what it holds too
+        let mut strings = self.lock();
+        let held = !strings.collecting;
+        strings.holders.insert(holder, held);
+    }
+
+    pub(crate) fn remove_holder(&self, holder: u64) {
+        let mut strings = self.lock();
+        strings.holders.remove(&holder);
+        strings.maybe_collect();
+    }
+
+    // `ids` is only asked for while collecting, and only once per holder
+    pub(crate) fn hold<I>(&self, holder: u64, ids: impl FnOnce() -> I)
+    where
+        I: Iterator<Item = u32>,
+    {
+        let mut strings = self.lock();
+        if !strings.collecting || strings.holders.get(&holder) != Some(&false) {
+            return;
+        }
+
+        let epoch = strings.epoch;
+        for id in ids() {
+            if let Some((_, seen)) = strings.list.get_mut(&id) {
+                *seen = epoch;
+            }
+        }
+        strings.holders.insert(holder, true);
+        strings.maybe_collect();
+    }
+}
+
+impl Strings {
+    pub(crate) fn get(&self, id: u32) -> Option<&str> {
+        self.list.get(&id).map(|(s, _)| &**s)
+    }
+
+    fn start_collecting(&mut self) {
+        // without any surfaces there's no telling what's still around
+        if self.holders.is_empty() {
+            self.kept = self.list.len();
+            return;
+        }
+
+        self.epoch += 1;
+        self.collecting = true;
+        self.holders.values_mut().for_each(|held| *held = false);
+    }
+
+    fn maybe_collect(&mut self) {
+        if !self.collecting || !self.holders.values().all(|&held| held) {
+            return;
+        }
+
+        let Self {
+            list, ids, epoch, ..
+        } = self;
+        list.retain(|_, (s, seen)| {
+            let keep = *seen == *epoch;
+            if !keep {
+                ids.remove(s);
+            }
+            keep
+        });
+
+        self.collecting = false;
+        self.kept = self.list.len();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fill(interner: &Interner, prefix: &str) -> Vec<u32> {
+        (0..=Interner::MIN_COLLECT)
+            .map(|i| interner.intern(&format!("{prefix}{i}")))
+            .collect()
+    }
+
+    #[test]
+    fn nothing_is_freed_without_holders() {
+        let interner = Interner::new();
+        let ids = fill(&interner, "a");
+        assert!(ids.iter().all(|&id| interner.get(id).is_some()));
+    }
+
+    #[test]
+    fn strings_nobody_holds_are_freed() {
+        let interner = Interner::new();
+        interner.add_holder(1);
+        interner.add_holder(2);
+
+        let ids = fill(&interner, "a");
+        let (held, rest) = ids.split_at(2);
+
+        // the collection waits for every holder
+        interner.hold(1, || held.iter().copied());
+        assert!(interner.get(rest[0]).is_some());
+
+        // interning again counts as being held
+        assert_eq!(interner.intern("a2"), rest[0]);
+        interner.hold(2, std::iter::empty);
+
+        assert_eq!(interner.get(held[0]).as_deref(), Some("a0"));
+        assert_eq!(interner.get(rest[0]).as_deref(), Some("a2"));
+        assert_eq!(interner.get(rest[1]), None);
+
+        // and freed ids aren't handed out again
+        let id = interner.intern("a3");
+        assert_ne!(id, rest[1]);
+        assert!(!ids.contains(&id));
+    }
+
+    #[test]
+    fn removing_the_last_holder_finishes_the_collection() {
+        let interner = Interner::new();
+        interner.add_holder(1);
+        interner.add_holder(2);
+
+        let ids = fill(&interner, "b");
+        interner.hold(1, std::iter::empty);
+        assert!(interner.get(ids[0]).is_some());
+
+        interner.remove_holder(2);
+        assert!(interner.get(ids[0]).is_none());
+    }
+}
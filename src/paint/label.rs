@@ -1,7 +1,13 @@
 use std::{borrow::Cow, rc::Rc};
 
+use unicode_segmentation::UnicodeSegmentation as _;
+use unicode_width::{UnicodeWidthChar as _, UnicodeWidthStr as _};
+
 use crate::geom::{vec2, Vec2};
 
+// tabs in labels are expanded to the next multiple of this
+pub(crate) const TAB_WIDTH: i32 = 4;
+
 pub trait Label: std::fmt::Debug {
     type Static: Label + 'static;
     fn into_static(self) -> Self::Static;
@@ -88,7 +94,7 @@ impl Label for char {
     }
 
     fn size(&self) -> Vec2 {
-        vec2(1, 1)
+        vec2(self.width().unwrap_or(0) as i32, 1)
     }
 
     fn chars(&self) -> impl Iterator<Item = char> {
@@ -279,14 +285,25 @@ fn split_f32(f: f32) -> (usize, usize) {
 fn size_of_str(s: &str) -> Vec2 {
     let mut size = vec2(0, 1);
     let mut max_x = 0;
-    for ch in s.chars() {
-        if ch == '\n' {
-            size.y += 1;
-            size.x = std::mem::take(&mut max_x).max(size.x);
-            continue;
+    for grapheme in s.graphemes(true) {
+        match grapheme {
+            "\n" | "\r\n" => {
+                size.y += 1;
+                size.x = std::mem::take(&mut max_x).max(size.x);
+            }
+            "\t" => max_x = next_tab_stop(max_x),
+            grapheme => max_x += grapheme_width(grapheme),
         }
-        max_x += 1;
     }
     size.x = size.x.max(max_x);
     size
 }
+
+pub(crate) const fn next_tab_stop(x: i32) -> i32 {
+    (x / TAB_WIDTH + 1) * TAB_WIDTH
+}
+
+// the same width a cell would give it
+pub(crate) fn grapheme_width(grapheme: &str) -> i32 {
+    grapheme.width().min(2) as i32
+}
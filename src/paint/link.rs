@@ -0,0 +1,31 @@
+use std::sync::Arc;
+
+use super::intern::Interner;
+
+pub(super) static LINKS: Interner = Interner::new();
+
+// a hyperlink target. cells only carry an id so they can stay Copy, which means a link
+// kept aside has to be on the screen (or made again) to stay around
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Link(u32);
+
+impl Link {
+    pub fn new(url: &str) -> Self {
+        Self(LINKS.intern(url))
+    }
+
+    // this is empty once the link has been freed
+    pub fn url(&self) -> Arc<str> {
+        LINKS.get(self.0).unwrap_or_else(|| Arc::from(""))
+    }
+
+    pub(crate) const fn id(&self) -> u32 {
+        self.0
+    }
+}
+
+impl From<&str> for Link {
+    fn from(value: &str) -> Self {
+        Self::new(value)
+    }
+}
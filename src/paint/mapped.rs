@@ -1,3 +1,5 @@
+use std::any::Any;
+
 use crate::{
     geom::{Pos2, Vec2},
     paint::{Cell, Label, Styled},
@@ -39,6 +41,23 @@ impl<T: Label> MappedStyle<T, fn(Pos2, Cell) -> Cell> {
     }
 }
 
+impl<T: Label, F: Fn(Pos2, Cell) -> Cell + 'static> MappedStyle<T, F> {
+    // closures that capture something can't be compared, so those are never the same
+    pub(crate) fn same_as(&self, other: &Self) -> bool {
+        let map = || {
+            if std::mem::size_of::<F>() == 0 {
+                return true;
+            }
+            let any = |map| (map as &dyn Any).downcast_ref::<fn(Pos2, Cell) -> Cell>();
+            matches!(
+                (any(&self.map), any(&other.map)),
+                (Some(&a), Some(&b)) if std::ptr::fn_addr_eq(a, b)
+            )
+        };
+        self.label.same_as(&other.label) && map()
+    }
+}
+
 impl<T: Label, F: Fn(Pos2, Cell) -> Cell> MappedStyle<T, F> {
     pub fn into_static(self) -> MappedStyle<T::Static, F> {
         MappedStyle {
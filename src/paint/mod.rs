@@ -1,5 +1,18 @@
 mod cell;
 pub use cell::Cell;
+pub(crate) use cell::CellAttr;
+
+mod cursor;
+pub(crate) use cursor::Cursor;
+pub use cursor::CursorShape;
+
+mod intern;
+
+mod link;
+pub use link::Link;
+
+mod image;
+pub use image::{Graphics, Image};
 
 mod attribute;
 pub use attribute::Attribute;
@@ -22,4 +35,14 @@ pub(crate) use buffer::Buffer;
 mod renderer;
 pub(crate) use renderer::{DebugRenderer, Renderer, TermRenderer};
 
+mod screenshot;
+pub(crate) use screenshot::Screenshot;
+
+mod cast;
+pub(crate) use cast::{CastRenderer, CastWriter};
+
+mod stats;
+pub use stats::RenderStats;
+pub(crate) use stats::StatsRenderer;
+
 pub mod shape;
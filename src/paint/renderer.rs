@@ -1,7 +1,10 @@
 use std::fmt::Write as _;
 
-use super::Attribute;
-use crate::{color::Rgba, geom::Pos2};
+use super::{Attribute, CursorShape, Graphics, Image, Link};
+use crate::{
+    color::{to_ansi16, to_ansi256, ColorDepth, Rgba},
+    geom::{vec2, Pos2, Rect, Vec2},
+};
 
 pub trait Renderer {
     fn begin(&mut self) -> std::io::Result<()>;
@@ -10,6 +13,69 @@ pub trait Renderer {
     fn move_to(&mut self, pos: Pos2) -> std::io::Result<()>;
     fn write(&mut self, ch: char) -> std::io::Result<()>;
 
+    // a grapheme cluster that takes up `width` columns
+    fn write_glyph(&mut self, glyph: &str, width: i32) -> std::io::Result<()> {
+        let _ = width;
+        glyph.chars().try_for_each(|ch| self.write(ch))
+    }
+
+    // relative movement, these never scroll the screen
+    fn move_up(&mut self, rows: i32) -> std::io::Result<()> {
+        let _ = rows;
+        Ok(())
+    }
+
+    fn move_down(&mut self, rows: i32) -> std::io::Result<()> {
+        let _ = rows;
+        Ok(())
+    }
+
+    fn move_to_column(&mut self, col: i32) -> std::io::Result<()> {
+        let _ = col;
+        Ok(())
+    }
+
+    fn clear_below(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+
+    // the same as `move_to`, but renderers that can get there in fewer bytes from `from` should
+    fn move_relative(&mut self, from: Pos2, to: Pos2) -> std::io::Result<()> {
+        let _ = from;
+        self.move_to(to)
+    }
+
+    // blanks `count` cells with the current background, the cursor stays where it is
+    fn erase_chars(&mut self, count: usize) -> std::io::Result<()> {
+        let _ = count;
+        Ok(())
+    }
+
+    // the same, up to the end of the line
+    fn erase_line(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+
+    fn repeat(&mut self, glyph: &str, width: i32, count: usize) -> std::io::Result<()> {
+        (0..count).try_for_each(|_| self.write_glyph(glyph, width))
+    }
+
+    fn can_scroll(&self) -> bool {
+        false
+    }
+
+    // moves the rows from `top` to `bottom` up by `rows`, or down if its negative.
+    // the rows that come in are blank, and the cursor ends up somewhere unknown
+    fn scroll(&mut self, top: i32, bottom: i32, rows: i32) -> std::io::Result<()> {
+        let _ = (top, bottom, rows);
+        Ok(())
+    }
+
+    // colors are quantized to this before they are set
+    fn color_depth(&self) -> ColorDepth {
+        ColorDepth::TrueColor
+    }
+
     fn set_fg(&mut self, rgb: Rgba) -> std::io::Result<()>;
     fn set_bg(&mut self, rgb: Rgba) -> std::io::Result<()>;
     fn set_attr(&mut self, attr: Attribute) -> std::io::Result<()>;
@@ -18,6 +84,49 @@ pub trait Renderer {
     fn reset_bg(&mut self) -> std::io::Result<()>;
     fn reset_attr(&mut self) -> std::io::Result<()>;
 
+    // reset makes the underline the same color as the text
+    fn set_underline_color(&mut self, rgb: Rgba) -> std::io::Result<()> {
+        let _ = rgb;
+        Ok(())
+    }
+
+    fn reset_underline_color(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+
+    // everything written until end_link is part of the link
+    fn start_link(&mut self, link: Link) -> std::io::Result<()> {
+        let _ = link;
+        Ok(())
+    }
+
+    fn end_link(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+
+    // the cursor is already at the top left of `rect`
+    fn draw_image(&mut self, image: &Image, rect: Rect) -> std::io::Result<()> {
+        let _ = (image, rect);
+        Ok(())
+    }
+
+    fn remove_image(&mut self, image: &Image, rect: Rect) -> std::io::Result<()> {
+        let _ = (image, rect);
+        Ok(())
+    }
+
+    // sends the pixels before the image is first drawn, so placing it again doesn't have to
+    fn load_image(&mut self, image: &Image) -> std::io::Result<()> {
+        let _ = image;
+        Ok(())
+    }
+
+    // the image isn't drawn anywhere anymore
+    fn unload_image(&mut self, image: &Image) -> std::io::Result<()> {
+        let _ = image;
+        Ok(())
+    }
+
     fn capture_mouse(&mut self) -> std::io::Result<()> {
         Ok(())
     }
@@ -26,6 +135,30 @@ pub trait Renderer {
         Ok(())
     }
 
+    fn enable_bracketed_paste(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+
+    fn enable_focus_reporting(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+
+    fn disable_focus_reporting(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+
+    fn disable_bracketed_paste(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+
+    fn push_keyboard_enhancement(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+
+    fn pop_keyboard_enhancement(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+
     fn show_cursor(&mut self) -> std::io::Result<()> {
         Ok(())
     }
@@ -34,6 +167,20 @@ pub trait Renderer {
         Ok(())
     }
 
+    fn set_cursor_shape(&mut self, shape: CursorShape, blinking: bool) -> std::io::Result<()> {
+        let _ = (shape, blinking);
+        Ok(())
+    }
+
+    // back to whatever the user configured their terminal to use
+    fn reset_cursor_shape(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+
+    fn clear_screen(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+
     fn enter_alt_screen(&mut self) -> std::io::Result<()> {
         Ok(())
     }
@@ -54,15 +201,325 @@ pub trait Renderer {
         let _ = title;
         Ok(())
     }
+
+    fn set_clipboard(&mut self, data: &str) -> std::io::Result<()> {
+        let _ = data;
+        Ok(())
+    }
+
+    fn request_clipboard(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+// renders to both, one after the other
+impl<A: Renderer, B: Renderer> Renderer for (A, B) {
+    fn begin(&mut self) -> std::io::Result<()> {
+        self.0.begin()?;
+        self.1.begin()
+    }
+
+    fn end(&mut self) -> std::io::Result<()> {
+        self.0.end()?;
+        self.1.end()
+    }
+
+    fn move_to(&mut self, pos: Pos2) -> std::io::Result<()> {
+        self.0.move_to(pos)?;
+        self.1.move_to(pos)
+    }
+
+    fn write(&mut self, ch: char) -> std::io::Result<()> {
+        self.0.write(ch)?;
+        self.1.write(ch)
+    }
+
+    fn write_glyph(&mut self, glyph: &str, width: i32) -> std::io::Result<()> {
+        self.0.write_glyph(glyph, width)?;
+        self.1.write_glyph(glyph, width)
+    }
+
+    fn move_up(&mut self, rows: i32) -> std::io::Result<()> {
+        self.0.move_up(rows)?;
+        self.1.move_up(rows)
+    }
+
+    fn move_down(&mut self, rows: i32) -> std::io::Result<()> {
+        self.0.move_down(rows)?;
+        self.1.move_down(rows)
+    }
+
+    fn move_to_column(&mut self, col: i32) -> std::io::Result<()> {
+        self.0.move_to_column(col)?;
+        self.1.move_to_column(col)
+    }
+
+    fn clear_below(&mut self) -> std::io::Result<()> {
+        self.0.clear_below()?;
+        self.1.clear_below()
+    }
+
+    fn move_relative(&mut self, from: Pos2, to: Pos2) -> std::io::Result<()> {
+        self.0.move_relative(from, to)?;
+        self.1.move_relative(from, to)
+    }
+
+    fn erase_chars(&mut self, count: usize) -> std::io::Result<()> {
+        self.0.erase_chars(count)?;
+        self.1.erase_chars(count)
+    }
+
+    fn erase_line(&mut self) -> std::io::Result<()> {
+        self.0.erase_line()?;
+        self.1.erase_line()
+    }
+
+    fn repeat(&mut self, glyph: &str, width: i32, count: usize) -> std::io::Result<()> {
+        self.0.repeat(glyph, width, count)?;
+        self.1.repeat(glyph, width, count)
+    }
+
+    // the screens would end up different if only one of them scrolled
+    fn can_scroll(&self) -> bool {
+        self.0.can_scroll() && self.1.can_scroll()
+    }
+
+    fn scroll(&mut self, top: i32, bottom: i32, rows: i32) -> std::io::Result<()> {
+        self.0.scroll(top, bottom, rows)?;
+        self.1.scroll(top, bottom, rows)
+    }
+
+    // the first one decides
+    fn color_depth(&self) -> ColorDepth {
+        self.0.color_depth()
+    }
+
+    fn set_fg(&mut self, rgb: Rgba) -> std::io::Result<()> {
+        self.0.set_fg(rgb)?;
+        self.1.set_fg(rgb)
+    }
+
+    fn set_bg(&mut self, rgb: Rgba) -> std::io::Result<()> {
+        self.0.set_bg(rgb)?;
+        self.1.set_bg(rgb)
+    }
+
+    fn set_attr(&mut self, attr: Attribute) -> std::io::Result<()> {
+        self.0.set_attr(attr)?;
+        self.1.set_attr(attr)
+    }
+
+    fn reset_fg(&mut self) -> std::io::Result<()> {
+        self.0.reset_fg()?;
+        self.1.reset_fg()
+    }
+
+    fn reset_bg(&mut self) -> std::io::Result<()> {
+        self.0.reset_bg()?;
+        self.1.reset_bg()
+    }
+
+    fn reset_attr(&mut self) -> std::io::Result<()> {
+        self.0.reset_attr()?;
+        self.1.reset_attr()
+    }
+
+    fn set_underline_color(&mut self, rgb: Rgba) -> std::io::Result<()> {
+        self.0.set_underline_color(rgb)?;
+        self.1.set_underline_color(rgb)
+    }
+
+    fn reset_underline_color(&mut self) -> std::io::Result<()> {
+        self.0.reset_underline_color()?;
+        self.1.reset_underline_color()
+    }
+
+    fn start_link(&mut self, link: Link) -> std::io::Result<()> {
+        self.0.start_link(link)?;
+        self.1.start_link(link)
+    }
+
+    fn end_link(&mut self) -> std::io::Result<()> {
+        self.0.end_link()?;
+        self.1.end_link()
+    }
+
+    fn draw_image(&mut self, image: &Image, rect: Rect) -> std::io::Result<()> {
+        self.0.draw_image(image, rect)?;
+        self.1.draw_image(image, rect)
+    }
+
+    fn remove_image(&mut self, image: &Image, rect: Rect) -> std::io::Result<()> {
+        self.0.remove_image(image, rect)?;
+        self.1.remove_image(image, rect)
+    }
+
+    fn load_image(&mut self, image: &Image) -> std::io::Result<()> {
+        self.0.load_image(image)?;
+        self.1.load_image(image)
+    }
+
+    fn unload_image(&mut self, image: &Image) -> std::io::Result<()> {
+        self.0.unload_image(image)?;
+        self.1.unload_image(image)
+    }
+
+    fn capture_mouse(&mut self) -> std::io::Result<()> {
+        self.0.capture_mouse()?;
+        self.1.capture_mouse()
+    }
+
+    fn release_mouse(&mut self) -> std::io::Result<()> {
+        self.0.release_mouse()?;
+        self.1.release_mouse()
+    }
+
+    fn enable_bracketed_paste(&mut self) -> std::io::Result<()> {
+        self.0.enable_bracketed_paste()?;
+        self.1.enable_bracketed_paste()
+    }
+
+    fn enable_focus_reporting(&mut self) -> std::io::Result<()> {
+        self.0.enable_focus_reporting()?;
+        self.1.enable_focus_reporting()
+    }
+
+    fn disable_focus_reporting(&mut self) -> std::io::Result<()> {
+        self.0.disable_focus_reporting()?;
+        self.1.disable_focus_reporting()
+    }
+
+    fn disable_bracketed_paste(&mut self) -> std::io::Result<()> {
+        self.0.disable_bracketed_paste()?;
+        self.1.disable_bracketed_paste()
+    }
+
+    fn push_keyboard_enhancement(&mut self) -> std::io::Result<()> {
+        self.0.push_keyboard_enhancement()?;
+        self.1.push_keyboard_enhancement()
+    }
+
+    fn pop_keyboard_enhancement(&mut self) -> std::io::Result<()> {
+        self.0.pop_keyboard_enhancement()?;
+        self.1.pop_keyboard_enhancement()
+    }
+
+    fn show_cursor(&mut self) -> std::io::Result<()> {
+        self.0.show_cursor()?;
+        self.1.show_cursor()
+    }
+
+    fn hide_cursor(&mut self) -> std::io::Result<()> {
+        self.0.hide_cursor()?;
+        self.1.hide_cursor()
+    }
+
+    fn set_cursor_shape(&mut self, shape: CursorShape, blinking: bool) -> std::io::Result<()> {
+        self.0.set_cursor_shape(shape, blinking)?;
+        self.1.set_cursor_shape(shape, blinking)
+    }
+
+    fn reset_cursor_shape(&mut self) -> std::io::Result<()> {
+        self.0.reset_cursor_shape()?;
+        self.1.reset_cursor_shape()
+    }
+
+    fn clear_screen(&mut self) -> std::io::Result<()> {
+        self.0.clear_screen()?;
+        self.1.clear_screen()
+    }
+
+    fn enter_alt_screen(&mut self) -> std::io::Result<()> {
+        self.0.enter_alt_screen()?;
+        self.1.enter_alt_screen()
+    }
+
+    fn leave_alt_screen(&mut self) -> std::io::Result<()> {
+        self.0.leave_alt_screen()?;
+        self.1.leave_alt_screen()
+    }
+
+    fn enable_line_wrap(&mut self) -> std::io::Result<()> {
+        self.0.enable_line_wrap()?;
+        self.1.enable_line_wrap()
+    }
+
+    fn disable_line_wrap(&mut self) -> std::io::Result<()> {
+        self.0.disable_line_wrap()?;
+        self.1.disable_line_wrap()
+    }
+
+    fn set_title(&mut self, title: &str) -> std::io::Result<()> {
+        self.0.set_title(title)?;
+        self.1.set_title(title)
+    }
+
+    fn set_clipboard(&mut self, data: &str) -> std::io::Result<()> {
+        self.0.set_clipboard(data)?;
+        self.1.set_clipboard(data)
+    }
+
+    fn request_clipboard(&mut self) -> std::io::Result<()> {
+        self.0.request_clipboard()?;
+        self.1.request_clipboard()
+    }
 }
 
 pub struct TermRenderer<W> {
     out: W,
+    depth: ColorDepth,
+    synchronized: bool,
+    graphics: Graphics,
+    cell_size: Vec2,
+    repeat: bool,
+    scrolling: bool,
 }
 
 impl<W> TermRenderer<W> {
     pub const fn new(out: W) -> Self {
-        Self { out }
+        Self {
+            out,
+            depth: ColorDepth::TrueColor,
+            synchronized: true,
+            graphics: Graphics::HalfBlocks,
+            cell_size: vec2(10, 20),
+            repeat: false,
+            scrolling: false,
+        }
+    }
+
+    pub const fn with_color_depth(mut self, depth: ColorDepth) -> Self {
+        self.depth = depth;
+        self
+    }
+
+    // wraps each frame in a synchronized update so it doesn't tear
+    pub const fn with_synchronized_output(mut self, synchronized: bool) -> Self {
+        self.synchronized = synchronized;
+        self
+    }
+
+    // REP ends up on the screen as garbage in terminals that don't know it
+    pub const fn with_repeat(mut self, repeat: bool) -> Self {
+        self.repeat = repeat;
+        self
+    }
+
+    // moving rows with a scroll region, instead of drawing them again
+    pub const fn with_scrolling(mut self, scrolling: bool) -> Self {
+        self.scrolling = scrolling;
+        self
+    }
+
+    // sixel images are sized in pixels, so they need to know how big a cell is
+    pub const fn with_graphics(mut self, graphics: Graphics, cell_size: Vec2) -> Self {
+        self.graphics = graphics;
+        self.cell_size = cell_size;
+        self
+    }
+
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.out
     }
 }
 
@@ -70,11 +527,16 @@ macro_rules! csi { ($($lit:literal),*) => { concat!( $("\x1b[",$lit),*).as_bytes
 
 impl<W: std::io::Write> Renderer for TermRenderer<W> {
     fn begin(&mut self) -> std::io::Result<()> {
+        if !self.synchronized {
+            return Ok(());
+        }
         self.out.write_all(csi!("?2026h"))
     }
 
     fn end(&mut self) -> std::io::Result<()> {
-        self.out.write_all(csi!("?2026l"))?;
+        if self.synchronized {
+            self.out.write_all(csi!("?2026l"))?;
+        }
         self.out.flush()
     }
 
@@ -87,12 +549,100 @@ impl<W: std::io::Write> Renderer for TermRenderer<W> {
         self.out.write_all(ch.encode_utf8(&mut [0; 4]).as_bytes())
     }
 
-    fn set_fg(&mut self, Rgba(r, g, b, ..): Rgba) -> std::io::Result<()> {
-        self.out.write_fmt(format_args!("\x1b[38;2;{r};{g};{b}m"))
+    fn move_up(&mut self, rows: i32) -> std::io::Result<()> {
+        self.out.write_fmt(format_args!("\x1b[{rows}A"))
+    }
+
+    fn move_down(&mut self, rows: i32) -> std::io::Result<()> {
+        self.out.write_fmt(format_args!("\x1b[{rows}B"))
+    }
+
+    fn move_to_column(&mut self, col: i32) -> std::io::Result<()> {
+        let col = col + 1;
+        self.out.write_fmt(format_args!("\x1b[{col}G"))
+    }
+
+    fn clear_below(&mut self) -> std::io::Result<()> {
+        self.out.write_all(csi!("J"))
+    }
+
+    fn move_relative(&mut self, from: Pos2, to: Pos2) -> std::io::Result<()> {
+        self.out.write_all(shortest_move(from, to).as_bytes())
+    }
+
+    fn erase_chars(&mut self, count: usize) -> std::io::Result<()> {
+        self.out.write_fmt(format_args!("\x1b[{count}X"))
+    }
+
+    fn erase_line(&mut self) -> std::io::Result<()> {
+        self.out.write_all(csi!("K"))
+    }
+
+    // REP repeats the last char written, so clusters have to be written out
+    fn repeat(&mut self, glyph: &str, width: i32, count: usize) -> std::io::Result<()> {
+        let rep = format!("\x1b[{}b", count.saturating_sub(1));
+        if !self.repeat
+            || glyph.chars().count() != 1
+            || rep.len() >= glyph.len() * count.saturating_sub(1)
+        {
+            return (0..count).try_for_each(|_| self.write_glyph(glyph, width));
+        }
+        self.write_glyph(glyph, width)?;
+        self.out.write_all(rep.as_bytes())
+    }
+
+    fn can_scroll(&self) -> bool {
+        self.scrolling
+    }
+
+    // the region is reset afterwards, which also homes the cursor
+    fn scroll(&mut self, top: i32, bottom: i32, rows: i32) -> std::io::Result<()> {
+        let (top, bottom) = (top + 1, bottom + 1);
+        self.out.write_fmt(format_args!("\x1b[{top};{bottom}r"))?;
+        match rows {
+            0 => {}
+            rows if rows > 0 => self.out.write_fmt(format_args!("\x1b[{rows}S"))?,
+            rows => self.out.write_fmt(format_args!("\x1b[{}T", -rows))?,
+        }
+        self.out.write_all(csi!("r"))
     }
 
-    fn set_bg(&mut self, Rgba(r, g, b, ..): Rgba) -> std::io::Result<()> {
-        self.out.write_fmt(format_args!("\x1b[48;2;{r};{g};{b}m"))
+    fn color_depth(&self) -> ColorDepth {
+        self.depth
+    }
+
+    fn set_fg(&mut self, rgb: Rgba) -> std::io::Result<()> {
+        let Rgba(r, g, b, ..) = rgb;
+        match self.depth {
+            ColorDepth::Monochrome => Ok(()),
+            ColorDepth::TrueColor => self.out.write_fmt(format_args!("\x1b[38;2;{r};{g};{b}m")),
+            ColorDepth::Indexed256 => {
+                let index = to_ansi256(rgb);
+                self.out.write_fmt(format_args!("\x1b[38;5;{index}m"))
+            }
+            ColorDepth::Ansi16 => match to_ansi16(rgb) {
+                index @ 0..=7 => self.out.write_fmt(format_args!("\x1b[{}m", 30 + index)),
+                index => self.out.write_fmt(format_args!("\x1b[{}m", 90 + index - 8)),
+            },
+        }
+    }
+
+    fn set_bg(&mut self, rgb: Rgba) -> std::io::Result<()> {
+        let Rgba(r, g, b, ..) = rgb;
+        match self.depth {
+            ColorDepth::Monochrome => Ok(()),
+            ColorDepth::TrueColor => self.out.write_fmt(format_args!("\x1b[48;2;{r};{g};{b}m")),
+            ColorDepth::Indexed256 => {
+                let index = to_ansi256(rgb);
+                self.out.write_fmt(format_args!("\x1b[48;5;{index}m"))
+            }
+            ColorDepth::Ansi16 => match to_ansi16(rgb) {
+                index @ 0..=7 => self.out.write_fmt(format_args!("\x1b[{}m", 40 + index)),
+                index => self
+                    .out
+                    .write_fmt(format_args!("\x1b[{}m", 100 + index - 8)),
+            },
+        }
     }
 
     fn set_attr(&mut self, attr: Attribute) -> std::io::Result<()> {
@@ -111,7 +661,24 @@ impl<W: std::io::Write> Renderer for TermRenderer<W> {
         .into_iter()
         .enumerate()
         .filter(|(_, c)| *c)
-        .try_for_each(|(n, _)| self.out.write_fmt(format_args!("\x1b[{n}m")))
+        .try_for_each(|(n, _)| self.out.write_fmt(format_args!("\x1b[{n}m")))?;
+
+        // these use the colon form, terminals that don't know them ignore the whole sequence
+        [
+            attr.is_double_underline(),
+            attr.is_curly_underline(),
+            attr.is_dotted_underline(),
+            attr.is_dashed_underline(),
+        ]
+        .into_iter()
+        .zip(2..)
+        .filter(|(c, _)| *c)
+        .try_for_each(|(_, n)| self.out.write_fmt(format_args!("\x1b[4:{n}m")))?;
+
+        if attr.is_overline() {
+            self.out.write_all(csi!("53m"))?;
+        }
+        Ok(())
     }
 
     fn reset_fg(&mut self) -> std::io::Result<()> {
@@ -126,6 +693,82 @@ impl<W: std::io::Write> Renderer for TermRenderer<W> {
         self.out.write_all(csi!("0m"))
     }
 
+    fn set_underline_color(&mut self, rgb: Rgba) -> std::io::Result<()> {
+        let Rgba(r, g, b, ..) = rgb;
+        match self.depth {
+            ColorDepth::Monochrome => Ok(()),
+            ColorDepth::TrueColor => self.out.write_fmt(format_args!("\x1b[58:2::{r}:{g}:{b}m")),
+            // there isn't a 16 color form of this, so those use the same palette index
+            ColorDepth::Indexed256 => {
+                let index = to_ansi256(rgb);
+                self.out.write_fmt(format_args!("\x1b[58:5:{index}m"))
+            }
+            ColorDepth::Ansi16 => {
+                let index = to_ansi16(rgb);
+                self.out.write_fmt(format_args!("\x1b[58:5:{index}m"))
+            }
+        }
+    }
+
+    fn reset_underline_color(&mut self) -> std::io::Result<()> {
+        self.out.write_all(csi!("59m"))
+    }
+
+    // the id lets the terminal join up a link that was split across lines
+    fn start_link(&mut self, link: Link) -> std::io::Result<()> {
+        // a stray escape would end the sequence early
+        let url = link
+            .url()
+            .chars()
+            .filter(|c| !c.is_control())
+            .collect::<String>();
+        self.out
+            .write_fmt(format_args!("\x1b]8;id={};{url}\x1b\\", link.id()))
+    }
+
+    fn end_link(&mut self) -> std::io::Result<()> {
+        self.out.write_all(b"\x1b]8;;\x1b\\")
+    }
+
+    fn draw_image(&mut self, image: &Image, rect: Rect) -> std::io::Result<()> {
+        // sixel moves the cursor, so it's put back to where we think it is
+        self.out.write_all(b"\x1b7")?;
+        match self.graphics {
+            Graphics::Kitty => image.place_kitty(&mut self.out, rect)?,
+            Graphics::Sixel => {
+                let pixels = vec2(
+                    rect.width() * self.cell_size.x,
+                    rect.height() * self.cell_size.y,
+                );
+                image.write_sixel(&mut self.out, pixels)?
+            }
+            Graphics::HalfBlocks => {}
+        }
+        self.out.write_all(b"\x1b8")
+    }
+
+    fn remove_image(&mut self, image: &Image, rect: Rect) -> std::io::Result<()> {
+        match self.graphics {
+            Graphics::Kitty => image.remove_kitty(&mut self.out, rect),
+            // the cells under it are drawn over it
+            _ => Ok(()),
+        }
+    }
+
+    fn load_image(&mut self, image: &Image) -> std::io::Result<()> {
+        match self.graphics {
+            Graphics::Kitty => image.write_kitty(&mut self.out),
+            _ => Ok(()),
+        }
+    }
+
+    fn unload_image(&mut self, image: &Image) -> std::io::Result<()> {
+        match self.graphics {
+            Graphics::Kitty => image.free_kitty(&mut self.out),
+            _ => Ok(()),
+        }
+    }
+
     fn show_cursor(&mut self) -> std::io::Result<()> {
         self.out.write_all(csi!("?25h"))
     }
@@ -134,6 +777,15 @@ impl<W: std::io::Write> Renderer for TermRenderer<W> {
         self.out.write_all(csi!("?25l"))
     }
 
+    fn set_cursor_shape(&mut self, shape: CursorShape, blinking: bool) -> std::io::Result<()> {
+        let n = shape.decscusr(blinking);
+        self.out.write_fmt(format_args!("\x1b[{n} q"))
+    }
+
+    fn reset_cursor_shape(&mut self) -> std::io::Result<()> {
+        self.out.write_all(csi!("0 q"))
+    }
+
     fn capture_mouse(&mut self) -> std::io::Result<()> {
         self.out.write_all(csi!(
             "?1000h", //
@@ -154,12 +806,43 @@ impl<W: std::io::Write> Renderer for TermRenderer<W> {
         ))
     }
 
+    fn enable_bracketed_paste(&mut self) -> std::io::Result<()> {
+        self.out.write_all(csi!("?2004h"))
+    }
+
+    fn enable_focus_reporting(&mut self) -> std::io::Result<()> {
+        self.out.write_all(csi!("?1004h"))
+    }
+
+    fn disable_focus_reporting(&mut self) -> std::io::Result<()> {
+        self.out.write_all(csi!("?1004l"))
+    }
+
+    fn disable_bracketed_paste(&mut self) -> std::io::Result<()> {
+        self.out.write_all(csi!("?2004l"))
+    }
+
+    // kitty's progressive enhancement: disambiguate escape codes, report
+    // event types, report alternate keys and report all keys as escape codes
+    fn push_keyboard_enhancement(&mut self) -> std::io::Result<()> {
+        self.out.write_all(csi!(">15u"))
+    }
+
+    fn pop_keyboard_enhancement(&mut self) -> std::io::Result<()> {
+        self.out.write_all(csi!("<u"))
+    }
+
+    fn clear_screen(&mut self) -> std::io::Result<()> {
+        self.out.write_all(csi!("2J"))
+    }
+
+    // 1049 rather than 1048, which only saves the cursor and never switched screens
     fn enter_alt_screen(&mut self) -> std::io::Result<()> {
-        self.out.write_all(csi!("?1048h"))
+        self.out.write_all(csi!("?1049h"))
     }
 
     fn leave_alt_screen(&mut self) -> std::io::Result<()> {
-        self.out.write_all(csi!("?1048l"))
+        self.out.write_all(csi!("?1049l"))
     }
 
     fn enable_line_wrap(&mut self) -> std::io::Result<()> {
@@ -173,6 +856,46 @@ impl<W: std::io::Write> Renderer for TermRenderer<W> {
     fn set_title(&mut self, title: &str) -> std::io::Result<()> {
         self.out.write_fmt(format_args!("\x1b]2;{title}\x07"))
     }
+
+    fn set_clipboard(&mut self, data: &str) -> std::io::Result<()> {
+        let data = crate::base64::encode(data.as_bytes());
+        self.out.write_fmt(format_args!("\x1b]52;c;{data}\x07"))
+    }
+
+    fn request_clipboard(&mut self) -> std::io::Result<()> {
+        self.out.write_all(b"\x1b]52;c;?\x07")
+    }
+}
+
+// relative movement is usually shorter when the cursor is already nearby
+fn shortest_move(from: Pos2, to: Pos2) -> String {
+    let absolute = format!("\x1b[{};{};H", to.y + 1, to.x + 1);
+
+    let column = [
+        step(to.x - from.x, 'C', 'D'),
+        format!("\r{}", step(to.x, 'C', 'D')),
+        format!("\x1b[{}G", to.x + 1),
+    ]
+    .into_iter()
+    .min_by_key(String::len)
+    .unwrap_or_default();
+
+    let relative = step(to.y - from.y, 'B', 'A') + &column;
+    if relative.len() < absolute.len() {
+        relative
+    } else {
+        absolute
+    }
+}
+
+fn step(n: i32, forward: char, back: char) -> String {
+    match n {
+        0 => String::new(),
+        1 => format!("\x1b[{forward}"),
+        -1 => format!("\x1b[{back}"),
+        n if n > 0 => format!("\x1b[{n}{forward}"),
+        n => format!("\x1b[{}{back}", -n),
+    }
 }
 
 #[derive(Default)]
@@ -215,6 +938,58 @@ impl Renderer for DebugRenderer {
         Ok(())
     }
 
+    fn move_up(&mut self, rows: i32) -> std::io::Result<()> {
+        self.next_entry();
+        let _ = writeln!(&mut self.out, "  move_up {rows}");
+        Ok(())
+    }
+
+    fn move_down(&mut self, rows: i32) -> std::io::Result<()> {
+        self.next_entry();
+        let _ = writeln!(&mut self.out, "  move_down {rows}");
+        Ok(())
+    }
+
+    fn move_to_column(&mut self, col: i32) -> std::io::Result<()> {
+        self.next_entry();
+        let _ = writeln!(&mut self.out, "  move_to_column {col}");
+        Ok(())
+    }
+
+    fn clear_below(&mut self) -> std::io::Result<()> {
+        self.next_entry();
+        let _ = writeln!(&mut self.out, "  clear_below");
+        Ok(())
+    }
+
+    fn erase_chars(&mut self, count: usize) -> std::io::Result<()> {
+        self.next_entry();
+        let _ = writeln!(&mut self.out, "  erase_chars {count}");
+        Ok(())
+    }
+
+    fn erase_line(&mut self) -> std::io::Result<()> {
+        self.next_entry();
+        let _ = writeln!(&mut self.out, "  erase_line");
+        Ok(())
+    }
+
+    fn repeat(&mut self, glyph: &str, _width: i32, count: usize) -> std::io::Result<()> {
+        self.next_entry();
+        let _ = writeln!(&mut self.out, "  repeat {glyph:?} {count}");
+        Ok(())
+    }
+
+    fn can_scroll(&self) -> bool {
+        true
+    }
+
+    fn scroll(&mut self, top: i32, bottom: i32, rows: i32) -> std::io::Result<()> {
+        self.next_entry();
+        let _ = writeln!(&mut self.out, "  scroll {top}..={bottom} by {rows}");
+        Ok(())
+    }
+
     fn write(&mut self, ch: char) -> std::io::Result<()> {
         if !self.incomplete {
             self.out.push_str("    ");
@@ -264,4 +1039,73 @@ impl Renderer for DebugRenderer {
         let _ = writeln!(&mut self.out, "  reset_attr");
         Ok(())
     }
+
+    fn set_underline_color(&mut self, rgb: Rgba) -> std::io::Result<()> {
+        self.next_entry();
+        let _ = writeln!(&mut self.out, "  set_underline_color: {rgb:?}");
+        Ok(())
+    }
+
+    fn reset_underline_color(&mut self) -> std::io::Result<()> {
+        self.next_entry();
+        let _ = writeln!(&mut self.out, "  reset_underline_color");
+        Ok(())
+    }
+
+    fn start_link(&mut self, link: Link) -> std::io::Result<()> {
+        self.next_entry();
+        let _ = writeln!(&mut self.out, "  start_link: {}", link.url());
+        Ok(())
+    }
+
+    fn end_link(&mut self) -> std::io::Result<()> {
+        self.next_entry();
+        let _ = writeln!(&mut self.out, "  end_link");
+        Ok(())
+    }
+
+    fn draw_image(&mut self, image: &Image, rect: Rect) -> std::io::Result<()> {
+        self.next_entry();
+        let _ = writeln!(&mut self.out, "  draw_image: {} {rect:?}", image.id());
+        Ok(())
+    }
+
+    fn remove_image(&mut self, image: &Image, rect: Rect) -> std::io::Result<()> {
+        self.next_entry();
+        let _ = writeln!(&mut self.out, "  remove_image: {} {rect:?}", image.id());
+        Ok(())
+    }
+
+    fn load_image(&mut self, image: &Image) -> std::io::Result<()> {
+        self.next_entry();
+        let _ = writeln!(&mut self.out, "  load_image: {}", image.id());
+        Ok(())
+    }
+
+    fn unload_image(&mut self, image: &Image) -> std::io::Result<()> {
+        self.next_entry();
+        let _ = writeln!(&mut self.out, "  unload_image: {}", image.id());
+        Ok(())
+    }
+
+    fn show_cursor(&mut self) -> std::io::Result<()> {
+        self.next_entry();
+        let _ = writeln!(&mut self.out, "  show_cursor");
+        Ok(())
+    }
+
+    fn hide_cursor(&mut self) -> std::io::Result<()> {
+        self.next_entry();
+        let _ = writeln!(&mut self.out, "  hide_cursor");
+        Ok(())
+    }
+
+    fn set_cursor_shape(&mut self, shape: CursorShape, blinking: bool) -> std::io::Result<()> {
+        self.next_entry();
+        let _ = writeln!(
+            &mut self.out,
+            "  set_cursor_shape: {shape:?} (blinking: {blinking})"
+        );
+        Ok(())
+    }
 }
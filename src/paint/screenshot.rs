@@ -0,0 +1,448 @@
+use std::{fmt::Write as _, path::Path};
+
+use crate::{
+    color::{Color, Rgba},
+    geom::pos2,
+};
+
+use super::{Attribute, Buffer, Cell, CellAttr, Link};
+
+// the size of a cell in the svg, in pixels
+const CELL_WIDTH: f32 = 8.4;
+const CELL_HEIGHT: f32 = 17.0;
+const FONT_SIZE: f32 = 14.0;
+
+// turns a buffer into a picture of it, either as an svg or as html
+pub struct Screenshot<'a> {
+    buffer: &'a Buffer,
+    // the terminal's own colors, used where a cell doesn't have one
+    fg: Rgba,
+    bg: Rgba,
+}
+
+impl<'a> Screenshot<'a> {
+    pub const fn new(buffer: &'a Buffer) -> Self {
+        Self {
+            buffer,
+            fg: Rgba::from_u32(0xD3D7CF),
+            bg: Rgba::from_u32(0x1E1E1E),
+        }
+    }
+
+    // html for .html and .htm files, an svg otherwise
+    pub fn save(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        let path = path.as_ref();
+        let is_html = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("html") || ext.eq_ignore_ascii_case("htm"));
+
+        let data = if is_html {
+            format!(
+                "<!DOCTYPE html>\n<html>\n<head><meta charset=\"utf-8\"></head>\n<body>\n{}</body>\n</html>\n",
+                self.html()
+            )
+        } else {
+            self.svg()
+        };
+        std::fs::write(path, data)
+    }
+
+    pub fn svg(&self) -> String {
+        let size = self.buffer.rect().size();
+        let (width, height) = (size.x as f32 * CELL_WIDTH, size.y as f32 * CELL_HEIGHT);
+
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width:.1}" height="{height:.1}" viewBox="0 0 {width:.1} {height:.1}" font-family="monospace" font-size="{FONT_SIZE}">"#
+        );
+        let _ = writeln!(
+            out,
+            r#"<rect width="100%" height="100%" fill="{}"/>"#,
+            hex(self.bg)
+        );
+
+        for (y, runs) in self.rows() {
+            let top = y as f32 * CELL_HEIGHT;
+
+            // neighbouring runs often only differ in their foreground
+            let mut backgrounds = Vec::<(i32, i32, Rgba)>::new();
+            for run in &runs {
+                match backgrounds.last_mut() {
+                    Some((_, len, bg)) if *bg == run.style.bg => *len += run.len,
+                    _ => backgrounds.push((run.x, run.len, run.style.bg)),
+                }
+            }
+
+            for (x, len, bg) in backgrounds.into_iter().filter(|&(.., bg)| bg != self.bg) {
+                let _ = writeln!(
+                    out,
+                    r#"<rect x="{:.1}" y="{top:.1}" width="{:.1}" height="{CELL_HEIGHT:.1}" fill="{}"/>"#,
+                    x as f32 * CELL_WIDTH,
+                    len as f32 * CELL_WIDTH,
+                    hex(bg)
+                );
+            }
+
+            let baseline = top + CELL_HEIGHT * 0.8;
+            for run in runs.iter().filter(|run| run.is_visible()) {
+                let _ = write!(
+                    out,
+                    r#"<text x="{:.1}" y="{baseline:.1}" textLength="{:.1}" xml:space="preserve" fill="{}""#,
+                    run.x as f32 * CELL_WIDTH,
+                    run.len as f32 * CELL_WIDTH,
+                    hex(run.style.fg)
+                );
+
+                let attr = run.style.attr;
+                if attr.is_bold() {
+                    out.push_str(r#" font-weight="bold""#);
+                }
+                if attr.is_italic() {
+                    out.push_str(r#" font-style="italic""#);
+                }
+                if attr.is_faint() {
+                    out.push_str(r#" opacity="0.5""#);
+                }
+                if let Some(decoration) = run.style.decoration() {
+                    let _ = write!(out, r#" style="text-decoration: {decoration}""#);
+                }
+
+                out.push('>');
+                escape(&mut out, &run.text);
+                out.push_str("</text>\n");
+            }
+        }
+
+        out.push_str("</svg>\n");
+        out
+    }
+
+    pub fn html(&self) -> String {
+        let mut out = String::new();
+        let _ = write!(
+            out,
+            r#"<pre style="color: {}; background: {}; font-family: monospace; line-height: 1.2">"#,
+            hex(self.fg),
+            hex(self.bg)
+        );
+
+        for (y, runs) in self.rows() {
+            if y > 0 {
+                out.push('\n');
+            }
+
+            for run in runs {
+                let style = run.style;
+                if let Some(link) = style.link {
+                    out.push_str(r#"<a href=""#);
+                    escape(&mut out, &link.url());
+                    out.push_str(r#"">"#);
+                }
+
+                let plain = Style {
+                    link: None,
+                    ..style
+                };
+                if plain == self.default_style() {
+                    escape(&mut out, &run.text);
+                    if style.link.is_some() {
+                        out.push_str("</a>");
+                    }
+                    continue;
+                }
+
+                out.push_str(r#"<span style=""#);
+                if style.fg != self.fg {
+                    let _ = write!(out, "color: {}; ", hex(style.fg));
+                }
+                if style.bg != self.bg {
+                    let _ = write!(out, "background: {}; ", hex(style.bg));
+                }
+                if style.attr.is_bold() {
+                    out.push_str("font-weight: bold; ");
+                }
+                if style.attr.is_italic() {
+                    out.push_str("font-style: italic; ");
+                }
+                if style.attr.is_faint() {
+                    out.push_str("opacity: 0.5; ");
+                }
+                if let Some(decoration) = style.decoration() {
+                    let _ = write!(out, "text-decoration: {decoration}; ");
+                }
+                out.truncate(out.trim_end().len());
+                out.push_str(r#"">"#);
+
+                escape(&mut out, &run.text);
+                out.push_str("</span>");
+                if style.link.is_some() {
+                    out.push_str("</a>");
+                }
+            }
+        }
+
+        out.push_str("</pre>\n");
+        out
+    }
+
+    const fn default_style(&self) -> Style {
+        Style {
+            fg: self.fg,
+            bg: self.bg,
+            attr: Attribute::RESET,
+            underline: None,
+            link: None,
+        }
+    }
+
+    fn rows(&self) -> impl Iterator<Item = (i32, Vec<Run>)> + '_ {
+        let size = self.buffer.rect().size();
+        (0..size.y).map(move |y| {
+            let mut runs = Vec::<Run>::new();
+            for x in 0..size.x {
+                let cell = &self.buffer[pos2(x, y)];
+                let style = self.style(cell);
+                match runs.last_mut() {
+                    Some(run) if run.style == style => {
+                        cell.glyph.with_str(|s| run.text.push_str(s));
+                        run.len += 1;
+                    }
+                    _ => runs.push(Run {
+                        x,
+                        len: 1,
+                        text: cell.glyph.with_str(|s| s.to_string()),
+                        style,
+                    }),
+                }
+            }
+            (y, runs)
+        })
+    }
+
+    fn style(&self, cell: &Cell) -> Style {
+        let attr = match cell.attr {
+            CellAttr::Attr(attr) => attr,
+            CellAttr::Reset => Attribute::RESET,
+        };
+
+        let fg = match cell.fg {
+            Color::Rgba(fg) => fg,
+            _ => self.fg,
+        };
+        let bg = match cell.bg {
+            Color::Rgba(bg) => bg,
+            _ => self.bg,
+        };
+        let underline = match cell.underline {
+            Color::Rgba(underline) if attr.is_underlined() => Some(underline),
+            _ => None,
+        };
+
+        if attr.is_reverse() {
+            Style {
+                fg: bg,
+                bg: fg,
+                attr: attr & !Attribute::REVERSE,
+                underline,
+                link: cell.link,
+            }
+        } else {
+            Style {
+                fg,
+                bg,
+                attr,
+                underline,
+                link: cell.link,
+            }
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq)]
+struct Style {
+    fg: Rgba,
+    bg: Rgba,
+    attr: Attribute,
+    underline: Option<Rgba>,
+    link: Option<Link>,
+}
+
+impl Style {
+    // the value for a css text-decoration
+    fn decoration(&self) -> Option<String> {
+        let attr = self.attr;
+        let lines = [
+            (attr.is_underlined(), "underline"),
+            (attr.is_overline(), "overline"),
+            (attr.is_strikeout(), "line-through"),
+        ];
+
+        let mut out = String::new();
+        for (_, line) in lines.into_iter().filter(|(set, _)| *set) {
+            if !out.is_empty() {
+                out.push(' ');
+            }
+            out.push_str(line);
+        }
+
+        if out.is_empty() {
+            return None;
+        }
+
+        let style = [
+            (attr.is_double_underline(), " double"),
+            (attr.is_curly_underline(), " wavy"),
+            (attr.is_dotted_underline(), " dotted"),
+            (attr.is_dashed_underline(), " dashed"),
+        ];
+        if let Some((_, style)) = style.into_iter().find(|(set, _)| *set) {
+            out.push_str(style);
+        }
+
+        if let Some(underline) = self.underline {
+            out.push(' ');
+            out.push_str(&hex(underline));
+        }
+
+        Some(out)
+    }
+}
+
+struct Run {
+    x: i32,
+    len: i32,
+    text: String,
+    style: Style,
+}
+
+impl Run {
+    fn is_visible(&self) -> bool {
+        self.style.decoration().is_some() || !self.text.trim().is_empty()
+    }
+}
+
+fn hex(Rgba(r, g, b, ..): Rgba) -> String {
+    format!("#{r:02x}{g:02x}{b:02x}")
+}
+
+fn escape(out: &mut String, text: &str) {
+    for ch in text.chars() {
+        match ch {
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '&' => out.push_str("&amp;"),
+            '"' => out.push_str("&quot;"),
+            ch => out.push(ch),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        debug::Harness,
+        geom::vec2,
+        paint::{Attribute, Styled},
+        ui::Command,
+        widgets::{column, label},
+        Ui,
+    };
+
+    // saves a screenshot of the frame, through the command an app would use
+    fn screenshot(name: &str, mut app: impl FnMut(&Ui)) -> String {
+        let path = std::env::temp_dir().join(format!("dearsum-{}-{name}", std::process::id()));
+        let mut harness = Harness::new(vec2(6, 2));
+        harness.frame(|ui| {
+            app(ui);
+            ui.command(Command::Screenshot(path.clone()));
+        });
+
+        let data = std::fs::read_to_string(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        data
+    }
+
+    fn styled(_: &Ui) {
+        column(|| {
+            label(
+                Styled::new("<a&b>")
+                    .fg(0xFF0000)
+                    .attr(Attribute::BOLD | Attribute::UNDERLINE),
+            );
+            label(
+                Styled::new("漢\"x")
+                    .bg(0x0000FF)
+                    .italic()
+                    .link("https://a?b&c"),
+            );
+        });
+    }
+
+    #[test]
+    fn text_is_escaped() {
+        let svg = screenshot("escaped.svg", styled);
+        assert!(svg.contains(">&lt;a&amp;b&gt;</text>"));
+        assert!(svg.contains(">漢&quot;x</text>"));
+
+        let html = screenshot("escaped.html", styled);
+        assert!(html.contains(">&lt;a&amp;b&gt;</span>"));
+        assert!(html.contains(r#"<a href="https://a?b&amp;c">"#));
+    }
+
+    #[test]
+    fn colors_and_attributes() {
+        let svg = screenshot("styled.svg", styled);
+        assert!(svg.contains(
+            r##"fill="#ff0000" font-weight="bold" style="text-decoration: underline">"##
+        ));
+        assert!(
+            svg.contains(r##"<rect x="0.0" y="17.0" width="33.6" height="17.0" fill="#0000ff"/>"##)
+        );
+        assert!(svg.contains(r##"fill="#d3d7cf" font-style="italic">"##));
+
+        let html = screenshot("styled.html", styled);
+        assert!(html.contains(
+            r#"<span style="color: #ff0000; font-weight: bold; text-decoration: underline;">"#
+        ));
+        assert!(html.contains(r#"<span style="background: #0000ff; font-style: italic;">"#));
+    }
+
+    #[test]
+    fn reverse_swaps_the_colors() {
+        let app = |_: &Ui| {
+            label(Styled::new("r").fg(0x00FF00).reverse());
+        };
+        let svg = screenshot("reverse.svg", app);
+        assert!(svg.contains(r##"width="8.4" height="17.0" fill="#00ff00"/>"##));
+        assert!(svg.contains(r##"fill="#1e1e1e">r</text>"##));
+    }
+
+    #[test]
+    fn wide_glyphs_take_two_cells() {
+        // four cells, the glyph's continuation adds nothing to the text
+        let svg = screenshot("wide.svg", styled);
+        assert!(svg.contains(r#"textLength="33.6""#));
+
+        let html = screenshot("wide.html", styled);
+        assert!(html.contains("漢&quot;x</span></a>  </pre>"));
+    }
+
+    #[test]
+    fn the_frame_being_shown_is_saved() {
+        let path = std::env::temp_dir().join(format!("dearsum-{}-frame.html", std::process::id()));
+        let mut harness = Harness::new(vec2(6, 1));
+        harness.frame(|_| {
+            label("old");
+        });
+        harness.frame(|ui| {
+            label("new");
+            ui.command(Command::Screenshot(path.clone()));
+        });
+
+        let html = std::fs::read_to_string(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert!(html.contains("new"));
+        assert!(!html.contains("old"));
+    }
+}
@@ -21,6 +21,9 @@ pub use border::Border;
 mod line;
 pub use line::Line;
 
+mod pixels;
+pub use pixels::Pixels;
+
 impl Shape for Cell {
     fn draw(&self, size: Vec2, mut put: impl FnMut(Pos2, Cell)) {
         for y in 0..size.y.max(1) {
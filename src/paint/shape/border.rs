@@ -6,7 +6,7 @@ use crate::{
 
 use super::{Line, Shape};
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub struct Border {
     pub left_top: char,
     pub right_top: char,
@@ -0,0 +1,218 @@
+use crate::{
+    color::{Color, Rgba},
+    geom::{pos2, vec2, Pos2, Vec2},
+    paint::{Cell, Image},
+};
+
+use super::Shape;
+
+// an image stretched over the cells, two pixels to a cell with half blocks
+// or four with quadrant blocks. transparent pixels leave the background alone
+#[derive(Copy, Clone, Debug)]
+pub struct Pixels<'a> {
+    image: &'a Image,
+    quadrants: bool,
+}
+
+impl<'a> Pixels<'a> {
+    pub const fn new(image: &'a Image) -> Self {
+        Self {
+            image,
+            quadrants: false,
+        }
+    }
+
+    // twice the detail across, but each cell only gets two of its four colors
+    pub const fn quadrants(mut self, quadrants: bool) -> Self {
+        self.quadrants = quadrants;
+        self
+    }
+
+    fn sample(&self, pos: Pos2, pixels: Vec2) -> Option<Rgba> {
+        let rgba = self.image.sample(pos, pixels);
+        (rgba.alpha() >= 128).then(|| rgba.opaque())
+    }
+
+    fn half_block(&self, pos: Pos2, size: Vec2) -> Option<Cell> {
+        let pixels = vec2(size.x, size.y * 2);
+        let top = self.sample(pos2(pos.x, pos.y * 2), pixels);
+        let bottom = self.sample(pos2(pos.x, pos.y * 2 + 1), pixels);
+        let cell = match (top, bottom) {
+            (Some(top), Some(bottom)) => Cell::new('▀').fg(top).bg(bottom),
+            (Some(top), None) => Cell::new('▀').fg(top),
+            (None, Some(bottom)) => Cell::new('▄').fg(bottom),
+            (None, None) => return None,
+        };
+        Some(cell)
+    }
+
+    fn quadrant(&self, pos: Pos2, size: Vec2) -> Option<Cell> {
+        // top left, top right, bottom left, bottom right
+        const BLOCKS: [char; 16] = [
+            ' ', '▘', '▝', '▀', '▖', '▌', '▞', '▛', '▗', '▚', '▐', '▜', '▄', '▙', '▟', '█',
+        ];
+
+        let pixels = vec2(size.x * 2, size.y * 2);
+        let quad = [(0, 0), (1, 0), (0, 1), (1, 1)]
+            .map(|(x, y)| self.sample(pos2(pos.x * 2 + x, pos.y * 2 + y), pixels));
+
+        // the two colors furthest apart split the cell, transparency always gets its own side
+        let opaque: Vec<_> = quad.iter().flatten().copied().collect();
+        let (fg, bg) = match opaque.len() {
+            0 => return None,
+            4 => {
+                let mut pair = (opaque[0], opaque[0]);
+                for (i, &a) in opaque.iter().enumerate() {
+                    for &b in &opaque[i + 1..] {
+                        if distance(a, b) > distance(pair.0, pair.1) {
+                            pair = (a, b)
+                        }
+                    }
+                }
+                (pair.0, Some(pair.1))
+            }
+            _ => (opaque[0], None),
+        };
+
+        let mut mask = 0;
+        let (mut front, mut back) = (Vec::new(), Vec::new());
+        for (i, rgba) in quad.into_iter().enumerate() {
+            match (rgba, bg) {
+                (Some(rgba), Some(bg)) if distance(rgba, bg) < distance(rgba, fg) => {
+                    back.push(rgba)
+                }
+                (Some(rgba), _) => {
+                    mask |= 1 << i;
+                    front.push(rgba)
+                }
+                (None, _) => {}
+            }
+        }
+
+        let cell = Cell::new(BLOCKS[mask]).fg(average(&front));
+        Some(match back.is_empty() {
+            true => cell,
+            false => cell.bg(average(&back)),
+        })
+    }
+}
+
+impl Shape for Pixels<'_> {
+    fn draw(&self, size: Vec2, mut put: impl FnMut(Pos2, Cell)) {
+        if self.image.width() == 0 || self.image.height() == 0 {
+            return;
+        }
+
+        for y in 0..size.y {
+            for x in 0..size.x {
+                let cell = match self.quadrants {
+                    true => self.quadrant(pos2(x, y), size),
+                    false => self.half_block(pos2(x, y), size),
+                };
+                if let Some(cell) = cell {
+                    put(pos2(x, y), cell)
+                }
+            }
+        }
+    }
+}
+
+fn distance(a: Rgba, b: Rgba) -> i32 {
+    let d = |a: u8, b: u8| (a as i32 - b as i32).pow(2);
+    d(a.red(), b.red()) + d(a.green(), b.green()) + d(a.blue(), b.blue())
+}
+
+fn average(colors: &[Rgba]) -> Color {
+    let n = colors.len().max(1) as u32;
+    let sum = |f: fn(&Rgba) -> u8| (colors.iter().map(|c| f(c) as u32).sum::<u32>() / n) as u8;
+    Color::Rgba(Rgba::new(sum(Rgba::red), sum(Rgba::green), sum(Rgba::blue)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RED: Rgba = Rgba::new(255, 0, 0);
+    const BLUE: Rgba = Rgba::new(0, 0, 255);
+    const CLEAR: Rgba = Rgba::TRANSPARENT;
+
+    // the rows of pixels, top to bottom
+    fn image(rows: &[&[Rgba]]) -> Image {
+        Image::from_fn(rows[0].len() as u32, rows.len() as u32, |x, y| {
+            rows[y as usize][x as usize]
+        })
+    }
+
+    fn draw(pixels: Pixels, size: Vec2) -> Vec<Vec<Option<Cell>>> {
+        let mut cells = vec![vec![None; size.x as usize]; size.y as usize];
+        pixels.draw(size, |pos, cell| {
+            cells[pos.y as usize][pos.x as usize] = Some(cell)
+        });
+        cells
+    }
+
+    #[test]
+    fn half_blocks() {
+        let table: &[(&[&[Rgba]], Option<Cell>)] = &[
+            (&[&[RED], &[BLUE]], Some(Cell::new('▀').fg(RED).bg(BLUE))),
+            (&[&[RED], &[CLEAR]], Some(Cell::new('▀').fg(RED))),
+            (&[&[CLEAR], &[BLUE]], Some(Cell::new('▄').fg(BLUE))),
+            (&[&[CLEAR], &[CLEAR]], None),
+        ];
+        for (rows, expected) in table {
+            let image = image(rows);
+            assert_eq!(
+                draw(Pixels::new(&image), vec2(1, 1)),
+                [[*expected]],
+                "{rows:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn quadrants() {
+        let table: &[(&[&[Rgba]], Option<Cell>)] = &[
+            (
+                &[&[RED, RED], &[BLUE, BLUE]],
+                Some(Cell::new('▀').fg(RED).bg(BLUE)),
+            ),
+            (
+                &[&[RED, BLUE], &[RED, BLUE]],
+                Some(Cell::new('▌').fg(RED).bg(BLUE)),
+            ),
+            (
+                &[&[RED, CLEAR], &[CLEAR, CLEAR]],
+                Some(Cell::new('▘').fg(RED)),
+            ),
+            (
+                &[&[CLEAR, BLUE], &[BLUE, CLEAR]],
+                Some(Cell::new('▞').fg(BLUE)),
+            ),
+            (&[&[RED, RED], &[RED, RED]], Some(Cell::new('█').fg(RED))),
+            (&[&[CLEAR, CLEAR], &[CLEAR, CLEAR]], None),
+        ];
+        for (rows, expected) in table {
+            let image = image(rows);
+            let pixels = Pixels::new(&image).quadrants(true);
+            assert_eq!(draw(pixels, vec2(1, 1)), [[*expected]], "{rows:?}");
+        }
+    }
+
+    #[test]
+    fn images_are_stretched_over_the_cells() {
+        let image = image(&[&[RED, BLUE]]);
+        let red = Some(Cell::new('▀').fg(RED).bg(RED));
+        let blue = Some(Cell::new('▀').fg(BLUE).bg(BLUE));
+        assert_eq!(
+            draw(Pixels::new(&image), vec2(4, 2)),
+            [[red, red, blue, blue], [red, red, blue, blue]]
+        );
+
+        // and shrunk by skipping pixels
+        let image = Image::from_fn(8, 8, |x, _| if x < 4 { RED } else { BLUE });
+        assert_eq!(draw(Pixels::new(&image), vec2(2, 1)), [[red, blue]]);
+
+        let empty = Image::new(0, 0, []);
+        assert_eq!(draw(Pixels::new(&empty), vec2(2, 1)), [[None, None]]);
+    }
+}
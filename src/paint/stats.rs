@@ -0,0 +1,424 @@
+use super::{Attribute, CursorShape, Image, Link, Renderer, TermRenderer};
+use crate::{
+    color::{ColorDepth, Rgba},
+    geom::{Pos2, Rect},
+};
+
+// what it cost to send a frame to a terminal
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct RenderStats {
+    // everything that would have been written, escape sequences included
+    pub bytes: usize,
+    // repeated glyphs are counted once for every cell they cover
+    pub glyphs: usize,
+    pub moves: usize,
+    pub erases: usize,
+    pub scrolled_rows: usize,
+}
+
+#[derive(Default)]
+struct Counter(usize);
+
+impl std::io::Write for Counter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0 += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+// renders a frame the same way the terminal would, but only keeps count
+pub struct StatsRenderer {
+    term: TermRenderer<Counter>,
+    stats: RenderStats,
+}
+
+impl Default for StatsRenderer {
+    fn default() -> Self {
+        Self {
+            term: TermRenderer::new(Counter::default()),
+            stats: RenderStats::default(),
+        }
+    }
+}
+
+impl StatsRenderer {
+    pub fn with_color_depth(mut self, depth: ColorDepth) -> Self {
+        self.term = self.term.with_color_depth(depth);
+        self
+    }
+
+    pub fn with_repeat(mut self, repeat: bool) -> Self {
+        self.term = self.term.with_repeat(repeat);
+        self
+    }
+
+    pub fn with_scrolling(mut self, scrolling: bool) -> Self {
+        self.term = self.term.with_scrolling(scrolling);
+        self
+    }
+
+    pub fn stats(&mut self) -> RenderStats {
+        RenderStats {
+            bytes: self.term.get_mut().0,
+            ..self.stats
+        }
+    }
+}
+
+impl Renderer for StatsRenderer {
+    fn begin(&mut self) -> std::io::Result<()> {
+        self.term.begin()
+    }
+
+    fn end(&mut self) -> std::io::Result<()> {
+        self.term.end()
+    }
+
+    fn move_to(&mut self, pos: Pos2) -> std::io::Result<()> {
+        self.stats.moves += 1;
+        self.term.move_to(pos)
+    }
+
+    fn write(&mut self, ch: char) -> std::io::Result<()> {
+        self.term.write(ch)
+    }
+
+    fn write_glyph(&mut self, glyph: &str, width: i32) -> std::io::Result<()> {
+        self.stats.glyphs += 1;
+        self.term.write_glyph(glyph, width)
+    }
+
+    fn move_up(&mut self, rows: i32) -> std::io::Result<()> {
+        self.stats.moves += 1;
+        self.term.move_up(rows)
+    }
+
+    fn move_down(&mut self, rows: i32) -> std::io::Result<()> {
+        self.stats.moves += 1;
+        self.term.move_down(rows)
+    }
+
+    fn move_to_column(&mut self, col: i32) -> std::io::Result<()> {
+        self.stats.moves += 1;
+        self.term.move_to_column(col)
+    }
+
+    fn clear_below(&mut self) -> std::io::Result<()> {
+        self.stats.erases += 1;
+        self.term.clear_below()
+    }
+
+    fn move_relative(&mut self, from: Pos2, to: Pos2) -> std::io::Result<()> {
+        self.stats.moves += 1;
+        self.term.move_relative(from, to)
+    }
+
+    fn erase_chars(&mut self, count: usize) -> std::io::Result<()> {
+        self.stats.erases += 1;
+        self.term.erase_chars(count)
+    }
+
+    fn erase_line(&mut self) -> std::io::Result<()> {
+        self.stats.erases += 1;
+        self.term.erase_line()
+    }
+
+    fn repeat(&mut self, glyph: &str, width: i32, count: usize) -> std::io::Result<()> {
+        self.stats.glyphs += count;
+        self.term.repeat(glyph, width, count)
+    }
+
+    fn can_scroll(&self) -> bool {
+        self.term.can_scroll()
+    }
+
+    fn scroll(&mut self, top: i32, bottom: i32, rows: i32) -> std::io::Result<()> {
+        self.stats.scrolled_rows += rows.unsigned_abs() as usize;
+        self.term.scroll(top, bottom, rows)
+    }
+
+    fn color_depth(&self) -> ColorDepth {
+        self.term.color_depth()
+    }
+
+    fn set_fg(&mut self, rgb: Rgba) -> std::io::Result<()> {
+        self.term.set_fg(rgb)
+    }
+
+    fn set_bg(&mut self, rgb: Rgba) -> std::io::Result<()> {
+        self.term.set_bg(rgb)
+    }
+
+    fn set_attr(&mut self, attr: Attribute) -> std::io::Result<()> {
+        self.term.set_attr(attr)
+    }
+
+    fn reset_fg(&mut self) -> std::io::Result<()> {
+        self.term.reset_fg()
+    }
+
+    fn reset_bg(&mut self) -> std::io::Result<()> {
+        self.term.reset_bg()
+    }
+
+    fn reset_attr(&mut self) -> std::io::Result<()> {
+        self.term.reset_attr()
+    }
+
+    fn set_underline_color(&mut self, rgb: Rgba) -> std::io::Result<()> {
+        self.term.set_underline_color(rgb)
+    }
+
+    fn reset_underline_color(&mut self) -> std::io::Result<()> {
+        self.term.reset_underline_color()
+    }
+
+    fn start_link(&mut self, link: Link) -> std::io::Result<()> {
+        self.term.start_link(link)
+    }
+
+    fn end_link(&mut self) -> std::io::Result<()> {
+        self.term.end_link()
+    }
+
+    fn draw_image(&mut self, image: &Image, rect: Rect) -> std::io::Result<()> {
+        self.term.draw_image(image, rect)
+    }
+
+    fn remove_image(&mut self, image: &Image, rect: Rect) -> std::io::Result<()> {
+        self.term.remove_image(image, rect)
+    }
+
+    fn load_image(&mut self, image: &Image) -> std::io::Result<()> {
+        self.term.load_image(image)
+    }
+
+    fn unload_image(&mut self, image: &Image) -> std::io::Result<()> {
+        self.term.unload_image(image)
+    }
+
+    fn capture_mouse(&mut self) -> std::io::Result<()> {
+        self.term.capture_mouse()
+    }
+
+    fn release_mouse(&mut self) -> std::io::Result<()> {
+        self.term.release_mouse()
+    }
+
+    fn enable_bracketed_paste(&mut self) -> std::io::Result<()> {
+        self.term.enable_bracketed_paste()
+    }
+
+    fn enable_focus_reporting(&mut self) -> std::io::Result<()> {
+        self.term.enable_focus_reporting()
+    }
+
+    fn disable_focus_reporting(&mut self) -> std::io::Result<()> {
+        self.term.disable_focus_reporting()
+    }
+
+    fn disable_bracketed_paste(&mut self) -> std::io::Result<()> {
+        self.term.disable_bracketed_paste()
+    }
+
+    fn push_keyboard_enhancement(&mut self) -> std::io::Result<()> {
+        self.term.push_keyboard_enhancement()
+    }
+
+    fn pop_keyboard_enhancement(&mut self) -> std::io::Result<()> {
+        self.term.pop_keyboard_enhancement()
+    }
+
+    fn show_cursor(&mut self) -> std::io::Result<()> {
+        self.term.show_cursor()
+    }
+
+    fn hide_cursor(&mut self) -> std::io::Result<()> {
+        self.term.hide_cursor()
+    }
+
+    fn set_cursor_shape(&mut self, shape: CursorShape, blinking: bool) -> std::io::Result<()> {
+        self.term.set_cursor_shape(shape, blinking)
+    }
+
+    fn reset_cursor_shape(&mut self) -> std::io::Result<()> {
+        self.term.reset_cursor_shape()
+    }
+
+    fn clear_screen(&mut self) -> std::io::Result<()> {
+        self.term.clear_screen()
+    }
+
+    fn enter_alt_screen(&mut self) -> std::io::Result<()> {
+        self.term.enter_alt_screen()
+    }
+
+    fn leave_alt_screen(&mut self) -> std::io::Result<()> {
+        self.term.leave_alt_screen()
+    }
+
+    fn enable_line_wrap(&mut self) -> std::io::Result<()> {
+        self.term.enable_line_wrap()
+    }
+
+    fn disable_line_wrap(&mut self) -> std::io::Result<()> {
+        self.term.disable_line_wrap()
+    }
+
+    fn set_title(&mut self, title: &str) -> std::io::Result<()> {
+        self.term.set_title(title)
+    }
+
+    fn set_clipboard(&mut self, data: &str) -> std::io::Result<()> {
+        self.term.set_clipboard(data)
+    }
+
+    fn request_clipboard(&mut self) -> std::io::Result<()> {
+        self.term.request_clipboard()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        geom::{pos2, vec2},
+        paint::{Cell, Surface},
+    };
+
+    // the stats are checked against what the terminal renderer actually wrote
+    fn render(surface: &mut Surface) -> (RenderStats, String) {
+        let stats = StatsRenderer::default()
+            .with_repeat(true)
+            .with_scrolling(true);
+        let term = TermRenderer::new(Vec::new())
+            .with_repeat(true)
+            .with_scrolling(true);
+        let mut both = (stats, term);
+        surface.render(&mut both).unwrap();
+        let out = String::from_utf8(std::mem::take(both.1.get_mut())).unwrap();
+        (both.0.stats(), out)
+    }
+
+    fn fill_row(surface: &mut Surface, y: i32, ch: char) {
+        for x in 0..surface.rect().width() {
+            surface.current_mut()[pos2(x, y)] = Cell::new(ch);
+        }
+    }
+
+    #[test]
+    fn repeated_glyphs() {
+        let mut surface = Surface::new(vec2(10, 1));
+        fill_row(&mut surface, 0, 'x');
+
+        let (stats, out) = render(&mut surface);
+        assert!(out.contains("x\x1b[9b"), "{out:?}");
+        assert_eq!(stats.bytes, out.len());
+        assert_eq!(stats.glyphs, 10);
+        assert_eq!(stats.moves, 2);
+        assert_eq!(stats.erases, 0);
+    }
+
+    #[test]
+    fn erased_runs() {
+        let mut surface = Surface::new(vec2(20, 2));
+        fill_row(&mut surface, 0, 'x');
+        fill_row(&mut surface, 1, 'x');
+        render(&mut surface);
+
+        fill_row(&mut surface, 0, ' ');
+        for x in 2..12 {
+            surface.current_mut()[pos2(x, 1)] = Cell::EMPTY;
+        }
+
+        let (stats, out) = render(&mut surface);
+        assert!(out.contains("\x1b[K"), "{out:?}");
+        assert!(out.contains("\x1b[10X"), "{out:?}");
+        assert_eq!(stats.bytes, out.len());
+        assert_eq!(stats.erases, 2);
+        assert_eq!(stats.glyphs, 0);
+    }
+
+    #[test]
+    fn scrolled_rows() {
+        let mut surface = Surface::new(vec2(10, 4));
+        for (y, ch) in (0..4).zip('a'..) {
+            fill_row(&mut surface, y, ch);
+        }
+        render(&mut surface);
+
+        for (y, ch) in (0..4).zip('b'..) {
+            fill_row(&mut surface, y, ch);
+        }
+
+        let (stats, out) = render(&mut surface);
+        assert!(out.contains("\x1b[1;4r\x1b[1S\x1b[r"), "{out:?}");
+        assert_eq!(stats.bytes, out.len());
+        assert_eq!(stats.scrolled_rows, 1);
+        // only the row that scrolled in is drawn
+        assert_eq!(stats.glyphs, 10);
+    }
+
+    #[test]
+    fn big_scrolls_are_found() {
+        let mut surface = Surface::new(vec2(20, 300));
+        for y in 0..300 {
+            fill_row(
+                &mut surface,
+                y,
+                char::from_u32(0x100 + y as u32 / 2).unwrap(),
+            );
+        }
+        render(&mut surface);
+
+        for y in 0..300 {
+            fill_row(
+                &mut surface,
+                y,
+                char::from_u32(0x100 + (y as u32 + 40) / 2).unwrap(),
+            );
+        }
+        let (stats, _) = render(&mut surface);
+        assert_eq!(stats.scrolled_rows, 40);
+    }
+
+    #[test]
+    fn plain_terminals_get_neither_rep_nor_scrolling() {
+        let mut surface = Surface::new(vec2(10, 2));
+        fill_row(&mut surface, 0, 'a');
+        fill_row(&mut surface, 1, 'b');
+        render(&mut surface);
+
+        fill_row(&mut surface, 0, 'b');
+        fill_row(&mut surface, 1, 'c');
+        let mut both = (StatsRenderer::default(), TermRenderer::new(Vec::new()));
+        surface.render(&mut both).unwrap();
+        let out = String::from_utf8(std::mem::take(both.1.get_mut())).unwrap();
+
+        assert!(out.contains("bbbbbbbbbb"), "{out:?}");
+        assert!(!out.contains("\x1b[9b"), "{out:?}");
+        assert!(!out.contains("\x1b[1;2r"), "{out:?}");
+        assert_eq!(both.0.stats().scrolled_rows, 0);
+        assert_eq!(both.0.stats().bytes, out.len());
+    }
+
+    #[test]
+    fn fewer_colors_are_counted() {
+        let red = Rgba::new(255, 0, 0);
+        let mut surface = Surface::new(vec2(1, 1));
+        surface.current_mut()[pos2(0, 0)] = Cell::new('x').fg(red);
+
+        let mut both = (
+            StatsRenderer::default().with_color_depth(ColorDepth::Ansi16),
+            TermRenderer::new(Vec::new()).with_color_depth(ColorDepth::Ansi16),
+        );
+        surface.render(&mut both).unwrap();
+        let out = String::from_utf8(std::mem::take(both.1.get_mut())).unwrap();
+        assert!(!out.contains("38;2"), "{out:?}");
+        assert_eq!(both.0.stats().bytes, out.len());
+    }
+}
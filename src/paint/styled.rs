@@ -3,13 +3,21 @@ use crate::{
     geom::{pos2, Align, Align2, Pos2, Vec2},
 };
 
-use super::{shape::Shape, Attribute, Cell, Label};
+use unicode_segmentation::UnicodeSegmentation as _;
+
+use super::{
+    label::{grapheme_width, next_tab_stop},
+    shape::Shape,
+    Attribute, Cell, Label, Link,
+};
 
 #[derive(Copy, Clone, Debug)]
 pub struct Styled<T: Label> {
     fg: Color,
     bg: Color,
     attr: Option<Attribute>,
+    underline: Color,
+    link: Option<Link>,
     align: Align2,
     pub label: T,
 }
@@ -32,6 +40,8 @@ impl<T: Label> Styled<T> {
             fg: Color::Reset,
             bg: Color::Reuse,
             attr: None,
+            underline: Color::Reset,
+            link: None,
             align: Align2::LEFT_TOP,
             label,
         }
@@ -63,6 +73,31 @@ impl<T: Label> Styled<T> {
         self.attr(Attribute::UNDERLINE)
     }
 
+    pub fn double_underline(self) -> Self {
+        self.attr(Attribute::DOUBLE_UNDERLINE)
+    }
+
+    pub fn curly_underline(self) -> Self {
+        self.attr(Attribute::CURLY_UNDERLINE)
+    }
+
+    pub fn dotted_underline(self) -> Self {
+        self.attr(Attribute::DOTTED_UNDERLINE)
+    }
+
+    pub fn dashed_underline(self) -> Self {
+        self.attr(Attribute::DASHED_UNDERLINE)
+    }
+
+    pub fn underline_color(mut self, underline: impl Into<Color>) -> Self {
+        self.underline = underline.into();
+        self
+    }
+
+    pub fn overline(self) -> Self {
+        self.attr(Attribute::OVERLINE)
+    }
+
     pub fn blink(self) -> Self {
         self.attr(Attribute::BLINK)
     }
@@ -75,6 +110,12 @@ impl<T: Label> Styled<T> {
         self.attr(Attribute::STRIKEOUT)
     }
 
+    // makes the text a hyperlink in terminals that support OSC 8
+    pub fn link(mut self, link: impl Into<Link>) -> Self {
+        self.link = Some(link.into());
+        self
+    }
+
     pub fn attr(mut self, attr: impl Into<Option<Attribute>>) -> Self {
         self.attr = attr.into();
         self
@@ -103,11 +144,25 @@ impl<T: Label> Styled<T> {
         self.label.size()
     }
 
+    // whether both would draw the same cells, labels don't have to be comparable for this
+    pub(crate) fn same_as(&self, other: &Self) -> bool {
+        self.fg == other.fg
+            && self.bg == other.bg
+            && self.attr == other.attr
+            && self.underline == other.underline
+            && self.link == other.link
+            && self.align == other.align
+            && self.label.size() == other.label.size()
+            && self.label.chars().eq(other.label.chars())
+    }
+
     pub fn into_static(self) -> Styled<T::Static> {
         Styled {
             fg: self.fg,
             bg: self.bg,
             attr: self.attr,
+            underline: self.underline,
+            link: self.link,
             align: self.align,
             label: self.label.into_static(),
         }
@@ -129,18 +184,49 @@ impl<T: Label> Shape for Styled<T> {
             Align::Max => size.y.saturating_sub(item_size.y),
         };
 
+        let cell = |grapheme| {
+            Cell::grapheme(grapheme)
+                .fg(self.fg)
+                .bg(self.bg)
+                .attr(self.attr)
+                .underline_color(self.underline)
+                .link(self.link)
+        };
+
+        // labels only give us chars, combining marks and the like need the whole string
+        let text = self.label.chars().collect::<String>();
+
         let mut start = pos2(x, y);
-        for ch in self.label.chars() {
+        for grapheme in text.graphemes(true) {
             if start.x >= size.x || start.y >= size.y {
                 break;
             }
-            if ch == '\n' {
-                start.y += 1;
-                start.x = x;
-                continue;
+
+            match grapheme {
+                "\n" | "\r\n" => {
+                    start.y += 1;
+                    start.x = x;
+                }
+                "\t" => {
+                    let stop = x + next_tab_stop(start.x - x);
+                    while start.x < stop.min(size.x) {
+                        put(start, cell(" "));
+                        start.x += 1;
+                    }
+                }
+                grapheme => match grapheme_width(grapheme) {
+                    0 => {}
+                    // the rest of a wide glyph wouldn't fit, so it'd get cut in half
+                    width if start.x + width > size.x => {
+                        put(start, cell(" "));
+                        break;
+                    }
+                    width => {
+                        put(start, cell(grapheme));
+                        start.x += width;
+                    }
+                },
             }
-            put(start, Cell::new(ch).fg(self.fg).bg(self.bg).attr(self.attr));
-            start.x += 1;
         }
     }
 }
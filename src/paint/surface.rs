@@ -1,44 +1,100 @@
+use std::{
+    ops::Range,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
 use crate::{
-    color::Color,
-    geom::{Pos2, Rect, Vec2},
+    color::{Color, ColorDepth},
+    geom::{pos2, vec2, Pos2, Rect, Vec2},
 };
 
-use super::{cell::CellAttr, shape::Shape, Buffer, Cell, Renderer};
+use super::{
+    cell::{CellAttr, Glyph, CLUSTERS},
+    image::Placement,
+    link::LINKS,
+    shape::Shape,
+    Buffer, Cell, Cursor, CursorShape, Image, Renderer,
+};
 
 #[derive(Debug)]
 pub struct Surface {
     front: Buffer,
     // we always draw to the back buffer
     back: Buffer,
+    // where the cursor was asked for this frame, and where it is on the screen
+    cursor: Option<Cursor>,
+    shown: Option<Cursor>,
+    // whether the terminal is showing its cursor, it may be before anyone asked for it
+    visible: bool,
+    // the same for images
+    images: Vec<Placement>,
+    placed: Vec<Placement>,
+    // the back buffer still has all of the last frame in it, so parts of it can be drawn again
+    complete: bool,
+    // the cells being drawn this frame, or all of them if this is None
+    damage: Option<Vec<bool>>,
+    // what the interned clusters and links know this surface by
+    id: u64,
 }
 
 impl Surface {
     pub fn new(size: Vec2) -> Self {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        CLUSTERS.add_holder(id);
+        LINKS.add_holder(id);
+
         Self {
             front: Buffer::new(size),
             back: Buffer::new(size),
+            cursor: None,
+            shown: None,
+            visible: false,
+            images: Vec::new(),
+            placed: Vec::new(),
+            complete: false,
+            damage: None,
+            id,
         }
     }
 
     pub fn resize(&mut self, size: Vec2) {
         const DIRTY: Cell = Cell {
-            char: '!',
+            glyph: Glyph::Char('!'),
             ..Cell::EMPTY
         };
 
         self.back.resize(size, Cell::EMPTY);
         self.front.resize(size, DIRTY);
+        self.shown = None;
+        self.complete = false;
+        self.damage = None;
     }
 
     pub const fn current(&self) -> &Buffer {
         &self.back
     }
 
+    // what was last rendered
+    pub const fn screen(&self) -> &Buffer {
+        &self.front
+    }
+
+    // where the cursor was asked for this frame
+    pub const fn cursor(&self) -> Option<Cursor> {
+        self.cursor
+    }
+
+    // for when the terminal was told to show or hide its cursor outside of a render
+    pub fn set_cursor_visible(&mut self, visible: bool) {
+        self.visible = visible
+    }
+
     pub fn current_mut(&mut self) -> &mut Buffer {
         &mut self.back
     }
 
-    pub fn crop(&mut self, rect: Rect) -> CroppedSurface {
+    pub fn crop(&mut self, rect: Rect) -> CroppedSurface<'_> {
         // TODO assert the rect is inside of our rect
         // future pos are relative to rect
         // clip outside of their rect,
@@ -50,17 +106,62 @@ impl Surface {
     }
 
     pub fn get_mut(&mut self, pos: Pos2) -> Option<&mut Cell> {
+        if !self.is_damaged(pos) {
+            return None;
+        }
         self.current_mut().get_mut(pos)
     }
 
     pub fn erase(&mut self) {
         self.back.reset();
+        self.images.clear();
+        self.cursor = None;
+        self.complete = true;
+        self.damage = None;
     }
 
-    pub fn draw(&mut self, shape: impl Shape) {
-        shape.draw(self.rect().size(), |pos, cell| {
+    // only `damage` is erased, and nothing outside of it can be drawn over until the next frame.
+    // this is false if the last frame isn't around anymore, then everything has to be erased
+    pub fn erase_damaged(&mut self, damage: &[Rect]) -> bool {
+        if !self.complete {
+            return false;
+        }
+
+        let area = self.back.rect();
+        let mut mask = vec![false; area.area().max(0) as usize];
+        for rect in damage {
+            let rect = rect.intersection(area);
+            for y in rect.top()..=rect.bottom() {
+                for x in rect.left()..=rect.right() {
+                    mask[(y * area.width() + x) as usize] = true;
+                    self.back[pos2(x, y)] = Cell::EMPTY;
+                }
+            }
+        }
+
+        self.images
+            .retain(|placement| !damage.iter().any(|rect| rect.intersects(placement.rect)));
+        if let Some(cursor) = self.cursor {
+            if damage.iter().any(|rect| rect.contains(cursor.pos)) {
+                self.cursor = None
+            }
+        }
+
+        self.damage = Some(mask);
+        true
+    }
+
+    fn is_damaged(&self, pos: Pos2) -> bool {
+        let Some(mask) = &self.damage else {
+            return true;
+        };
+        self.back.contains(pos) && mask[(pos.y * self.back.rect().width() + pos.x) as usize]
+    }
+
+    fn put_damaged(&mut self, pos: Pos2, cell: Cell) {
+        if self.is_damaged(pos) {
             Self::put(&mut self.back, pos, cell)
-        })
+        }
     }
 
     pub fn rect(&self) -> Rect {
@@ -68,21 +169,95 @@ impl Surface {
     }
 
     pub fn render(&mut self, renderer: &mut impl Renderer) -> std::io::Result<()> {
+        self.hold();
+        let clusters = CLUSTERS.lock();
+
+        // colors are compared after quantizing so ones that end up the same aren't sent twice
+        let depth = renderer.color_depth();
+        let quantize = |color| match color {
+            // without colors everything is left at the terminal's default
+            _ if depth == ColorDepth::Monochrome => Color::Reuse,
+            Color::Rgba(rgba) => Color::Rgba(depth.quantize(rgba)),
+            color => color,
+        };
+
         let mut state = CursorState::default();
         let mut seen = false;
         let mut wrote_reset = false;
+        let mut link = None;
+        let width = self.back.rect().width();
+
+        let images = std::mem::take(&mut self.images);
+        let mut damage = self.damage.take();
+
+        // the terminal can move the rows itself, then only the ones that came in are drawn.
+        // images don't scroll the same way everywhere, so they turn this off
+        if renderer.can_scroll() && images.is_empty() && self.placed.is_empty() {
+            if let Some(rows) = self.find_scroll() {
+                renderer.begin()?;
+                seen = true;
+                renderer.scroll(0, self.front.rect().height() - 1, rows)?;
+                self.front.scroll(rows, Cell::EMPTY);
+                damage = None;
+            }
+        }
 
-        for (pos, change) in self.front.diff(&self.back) {
+        // whatever was under an image that went away has to be drawn again
+        for removed in self.placed.iter().filter(|p| !images.contains(p)) {
             if !seen {
                 renderer.begin()?;
                 seen = true;
             }
+            renderer.remove_image(&removed.image, removed.rect)?;
+            Self::invalidate(&mut self.front, removed.rect);
+            if let Some(mask) = &mut damage {
+                Self::invalidate_mask(mask, width, removed.rect);
+            }
+        }
+
+        let ranges = match &damage {
+            Some(mask) => Self::damaged_ranges(mask, width),
+            None => std::iter::once(0..self.back.cells.len()).collect(),
+        };
+
+        // images are only sent when they're new, moved, or something drew over them
+        let mut send: Vec<_> = images.iter().map(|p| !self.placed.contains(p)).collect();
+
+        // the terminal draws continuations when it draws the glyph to their left
+        let mut changes = self
+            .front
+            .diff(&self.back, &ranges)
+            .filter(|(_, change)| change.glyph != Glyph::Continuation)
+            .peekable();
+
+        while let Some((pos, &change)) = changes.next() {
+            // the same cell over and over can be sent once
+            let mut run = 1;
+            if matches!(change.glyph, Glyph::Char(..)) && change.glyph.width() == 1 {
+                while changes
+                    .next_if(|&(next, cell)| next == pos + vec2(run, 0) && *cell == change)
+                    .is_some()
+                {
+                    run += 1;
+                }
+            }
 
-            if state.maybe_move(pos) {
-                renderer.move_to(pos)?;
+            if !seen {
+                renderer.begin()?;
+                seen = true;
             }
 
+            state.move_to(renderer, pos, width)?;
+
+            let previous = state.attr;
             match state.maybe_attr(change.attr) {
+                // attributes are only ever turned on, so the old ones are cleared first.
+                // that also clears the colors, so they're sent again
+                Some(CellAttr::Attr(attr)) if matches!(previous, Some(CellAttr::Attr(..))) => {
+                    wrote_reset = true;
+                    renderer.reset_attr()?;
+                    renderer.set_attr(attr)?
+                }
                 Some(CellAttr::Attr(attr)) => {
                     wrote_reset = false;
                     renderer.set_attr(attr)?
@@ -94,44 +269,301 @@ impl Surface {
                 _ => {}
             }
 
-            match state.maybe_fg(change.fg, wrote_reset) {
+            match state.maybe_fg(quantize(change.fg), wrote_reset) {
                 Some(Color::Rgba(fg)) => renderer.set_fg(fg)?,
                 Some(Color::Reset) => renderer.reset_fg()?,
                 _ => {}
             }
 
-            match state.maybe_bg(change.bg, wrote_reset) {
+            match state.maybe_bg(quantize(change.bg), wrote_reset) {
                 Some(Color::Rgba(bg)) => renderer.set_bg(bg)?,
                 Some(Color::Reset) => renderer.reset_bg()?,
                 _ => {}
             }
 
+            match state.maybe_underline(quantize(change.underline), wrote_reset) {
+                Some(Color::Rgba(underline)) => renderer.set_underline_color(underline)?,
+                Some(Color::Reset) => renderer.reset_underline_color()?,
+                _ => {}
+            }
+
+            // links stay open across cursor moves, so they only change when a cell's does
+            if change.link != link {
+                if link.is_some() {
+                    renderer.end_link()?;
+                }
+                if let Some(new) = change.link {
+                    renderer.start_link(new)?;
+                }
+                link = change.link;
+            }
+
             wrote_reset = false;
-            renderer.write(change.char)?;
+
+            // erasing only fills in the background, so anything with attributes has to be written
+            let blank = change.glyph == Glyph::Char(' ')
+                && change.attr == CellAttr::Reset
+                && change.link.is_none();
+            let glyph_width = change.glyph.width();
+            match run as usize {
+                run if blank && run >= MIN_ERASE && pos.x + run as i32 == width => {
+                    renderer.erase_line()?;
+                    state.last = Some(pos - vec2(1, 0));
+                }
+                run if blank && run >= MIN_ERASE * 2 => {
+                    renderer.erase_chars(run)?;
+                    state.last = Some(pos - vec2(1, 0));
+                }
+                1 => {
+                    change
+                        .glyph
+                        .with_str_in(&clusters, |glyph| renderer.write_glyph(glyph, glyph_width))?;
+                    state.advance(pos, glyph_width);
+                }
+                count => {
+                    change.glyph.with_str_in(&clusters, |glyph| {
+                        renderer.repeat(glyph, glyph_width, count)
+                    })?;
+                    state.last = Some(pos + vec2(run - 1, 0));
+                }
+            }
+
+            let cells = Rect::from_min_size(pos, vec2(run, 1));
+            for (send, placement) in send.iter_mut().zip(&images) {
+                *send |= placement.rect.intersects(cells);
+            }
         }
+        drop(changes);
 
-        if seen {
-            if state.maybe_move(Pos2::ZERO) {
-                renderer.move_to(Pos2::ZERO)?;
+        let mut loaded: Vec<_> = self.placed.iter().map(|p| &p.image).collect();
+        for (placement, _) in images.iter().zip(&send).filter(|(_, send)| **send) {
+            if !seen {
+                renderer.begin()?;
+                seen = true;
             }
 
-            renderer.reset_bg()?;
-            renderer.reset_fg()?;
-            renderer.reset_attr()?;
-            renderer.end()?;
+            if link.take().is_some() {
+                renderer.end_link()?;
+            }
+
+            // the pixels are only sent once, however many times the image is placed
+            if !loaded.contains(&&placement.image) {
+                renderer.load_image(&placement.image)?;
+                loaded.push(&placement.image);
+            }
 
-            self.back.reset();
+            // images are drawn over the cells, and where the cursor ends up depends on the protocol
+            renderer.move_to(placement.rect.left_top())?;
+            renderer.draw_image(&placement.image, placement.rect)?;
+            state.last = None;
         }
 
-        Ok(())
+        // an image that was taken down everywhere isn't needed anymore
+        let mut unloaded = Vec::new();
+        for removed in &self.placed {
+            if images.iter().any(|p| p.image == removed.image) || unloaded.contains(&&removed.image)
+            {
+                continue;
+            }
+            unloaded.push(&removed.image);
+            if !seen {
+                renderer.begin()?;
+                seen = true;
+            }
+            renderer.unload_image(&removed.image)?;
+        }
+        self.placed.clone_from(&images);
+        self.images = images;
+
+        let cursor = self.cursor;
+        if !seen && cursor == self.shown && cursor.is_some() == self.visible {
+            return Ok(());
+        }
+
+        if !seen {
+            renderer.begin()?;
+        }
+
+        if link.is_some() {
+            renderer.end_link()?;
+        }
+
+        // the cursor is left where a widget asked for it, otherwise its hidden in the corner
+        let pos = cursor.map_or(Pos2::ZERO, |cursor| cursor.pos);
+        state.move_to(renderer, pos, width)?;
+
+        renderer.reset_bg()?;
+        renderer.reset_fg()?;
+        renderer.reset_attr()?;
+
+        match cursor {
+            Some(cursor) => {
+                let style = |c: Cursor| (c.shape, c.blinking);
+                if self.shown.map(style) != Some(style(cursor)) {
+                    renderer.set_cursor_shape(cursor.shape, cursor.blinking)?;
+                }
+                if !self.visible {
+                    renderer.show_cursor()?;
+                }
+            }
+            None if self.visible => renderer.hide_cursor()?,
+            None => {}
+        }
+        self.shown = cursor;
+        self.visible = cursor.is_some();
+
+        renderer.end()
     }
 
-    fn put(buffer: &mut Buffer, pos: Pos2, cell: Cell) {
+    // tells the interners which clusters and links are still on either buffer
+    fn hold(&self) {
+        let cells = || self.front.cells.iter().chain(&self.back.cells);
+        CLUSTERS.hold(self.id, || {
+            cells().filter_map(|cell| match cell.glyph {
+                Glyph::Cluster { id, .. } => Some(id),
+                _ => None,
+            })
+        });
+        LINKS.hold(self.id, || cells().filter_map(|cell| Some(cell.link?.id())));
+    }
+
+    // how many rows everything moved up by, when most of what changed is already on the screen.
+    //
+    // rows are compared by their hashes, so this stays cheap on big terminals. a collision
+    // only costs a worse scroll, everything is still diffed afterwards
+    fn find_scroll(&self) -> Option<i32> {
+        let (front, back) = (&self.front, &self.back);
+        let height = back.rect().height();
+
+        let state = ahash::RandomState::new();
+        let hash = |buffer: &Buffer| -> Vec<u64> {
+            (0..height).map(|y| state.hash_one(buffer.row(y))).collect()
+        };
+        let (old, new) = (hash(front), hash(back));
+
+        let changed: Vec<_> = (0..height as usize).map(|y| old[y] != new[y]).collect();
+        if changed.iter().filter(|&&c| c).count() * 2 < height as usize {
+            return None;
+        }
+
+        let moved = |rows: i32| {
+            (0..height)
+                .filter(|&y| {
+                    let from = y + rows;
+                    changed[y as usize]
+                        && (0..height).contains(&from)
+                        && new[y as usize] == old[from as usize]
+                })
+                .count()
+        };
+
+        // half the rows have to have moved, which can't happen with a bigger scroll than that.
+        // the smallest scroll wins a tie
+        (1..=height / 2)
+            .flat_map(|rows| [rows, -rows])
+            .map(|rows| (moved(rows), rows))
+            .fold(None, |best: Option<(usize, i32)>, (n, rows)| match best {
+                Some((most, _)) if most >= n => best,
+                _ => Some((n, rows)),
+            })
+            .filter(|&(n, _)| n * 2 >= height as usize)
+            .map(|(_, rows)| rows)
+    }
+
+    fn put(buffer: &mut Buffer, pos: Pos2, mut cell: Cell) {
         if !pos.is_normalized() || !buffer.contains(pos) {
             return;
         }
 
+        // zero width glyphs would put the terminal's cursor out of step with ours,
+        // and a wide glyph is never split across the edge
+        let right = pos + vec2(1, 0);
+        let width = cell.glyph.width();
+        if width == 0 || (width > 1 && !buffer.contains(right)) {
+            cell.glyph = Glyph::Char(' ');
+        }
+
+        Self::break_wide(buffer, pos);
         Self::merge_cell(&mut buffer[pos], cell);
+
+        if cell.glyph.width() > 1 {
+            Self::break_wide(buffer, right);
+            buffer[right] = Cell {
+                glyph: Glyph::Continuation,
+                ..buffer[pos]
+            };
+        }
+    }
+
+    fn invalidate(buffer: &mut Buffer, rect: Rect) {
+        const DIRTY: Cell = Cell {
+            glyph: Glyph::Char('!'),
+            ..Cell::EMPTY
+        };
+
+        for y in rect.top()..=rect.bottom() {
+            for x in rect.left()..=rect.right() {
+                if buffer.contains(pos2(x, y)) {
+                    buffer[pos2(x, y)] = DIRTY;
+                }
+            }
+        }
+    }
+
+    fn invalidate_mask(mask: &mut [bool], width: i32, rect: Rect) {
+        for y in rect.top()..=rect.bottom() {
+            for x in rect.left()..=rect.right() {
+                if (0..width).contains(&x) && y >= 0 {
+                    if let Some(damaged) = mask.get_mut((y * width + x) as usize) {
+                        *damaged = true
+                    }
+                }
+            }
+        }
+    }
+
+    // runs of damaged cells in each row. they're one wider on each side because
+    // breaking a wide glyph at the edge of the damage changes the cell next to it
+    fn damaged_ranges(mask: &[bool], width: i32) -> Vec<Range<usize>> {
+        let width = width.max(0) as usize;
+        let mut ranges: Vec<Range<usize>> = Vec::new();
+        if width == 0 {
+            return ranges;
+        }
+
+        for (y, row) in mask.chunks(width).enumerate() {
+            let mut x = 0;
+            while x < width {
+                if !row[x] {
+                    x += 1;
+                    continue;
+                }
+                let start = x;
+                while x < width && row[x] {
+                    x += 1;
+                }
+
+                let range = y * width + start.saturating_sub(1)..y * width + (x + 1).min(width);
+                match ranges.last_mut() {
+                    Some(last) if last.end >= range.start => last.end = last.end.max(range.end),
+                    _ => ranges.push(range),
+                }
+            }
+        }
+        ranges
+    }
+
+    // overwriting half of a wide glyph leaves a space in the other half
+    fn break_wide(buffer: &mut Buffer, pos: Pos2) {
+        match buffer[pos].glyph {
+            Glyph::Continuation if pos.x > 0 => {
+                buffer[pos - vec2(1, 0)].glyph = Glyph::Char(' ');
+            }
+            glyph if glyph.width() > 1 && buffer.contains(pos + vec2(1, 0)) => {
+                buffer[pos + vec2(1, 0)].glyph = Glyph::Char(' ');
+            }
+            _ => {}
+        }
     }
 
     fn merge_cell(cell: &mut Cell, new_cell: Cell) {
@@ -146,8 +578,20 @@ impl Surface {
             (Color::Reuse, _) => {}
         }
 
-        cell.char = new_cell.char;
+        if new_cell.underline != Color::Reuse {
+            cell.underline = new_cell.underline;
+        }
+
+        cell.glyph = new_cell.glyph;
         cell.attr = new_cell.attr;
+        cell.link = new_cell.link;
+    }
+}
+
+impl Drop for Surface {
+    fn drop(&mut self) {
+        CLUSTERS.remove_holder(self.id);
+        LINKS.remove_holder(self.id);
     }
 }
 
@@ -171,7 +615,7 @@ impl<'a> CroppedSurface<'a> {
     pub fn draw(&mut self, shape: impl Shape) {
         shape.draw(self.rect.size(), |pos, cell| {
             let pos = self.translate(pos);
-            Surface::put(&mut self.surface.back, pos, cell)
+            self.surface.put_damaged(pos, cell)
         })
     }
 
@@ -179,20 +623,77 @@ impl<'a> CroppedSurface<'a> {
         self.surface.get_mut(pos)
     }
 
+    // the last one to ask this frame gets the cursor
+    pub fn set_cursor(&mut self, pos: Pos2, shape: CursorShape, blinking: bool) {
+        if self.rect.contains(pos) {
+            self.surface.cursor = Some(Cursor {
+                pos,
+                shape,
+                blinking,
+            });
+        }
+    }
+
+    // the image covers `rect`, which has to be inside of this surface's rect
+    pub fn place_image(&mut self, image: &Image, rect: Rect) {
+        if self.rect.intersection(rect) != rect {
+            return;
+        }
+
+        for y in rect.top()..=rect.bottom() {
+            for x in rect.left()..=rect.right() {
+                self.surface.put_damaged(pos2(x, y), Cell::EMPTY)
+            }
+        }
+
+        // it's still placed if it was outside of the damage
+        let placement = Placement {
+            image: image.clone(),
+            rect,
+        };
+        if !self.surface.images.contains(&placement) {
+            self.surface.images.push(placement);
+        }
+    }
+
     fn translate(&self, pos: Pos2) -> Pos2 {
         pos + self.rect.left_top()
     }
 }
 
+// erasing moves the cursor less than writing does, so short runs are just written
+const MIN_ERASE: usize = 4;
+
 #[derive(Default)]
 struct CursorState {
     last: Option<Pos2>,
     fg: Option<Color>,
     bg: Option<Color>,
+    underline: Option<Color>,
     attr: Option<CellAttr>,
 }
 
 impl CursorState {
+    fn move_to(
+        &mut self,
+        renderer: &mut impl Renderer,
+        pos: Pos2,
+        width: i32,
+    ) -> std::io::Result<()> {
+        // the terminal's cursor stops on the last column rather than going past it
+        let from = self
+            .last
+            .map(|last| last + vec2(1, 0))
+            .filter(|from| from.x < width);
+        if !self.maybe_move(pos) {
+            return Ok(());
+        }
+        match from {
+            Some(from) => renderer.move_relative(from, pos),
+            None => renderer.move_to(pos),
+        }
+    }
+
     fn maybe_move(&mut self, pos: Pos2) -> bool {
         let should_move = match self.last {
             Some(last) if last.y != pos.y || last.x != pos.x - 1 => true,
@@ -204,6 +705,13 @@ impl CursorState {
         should_move
     }
 
+    // a wide glyph moves the cursor past its continuation
+    fn advance(&mut self, pos: Pos2, width: i32) {
+        if width > 1 {
+            self.last = Some(pos + vec2(width - 1, 0));
+        }
+    }
+
     fn maybe_fg(&mut self, color: Color, resetting: bool) -> Option<Color> {
         Self::maybe_color(color, resetting, &mut self.fg)
     }
@@ -212,6 +720,10 @@ impl CursorState {
         Self::maybe_color(color, resetting, &mut self.bg)
     }
 
+    fn maybe_underline(&mut self, color: Color, resetting: bool) -> Option<Color> {
+        Self::maybe_color(color, resetting, &mut self.underline)
+    }
+
     fn maybe_color(color: Color, resetting: bool, cache: &mut Option<Color>) -> Option<Color> {
         if matches!(color, Color::Reuse) {
             return None;
@@ -246,3 +758,131 @@ impl CursorState {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{color::Rgba, paint::Attribute, paint::TermRenderer};
+
+    fn render(surface: &mut Surface) -> String {
+        let mut renderer = TermRenderer::new(Vec::new()).with_synchronized_output(false);
+        surface.render(&mut renderer).unwrap();
+        String::from_utf8(std::mem::take(renderer.get_mut())).unwrap()
+    }
+
+    fn render_kitty(surface: &mut Surface, image: &Image, rects: &[Rect]) -> String {
+        surface.erase();
+        let area = surface.rect();
+        for &rect in rects {
+            surface.crop(area).place_image(image, rect);
+        }
+
+        let mut renderer = TermRenderer::new(Vec::new())
+            .with_synchronized_output(false)
+            .with_graphics(crate::paint::Graphics::Kitty, vec2(10, 20));
+        surface.render(&mut renderer).unwrap();
+        String::from_utf8(std::mem::take(renderer.get_mut())).unwrap()
+    }
+
+    #[test]
+    fn kitty_placements() {
+        let image = Image::new(1, 1, vec![255; 4]);
+        let a = Rect::from_min_size(pos2(0, 0), vec2(2, 1));
+        let b = Rect::from_min_size(pos2(4, 0), vec2(2, 1));
+        let c = Rect::from_min_size(pos2(0, 2), vec2(2, 1));
+        let mut surface = Surface::new(vec2(8, 4));
+
+        let out = render_kitty(&mut surface, &image, &[a]);
+        assert_eq!(out.matches("a=t,").count(), 1, "{out:?}");
+        assert_eq!(out.matches("a=p,").count(), 1, "{out:?}");
+
+        // the pixels are already there for the second one
+        let out = render_kitty(&mut surface, &image, &[a, b]);
+        assert_eq!(out.matches("a=t,").count(), 0, "{out:?}");
+        assert_eq!(out.matches("a=p,").count(), 1, "{out:?}");
+        assert_eq!(out.matches("a=d,").count(), 0, "{out:?}");
+
+        // moving one doesn't touch the other
+        let out = render_kitty(&mut surface, &image, &[a, c]);
+        assert_eq!(out.matches("a=t,").count(), 0, "{out:?}");
+        assert_eq!(out.matches("a=p,").count(), 1, "{out:?}");
+        assert_eq!(out.matches("a=d,d=i,").count(), 1, "{out:?}");
+        assert!(!out.contains("d=I"), "{out:?}");
+
+        let out = render_kitty(&mut surface, &image, &[]);
+        assert_eq!(out.matches("a=d,d=i,").count(), 2, "{out:?}");
+        assert_eq!(out.matches("a=d,d=I,").count(), 1, "{out:?}");
+    }
+
+    #[test]
+    fn changing_attributes_clears_the_old_ones() {
+        let red = Rgba::new(255, 0, 0);
+        let mut surface = Surface::new(vec2(2, 1));
+        surface.current_mut()[pos2(0, 0)] = Cell::new('a')
+            .fg(red)
+            .attr(Attribute::ITALIC | Attribute::CURLY_UNDERLINE);
+        surface.current_mut()[pos2(1, 0)] = Cell::new('b').fg(red).attr(Attribute::BOLD);
+
+        let out = render(&mut surface);
+        let (_, between) = out.split_once('a').unwrap();
+        let (between, _) = between.split_once('b').unwrap();
+        assert_eq!(
+            between, "\x1b[0m\x1b[1m\x1b[38;2;255;0;0m\x1b[59m",
+            "{out:?}"
+        );
+    }
+
+    #[test]
+    fn unchanged_attributes_are_not_sent_again() {
+        let mut surface = Surface::new(vec2(2, 1));
+        surface.current_mut()[pos2(0, 0)] = Cell::new('a').attr(Attribute::OVERLINE);
+        surface.current_mut()[pos2(1, 0)] = Cell::new('b').attr(Attribute::OVERLINE);
+
+        let out = render(&mut surface);
+        assert!(out.contains("ab"), "{out:?}");
+        assert_eq!(out.matches("\x1b[53m").count(), 1, "{out:?}");
+    }
+
+    #[test]
+    fn a_visible_cursor_is_hidden_once_when_nobody_asks_for_it() {
+        let mut surface = Surface::new(vec2(2, 1));
+        surface.set_cursor_visible(true);
+
+        let out = render(&mut surface);
+        assert_eq!(out.matches("\x1b[?25l").count(), 1, "{out:?}");
+
+        // and isn't moved to the corner again after that
+        surface.erase();
+        assert_eq!(render(&mut surface), "");
+    }
+
+    #[test]
+    fn the_cursor_is_shown_where_it_was_asked_for() {
+        let mut surface = Surface::new(vec2(4, 1));
+        let area = surface.rect();
+        surface
+            .crop(area)
+            .set_cursor(pos2(2, 0), CursorShape::Bar, false);
+        let out = render(&mut surface);
+        assert_eq!(out.matches("\x1b[?25h").count(), 1, "{out:?}");
+
+        // it is already showing, so asking again only moves it
+        surface.erase();
+        surface
+            .crop(area)
+            .set_cursor(pos2(1, 0), CursorShape::Bar, false);
+        let out = render(&mut surface);
+        assert!(!out.contains("\x1b[?25h"), "{out:?}");
+        assert!(!out.contains("\x1b[?25l"), "{out:?}");
+    }
+
+    #[test]
+    fn clusters_are_written_whole() {
+        let mut surface = Surface::new(vec2(3, 1));
+        surface.current_mut()[pos2(0, 0)] = Cell::grapheme("e\u{301}");
+        surface.current_mut()[pos2(1, 0)] = Cell::grapheme("e\u{301}");
+
+        let out = render(&mut surface);
+        assert_eq!(out.matches("e\u{301}").count(), 2, "{out:?}");
+    }
+}
@@ -0,0 +1,210 @@
+use std::{
+    io::{Read, Write},
+    sync::mpsc::{self, Receiver, Sender},
+    time::Duration,
+};
+
+use crate::geom::{vec2, Vec2};
+
+use super::{
+    event::{Event, EventSource, ReadEvents},
+    Config,
+};
+
+pub trait Backend {
+    type Writer: Write;
+    type Events: EventSource;
+
+    fn size(&mut self) -> std::io::Result<Vec2>;
+
+    fn enable_raw_mode(&mut self) -> std::io::Result<()>;
+    fn disable_raw_mode(&mut self) -> std::io::Result<()>;
+
+    // these are only asked for once, when the terminal is set up
+    fn writer(&mut self) -> std::io::Result<Self::Writer>;
+    fn events(&mut self) -> std::io::Result<Self::Events>;
+
+    // the screen row the cursor is on, if the backend can find out.
+    // the input thread is paused while this is asked
+    fn cursor_row(&mut self) -> Option<i32> {
+        None
+    }
+
+    // sends a query straight to the terminal and reads its answer until `done` is satisfied.
+    // this is only asked before the input thread starts
+    fn query(
+        &mut self,
+        query: &[u8],
+        timeout: Duration,
+        done: fn(&[u8]) -> bool,
+    ) -> Option<Vec<u8>> {
+        let _ = (query, timeout, done);
+        None
+    }
+
+    // raw mode is usually process wide so this can't borrow the backend
+    fn restore_after_panic(config: &Config)
+    where
+        Self: Sized,
+    {
+        let _ = config;
+    }
+}
+
+// the process's terminal: output goes to stdout, and input is read from the tty
+#[derive(Default, Debug)]
+pub struct TtyBackend;
+
+impl Backend for TtyBackend {
+    type Writer = std::io::Stdout;
+    #[cfg(unix)]
+    type Events = super::event::StreamEvents<std::fs::File>;
+    #[cfg(not(unix))]
+    type Events = super::event::CrosstermEvents;
+
+    fn size(&mut self) -> std::io::Result<Vec2> {
+        crossterm::terminal::size().map(|(w, h)| vec2(w as _, h as _))
+    }
+
+    fn enable_raw_mode(&mut self) -> std::io::Result<()> {
+        crossterm::terminal::enable_raw_mode()
+    }
+
+    fn disable_raw_mode(&mut self) -> std::io::Result<()> {
+        crossterm::terminal::disable_raw_mode()
+    }
+
+    fn writer(&mut self) -> std::io::Result<Self::Writer> {
+        Ok(std::io::stdout())
+    }
+
+    fn events(&mut self) -> std::io::Result<Self::Events> {
+        #[cfg(unix)]
+        return super::event::StreamEvents::tty();
+        #[cfg(not(unix))]
+        return Ok(super::event::CrosstermEvents::default());
+    }
+
+    fn cursor_row(&mut self) -> Option<i32> {
+        crossterm::cursor::position().ok().map(|(_, y)| y as i32)
+    }
+
+    #[cfg(unix)]
+    fn query(
+        &mut self,
+        query: &[u8],
+        timeout: Duration,
+        done: fn(&[u8]) -> bool,
+    ) -> Option<Vec<u8>> {
+        let mut out = std::io::stdout();
+        out.write_all(query).ok()?;
+        out.flush().ok()?;
+        super::event::read_reply(timeout, done).ok()
+    }
+
+    fn restore_after_panic(config: &Config) {
+        let _ = super::Terminal::<Self>::write_reset(config, &mut std::io::stdout());
+        let _ = crossterm::terminal::disable_raw_mode();
+    }
+}
+
+// renders to any writer, with events sent through a channel.
+//
+// this is useful for pipes, sockets and in-memory buffers
+#[derive(Debug)]
+pub struct ChannelBackend<W> {
+    size: Vec2,
+    writer: Option<W>,
+    events: Option<Receiver<Event>>,
+}
+
+impl<W: Write> ChannelBackend<W> {
+    pub fn new(writer: W, size: Vec2) -> (Self, Sender<Event>) {
+        let (tx, rx) = mpsc::channel();
+        let this = Self {
+            size,
+            writer: Some(writer),
+            events: Some(rx),
+        };
+        (this, tx)
+    }
+}
+
+impl<W: Write> Backend for ChannelBackend<W> {
+    type Writer = W;
+    type Events = Receiver<Event>;
+
+    fn size(&mut self) -> std::io::Result<Vec2> {
+        Ok(self.size)
+    }
+
+    fn enable_raw_mode(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+
+    fn disable_raw_mode(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+
+    fn writer(&mut self) -> std::io::Result<Self::Writer> {
+        self.writer
+            .take()
+            .ok_or_else(|| std::io::ErrorKind::AlreadyExists.into())
+    }
+
+    fn events(&mut self) -> std::io::Result<Self::Events> {
+        self.events
+            .take()
+            .ok_or_else(|| std::io::ErrorKind::AlreadyExists.into())
+    }
+}
+
+// renders to any writer and decodes the input of any reader, such as a socket or a pipe.
+//
+// unlike `ChannelBackend` the input is the raw bytes a terminal would send
+#[derive(Debug)]
+pub struct StreamBackend<R, W> {
+    size: Vec2,
+    reader: Option<R>,
+    writer: Option<W>,
+}
+
+impl<R: Read + Send + 'static, W: Write> StreamBackend<R, W> {
+    pub fn new(reader: R, writer: W, size: Vec2) -> Self {
+        Self {
+            size,
+            reader: Some(reader),
+            writer: Some(writer),
+        }
+    }
+}
+
+impl<R: Read + Send + 'static, W: Write> Backend for StreamBackend<R, W> {
+    type Writer = W;
+    type Events = ReadEvents;
+
+    fn size(&mut self) -> std::io::Result<Vec2> {
+        Ok(self.size)
+    }
+
+    fn enable_raw_mode(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+
+    fn disable_raw_mode(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+
+    fn writer(&mut self) -> std::io::Result<Self::Writer> {
+        self.writer
+            .take()
+            .ok_or_else(|| std::io::ErrorKind::AlreadyExists.into())
+    }
+
+    fn events(&mut self) -> std::io::Result<Self::Events> {
+        self.reader
+            .take()
+            .map(ReadEvents::new)
+            .ok_or_else(|| std::io::ErrorKind::AlreadyExists.into())
+    }
+}
@@ -0,0 +1,400 @@
+use std::time::Duration;
+
+use crate::{
+    color::Rgba,
+    geom::{vec2, Vec2},
+    paint::Graphics,
+};
+
+use super::Backend;
+
+// what the terminal said about itself when it was asked at startup.
+//
+// anything it wasn't asked, or didn't get the chance to answer, is None
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Capabilities {
+    // the name and version from XTVERSION
+    pub name: Option<String>,
+    // the parameters of the primary device attributes, empty if the terminal never answered
+    pub device_attributes: Vec<u16>,
+    pub foreground: Option<Rgba>,
+    pub background: Option<Rgba>,
+    pub kitty_keyboard: Option<bool>,
+    pub kitty_graphics: Option<bool>,
+    pub synchronized_output: Option<bool>,
+    // how many pixels wide and tall a cell is
+    pub cell_size: Option<Vec2>,
+}
+
+impl Capabilities {
+    // every terminal answers DA1 and replies come back in order, so that one goes last
+    const QUERY: &'static [u8] = concat!(
+        "\x1b[>0q",                                   // XTVERSION
+        "\x1b]10;?\x1b\\",                            // foreground
+        "\x1b]11;?\x1b\\",                            // background
+        "\x1b[?u",                                    // kitty keyboard flags
+        "\x1b_Gi=31,s=1,v=1,a=q,t=d,f=24;AAAA\x1b\\", // kitty graphics
+        "\x1b[16t",                                   // cell size in pixels
+        "\x1b[?2026$p",                               // DECRQM synchronized output
+        "\x1b[c",                                     // DA1
+    )
+    .as_bytes();
+
+    pub(crate) fn detect(backend: &mut impl Backend, timeout: Duration) -> Self {
+        if timeout.is_zero() {
+            return Self::default();
+        }
+
+        backend
+            .query(Self::QUERY, timeout, Self::is_complete)
+            .map_or_else(Self::default, |reply| Self::parse(&reply))
+    }
+
+    // light terminals need darker colors, this is None if the background is unknown
+    pub fn is_dark(&self) -> Option<bool> {
+        let bg = self.background?;
+        let luma =
+            0.2126 * bg.red() as f32 + 0.7152 * bg.green() as f32 + 0.0722 * bg.blue() as f32;
+        Some(luma < 128.0)
+    }
+
+    // terminals that don't know about it ignore it, so this is only false if they said so
+    pub fn supports_synchronized_output(&self) -> bool {
+        self.synchronized_output != Some(false)
+    }
+
+    // flags that a terminal doesn't understand can end up on the screen, so this has to be asked
+    pub fn supports_kitty_keyboard(&self) -> bool {
+        self.kitty_keyboard == Some(true)
+    }
+
+    // REP and scrolling with SU and SD aren't in the vt100, so they're only used when the
+    // terminal said it is at least a vt220 (62). silence, or a plain vt100, means no
+    pub fn supports_repeat(&self) -> bool {
+        self.is_vt220()
+    }
+
+    pub fn supports_scrolling(&self) -> bool {
+        self.is_vt220()
+    }
+
+    fn is_vt220(&self) -> bool {
+        self.device_attributes
+            .first()
+            .is_some_and(|&level| level >= 62)
+    }
+
+    // the 4th device attribute is sixel graphics
+    pub fn supports_sixel(&self) -> bool {
+        self.device_attributes.contains(&4)
+    }
+
+    // unlike the others, nothing is assumed here because half blocks work everywhere
+    pub fn graphics(&self) -> Graphics {
+        if self.kitty_graphics == Some(true) {
+            Graphics::Kitty
+        } else if self.supports_sixel() {
+            Graphics::Sixel
+        } else {
+            Graphics::HalfBlocks
+        }
+    }
+
+    fn is_complete(reply: &[u8]) -> bool {
+        Sequences(reply).any(|seq| matches!(seq, Sequence::Csi([b'?', .., b'c'])))
+    }
+
+    fn parse(reply: &[u8]) -> Self {
+        let mut this = Self::default();
+        for seq in Sequences(reply) {
+            match seq {
+                Sequence::Csi([b'?', params @ .., b'c']) => {
+                    this.device_attributes = params
+                        .split(|&c| c == b';')
+                        .filter_map(|p| std::str::from_utf8(p).ok()?.parse().ok())
+                        .collect();
+                }
+                Sequence::Csi([b'?', flags @ .., b'u']) if flags.iter().all(u8::is_ascii_digit) => {
+                    this.kitty_keyboard = Some(true)
+                }
+                Sequence::Csi([b'?', b'2', b'0', b'2', b'6', b';', mode, b'$', b'y']) => {
+                    // 1 and 2 are set and reset, 3 is always set. 0 and 4 mean it can't be used
+                    this.synchronized_output = Some(matches!(mode, b'1'..=b'3'))
+                }
+                Sequence::Csi([b'6', b';', size @ .., b't']) => {
+                    let mut size = size
+                        .split(|&c| c == b';')
+                        .filter_map(|p| std::str::from_utf8(p).ok()?.parse().ok());
+                    if let (Some(h), Some(w)) = (size.next(), size.next()) {
+                        this.cell_size = Some(vec2(w, h))
+                    }
+                }
+                Sequence::Apc([b'G', b'i', b'=', b'3', b'1', b';', status @ ..]) => {
+                    this.kitty_graphics = Some(status == b"OK")
+                }
+                Sequence::Dcs([b'>', b'|', name @ ..]) => {
+                    this.name = Some(String::from_utf8_lossy(name).into_owned())
+                }
+                Sequence::Osc([b'1', b'0', b';', color @ ..]) => {
+                    this.foreground = parse_color(color)
+                }
+                Sequence::Osc([b'1', b'1', b';', color @ ..]) => {
+                    this.background = parse_color(color)
+                }
+                _ => {}
+            }
+        }
+
+        // it answered everything else before DA1, so silence means no
+        if !this.device_attributes.is_empty() {
+            this.kitty_keyboard.get_or_insert(false);
+            this.kitty_graphics.get_or_insert(false);
+            this.synchronized_output.get_or_insert(false);
+        }
+        this
+    }
+}
+
+// `rgb:r/g/b` where each part has 1 to 4 hex digits
+fn parse_color(color: &[u8]) -> Option<Rgba> {
+    let color = std::str::from_utf8(color).ok()?.strip_prefix("rgb:")?;
+    let mut parts = color.split('/').map(|part| {
+        let value = u32::from_str_radix(part, 16).ok()?;
+        let max = (1u32 << (part.len() * 4).clamp(4, 16)) - 1;
+        Some((value * 255 / max) as u8)
+    });
+    let (r, g, b) = (parts.next()??, parts.next()??, parts.next()??);
+    Some(Rgba::new(r, g, b))
+}
+
+enum Sequence<'a> {
+    // everything after `ESC [`, including the final byte
+    Csi(&'a [u8]),
+    // these don't include the terminator
+    Osc(&'a [u8]),
+    Dcs(&'a [u8]),
+    Apc(&'a [u8]),
+}
+
+// splits a reply into escape sequences, skipping anything else
+struct Sequences<'a>(&'a [u8]);
+
+impl<'a> Iterator for Sequences<'a> {
+    type Item = Sequence<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let start = self.0.iter().position(|&c| c == 0x1b)?;
+            let input = &self.0[start..];
+            let (len, seq) = match input {
+                [_, b'[', body @ ..] => {
+                    let end = body.iter().position(|c| (0x40..=0x7e).contains(c))?;
+                    (end + 3, Some(Sequence::Csi(&body[..=end])))
+                }
+                [_, kind @ (b']' | b'P' | b'_'), body @ ..] => {
+                    let end = body.iter().position(|&c| c == 0x07 || c == 0x1b)?;
+                    let len = match body[end] {
+                        0x07 => end + 3,
+                        _ => end + 4,
+                    };
+                    let body = &body[..end];
+                    let seq = match kind {
+                        b']' => Sequence::Osc(body),
+                        b'P' => Sequence::Dcs(body),
+                        _ => Sequence::Apc(body),
+                    };
+                    (len, Some(seq))
+                }
+                _ => (1, None),
+            };
+
+            self.0 = &input[len.min(input.len())..];
+            if let Some(seq) = seq {
+                return Some(seq);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{event::Event, geom::Vec2};
+
+    // answers the query with a canned reply, or not at all
+    struct Replies(Option<&'static [u8]>);
+
+    impl Backend for Replies {
+        type Writer = Vec<u8>;
+        type Events = std::sync::mpsc::Receiver<Event>;
+
+        fn size(&mut self) -> std::io::Result<Vec2> {
+            Ok(vec2(80, 24))
+        }
+
+        fn enable_raw_mode(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+
+        fn disable_raw_mode(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+
+        fn writer(&mut self) -> std::io::Result<Self::Writer> {
+            Ok(Vec::new())
+        }
+
+        fn events(&mut self) -> std::io::Result<Self::Events> {
+            Ok(std::sync::mpsc::channel().1)
+        }
+
+        fn query(&mut self, query: &[u8], _: Duration, done: fn(&[u8]) -> bool) -> Option<Vec<u8>> {
+            assert_eq!(query, Capabilities::QUERY);
+            let reply = self.0?;
+            assert_eq!(done(reply), reply.ends_with(b"c"));
+            Some(reply.to_vec())
+        }
+    }
+
+    fn detect(reply: &'static [u8]) -> Capabilities {
+        Capabilities::detect(&mut Replies(Some(reply)), Duration::from_millis(100))
+    }
+
+    #[test]
+    fn replies() {
+        let answered = Capabilities {
+            device_attributes: vec![62, 22],
+            kitty_keyboard: Some(false),
+            kitty_graphics: Some(false),
+            synchronized_output: Some(false),
+            ..Capabilities::default()
+        };
+
+        let table: &[(&[u8], Capabilities)] = &[
+            (b"\x1b[?62;22c", answered.clone()),
+            (
+                b"\x1bP>|xterm(390)\x1b\\\x1b[?62;22c",
+                Capabilities {
+                    name: Some(String::from("xterm(390)")),
+                    ..answered.clone()
+                },
+            ),
+            (
+                b"\x1b]10;rgb:ffff/8080/0000\x1b\\\x1b]11;rgb:1/2/3\x07\x1b[?62;22c",
+                Capabilities {
+                    foreground: Some(Rgba::new(255, 128, 0)),
+                    background: Some(Rgba::new(17, 34, 51)),
+                    ..answered.clone()
+                },
+            ),
+            (
+                b"\x1b[?1u\x1b_Gi=31;OK\x1b\\\x1b[6;20;10t\x1b[?2026;2$y\x1b[?62;22c",
+                Capabilities {
+                    kitty_keyboard: Some(true),
+                    kitty_graphics: Some(true),
+                    synchronized_output: Some(true),
+                    cell_size: Some(vec2(10, 20)),
+                    ..answered.clone()
+                },
+            ),
+            (
+                b"\x1b_Gi=31;ENOTSUPPORTED\x1b\\\x1b[?2026;4$y\x1b[?62;22c",
+                answered.clone(),
+            ),
+            // the colors can't be read
+            (
+                b"\x1b]10;#ffffff\x1b\\\x1b]11;rgb:zz/0/0\x1b\\\x1b[?62;22c",
+                answered.clone(),
+            ),
+            // DA1 never came, so nothing can be said about what wasn't answered
+            (
+                b"\x1b[?1u",
+                Capabilities {
+                    kitty_keyboard: Some(true),
+                    ..Capabilities::default()
+                },
+            ),
+        ];
+
+        for (reply, expected) in table {
+            assert_eq!(
+                &detect(reply),
+                expected,
+                "{:?}",
+                String::from_utf8_lossy(reply)
+            );
+        }
+    }
+
+    #[test]
+    fn nothing_is_known_without_a_reply() {
+        let timeout = Duration::from_millis(100);
+        assert_eq!(
+            Capabilities::detect(&mut Replies(None), timeout),
+            Capabilities::default()
+        );
+        // a zero timeout doesn't even ask
+        assert_eq!(
+            Capabilities::detect(&mut Replies(Some(b"\x1b[?62c")), Duration::ZERO),
+            Capabilities::default()
+        );
+
+        let unknown = Capabilities::default();
+        assert!(unknown.supports_synchronized_output());
+        assert!(!unknown.supports_kitty_keyboard());
+        assert!(!unknown.supports_repeat());
+        assert!(!unknown.supports_scrolling());
+        assert_eq!(unknown.graphics(), Graphics::HalfBlocks);
+        assert_eq!(unknown.is_dark(), None);
+    }
+
+    #[test]
+    fn decisions() {
+        let table: &[(&[u8], bool, bool, bool, Graphics)] = &[
+            // (reply, synchronized output, kitty keyboard, repeat and scrolling, graphics)
+            (b"\x1b[?1;2c", false, false, false, Graphics::HalfBlocks),
+            (b"\x1b[?6c", false, false, false, Graphics::HalfBlocks),
+            (b"\x1b[?62;4c", false, false, true, Graphics::Sixel),
+            (
+                b"\x1b[?1u\x1b_Gi=31;OK\x1b\\\x1b[?2026;1$y\x1b[?65;4;22c",
+                true,
+                true,
+                true,
+                Graphics::Kitty,
+            ),
+        ];
+
+        for &(reply, synchronized, kitty, vt220, graphics) in table {
+            let caps = detect(reply);
+            let reply = String::from_utf8_lossy(reply);
+            assert_eq!(
+                caps.supports_synchronized_output(),
+                synchronized,
+                "{reply:?}"
+            );
+            assert_eq!(caps.supports_kitty_keyboard(), kitty, "{reply:?}");
+            assert_eq!(caps.supports_repeat(), vt220, "{reply:?}");
+            assert_eq!(caps.supports_scrolling(), vt220, "{reply:?}");
+            assert_eq!(caps.graphics(), graphics, "{reply:?}");
+        }
+    }
+
+    #[test]
+    fn dark_and_light_backgrounds() {
+        let table = [
+            (0x000000, true),
+            (0x1E1E1E, true),
+            (0x0000FF, true),
+            (0xFFFFFF, false),
+            (0xFDF6E3, false),
+            (0x00FF00, false),
+        ];
+        for (bg, dark) in table {
+            let caps = Capabilities {
+                background: Some(Rgba::from_u32(bg)),
+                ..Capabilities::default()
+            };
+            assert_eq!(caps.is_dark(), Some(dark), "{bg:06x}");
+        }
+    }
+}
@@ -1,12 +1,37 @@
-use std::sync::{Arc, RwLock};
+use crate::color::ColorDepth;
 
-#[derive(Copy, Clone, Debug)]
+use std::{
+    path::PathBuf,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+// how many rows the viewport takes up when not using the alternate screen
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum InlineHeight {
+    Fixed(u16),
+    // grows and shrinks to fit the laid out widgets
+    #[default]
+    Content,
+}
+
+#[derive(Clone, Debug)]
 pub struct Config {
     pub(crate) hide_cursor: bool,
     pub(crate) mouse_capture: bool,
     pub(crate) ctrl_c_quits: bool,
     pub(crate) ctrl_z_switches: bool,
     pub(crate) use_alt_screen: bool,
+    pub(crate) max_frame_rate: u16,
+    pub(crate) inline_height: InlineHeight,
+    pub(crate) keep_final_frame: bool,
+    pub(crate) kitty_keyboard: bool,
+    pub(crate) bracketed_paste: bool,
+    pub(crate) pause_when_unfocused: bool,
+    pub(crate) record: Option<PathBuf>,
+    pub(crate) cast: Option<PathBuf>,
+    pub(crate) color_depth: Option<ColorDepth>,
+    pub(crate) query_timeout: Duration,
 }
 
 impl Default for Config {
@@ -17,11 +42,22 @@ impl Default for Config {
             ctrl_c_quits: true,
             ctrl_z_switches: false,
             use_alt_screen: true,
+            max_frame_rate: 60,
+            inline_height: InlineHeight::Content,
+            keep_final_frame: false,
+            kitty_keyboard: true,
+            bracketed_paste: true,
+            pause_when_unfocused: false,
+            record: None,
+            cast: None,
+            color_depth: None,
+            query_timeout: Duration::from_millis(100),
         }
     }
 }
 
 impl Config {
+    // hides the cursor on startup, widgets can still ask for it to be shown while they're drawn
     pub const fn hide_cursor(mut self, hide_cursor: bool) -> Self {
         self.hide_cursor = hide_cursor;
         self
@@ -47,6 +83,77 @@ impl Config {
         self
     }
 
+    // 0 means the frame rate is not capped
+    pub const fn max_frame_rate(mut self, max_frame_rate: u16) -> Self {
+        self.max_frame_rate = max_frame_rate;
+        self
+    }
+
+    pub const fn inline_height(mut self, inline_height: InlineHeight) -> Self {
+        self.inline_height = inline_height;
+        self
+    }
+
+    // leave the last inline frame in the scrollback instead of clearing it on exit
+    pub const fn keep_final_frame(mut self, keep_final_frame: bool) -> Self {
+        self.keep_final_frame = keep_final_frame;
+        self
+    }
+
+    // ask for key release events and unambiguous key codes, on terminals that support it
+    pub const fn kitty_keyboard(mut self, kitty_keyboard: bool) -> Self {
+        self.kitty_keyboard = kitty_keyboard;
+        self
+    }
+
+    // pasted text arrives as one event instead of a key press per character
+    pub const fn bracketed_paste(mut self, bracketed_paste: bool) -> Self {
+        self.bracketed_paste = bracketed_paste;
+        self
+    }
+
+    // stop animating and ignore repaint requests while the terminal doesn't have focus
+    pub const fn pause_when_unfocused(mut self, pause_when_unfocused: bool) -> Self {
+        self.pause_when_unfocused = pause_when_unfocused;
+        self
+    }
+
+    // write every event, and when each frame happened, to this file so the session can be replayed
+    pub fn record(mut self, path: impl Into<PathBuf>) -> Self {
+        self.record = Some(path.into());
+        self
+    }
+
+    // write everything sent to the terminal to an asciinema cast file
+    pub fn cast(mut self, path: impl Into<PathBuf>) -> Self {
+        self.cast = Some(path.into());
+        self
+    }
+
+    // this is detected from the environment if it isn't set
+    pub const fn color_depth(mut self, color_depth: ColorDepth) -> Self {
+        self.color_depth = Some(color_depth);
+        self
+    }
+
+    // the same as setting NO_COLOR, setting a color depth overrides NO_COLOR
+    pub const fn no_color(self) -> Self {
+        self.color_depth(ColorDepth::Monochrome)
+    }
+
+    // how long to wait for the terminal to say what it supports on startup, zero doesn't ask
+    pub const fn query_timeout(mut self, query_timeout: Duration) -> Self {
+        self.query_timeout = query_timeout;
+        self
+    }
+
+    pub(crate) fn frame_interval(&self) -> Duration {
+        match self.max_frame_rate {
+            0 => Duration::ZERO,
+            fps => Duration::from_secs_f32(1.0 / fps as f32),
+        }
+    }
+
     pub fn into_shareable(self) -> ShareableConfig {
         self.into()
     }
@@ -19,6 +19,76 @@ pub enum Key {
     Escape,
     Tab,
     BackTab,
+    CapsLock,
+    ScrollLock,
+    NumLock,
+    PrintScreen,
+    Pause,
+    Menu,
+    // digits and operators on the number pad
+    Keypad(char),
+    KeypadEnter,
+    KeypadBegin,
+    Media(MediaKey),
+    // a modifier pressed on its own
+    Modifier(ModifierKey),
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum MediaKey {
+    Play,
+    Pause,
+    PlayPause,
+    Reverse,
+    Stop,
+    FastForward,
+    Rewind,
+    TrackNext,
+    TrackPrevious,
+    Record,
+    LowerVolume,
+    RaiseVolume,
+    MuteVolume,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ModifierKey {
+    LeftShift,
+    LeftCtrl,
+    LeftAlt,
+    LeftSuper,
+    LeftHyper,
+    LeftMeta,
+    RightShift,
+    RightCtrl,
+    RightAlt,
+    RightSuper,
+    RightHyper,
+    RightMeta,
+    IsoLevel3Shift,
+    IsoLevel5Shift,
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum KeyKind {
+    #[default]
+    Press,
+    Repeat,
+    Release,
+}
+
+impl KeyKind {
+    pub const fn is_press(&self) -> bool {
+        matches!(self, Self::Press)
+    }
+
+    pub const fn is_repeat(&self) -> bool {
+        matches!(self, Self::Repeat)
+    }
+
+    pub const fn is_release(&self) -> bool {
+        matches!(self, Self::Release)
+    }
 }
 
 impl TryFrom<crossterm::event::KeyCode> for Key {
@@ -32,6 +102,7 @@ impl TryFrom<crossterm::event::KeyCode> for Key {
             crossterm::event::KeyCode::Up => Ok(Self::Up),
             crossterm::event::KeyCode::Down => Ok(Self::Down),
             crossterm::event::KeyCode::PageUp => Ok(Self::PageUp),
+            crossterm::event::KeyCode::PageDown => Ok(Self::PageDown),
             crossterm::event::KeyCode::Home => Ok(Self::Home),
             crossterm::event::KeyCode::End => Ok(Self::End),
             crossterm::event::KeyCode::Enter => Ok(Self::Enter),
@@ -41,6 +112,13 @@ impl TryFrom<crossterm::event::KeyCode> for Key {
             crossterm::event::KeyCode::Esc => Ok(Self::Escape),
             crossterm::event::KeyCode::Tab => Ok(Self::Tab),
             crossterm::event::KeyCode::BackTab => Ok(Self::BackTab),
+            crossterm::event::KeyCode::CapsLock => Ok(Self::CapsLock),
+            crossterm::event::KeyCode::ScrollLock => Ok(Self::ScrollLock),
+            crossterm::event::KeyCode::NumLock => Ok(Self::NumLock),
+            crossterm::event::KeyCode::PrintScreen => Ok(Self::PrintScreen),
+            crossterm::event::KeyCode::Pause => Ok(Self::Pause),
+            crossterm::event::KeyCode::Menu => Ok(Self::Menu),
+            crossterm::event::KeyCode::KeypadBegin => Ok(Self::KeypadBegin),
             _ => Err(value),
         }
     }
@@ -86,3 +164,9 @@ impl From<char> for Keybind {
         Self::new(Key::Char(value), Modifiers::NONE)
     }
 }
+
+impl From<Key> for Keybind {
+    fn from(value: Key) -> Self {
+        Self::key(value)
+    }
+}
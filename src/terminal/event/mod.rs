@@ -1,18 +1,36 @@
-use crate::geom::{pos2, rect, vec2, Pos2, Rect};
+use crate::geom::{Pos2, Rect};
 
 mod keyboard;
-pub use keyboard::{Key, Keybind};
+pub use keyboard::{Key, KeyKind, Keybind, MediaKey, ModifierKey};
 
 mod mouse;
-use mouse::TemporalEvent;
+pub(crate) use mouse::TemporalEvent;
 pub use mouse::{MouseButton, MouseEvent, MouseState};
 
 mod modifiers;
 pub use modifiers::Modifiers;
 
+mod reader;
+pub(crate) use reader::EventReader;
+
+mod source;
+#[cfg(unix)]
+pub(crate) use source::read_reply;
+#[cfg(not(unix))]
+pub(crate) use source::CrosstermEvents;
+#[cfg(unix)]
+pub use source::StreamEvents;
+pub use source::{EventSource, ReadEvents};
+
+mod parser;
+pub use parser::Parser;
+
+mod record;
+pub use record::{Entry, EntryKind, Recorder, Recording};
+
 #[derive(Clone, Debug, PartialEq)]
 pub enum Event {
-    Keyboard(Key, Modifiers),
+    Keyboard(Key, Modifiers, KeyKind),
     Mouse(MouseEvent, Pos2, Modifiers),
     Resize(Rect),
     Quit,
@@ -21,22 +39,16 @@ pub enum Event {
     Paste(String),
 }
 
-// TODO this needs to read before the timer and still advance the timer. and then read after the timer
-pub fn read_next_event(state: &mut MouseState) -> Option<Event> {
-    const NO_TIMEOUT: std::time::Duration = std::time::Duration::ZERO;
-    match crossterm::event::poll(NO_TIMEOUT) {
-        Ok(true) => {
-            let Ok(ev) = crossterm::event::read() else {
-                return Some(Event::Quit);
-            };
-            translate(ev, state)
-        }
+pub(crate) fn read_next_event(reader: &EventReader) -> Option<Event> {
+    match reader.try_read()? {
+        Ok(ev) => Some(ev),
         Err(..) => Some(Event::Quit),
-        _ => None,
     }
 }
 
+#[cfg(not(unix))]
 fn translate(ev: crossterm::event::Event, state: &mut MouseState) -> Option<Event> {
+    use crate::geom::{pos2, rect, vec2};
     use crossterm::event::{Event as E, KeyEventKind, MouseEventKind as M};
 
     let ev = match ev {
@@ -45,10 +57,15 @@ fn translate(ev: crossterm::event::Event, state: &mut MouseState) -> Option<Even
         E::Paste(data) => Event::Paste(data),
         E::Resize(cols, rows) => Event::Resize(rect(vec2(cols as _, rows as _))),
 
-        E::Key(ev) if matches!(ev.kind, KeyEventKind::Release) => {
+        E::Key(ev) => {
             let key = ev.code.try_into().ok()?;
             let modifiers = ev.modifiers.into();
-            Event::Keyboard(key, modifiers)
+            let kind = match ev.kind {
+                KeyEventKind::Press => KeyKind::Press,
+                KeyEventKind::Repeat => KeyKind::Repeat,
+                KeyEventKind::Release => KeyKind::Release,
+            };
+            Event::Keyboard(key, modifiers, kind)
         }
 
         E::Mouse(ev) => {
@@ -6,6 +6,9 @@ impl Modifiers {
     pub const SHIFT: Self = Self(1 << 0);
     pub const CTRL: Self = Self(1 << 1);
     pub const ALT: Self = Self(1 << 2);
+    pub const SUPER: Self = Self(1 << 3);
+    pub const HYPER: Self = Self(1 << 4);
+    pub const META: Self = Self(1 << 5);
 }
 
 impl Modifiers {
@@ -36,6 +39,18 @@ impl Modifiers {
     pub const fn is_alt_only(&self) -> bool {
         self.0 == Self::ALT.0
     }
+
+    pub const fn is_super(&self) -> bool {
+        (self.0 >> 3) & 1 == 1
+    }
+
+    pub const fn is_hyper(&self) -> bool {
+        (self.0 >> 4) & 1 == 1
+    }
+
+    pub const fn is_meta(&self) -> bool {
+        (self.0 >> 5) & 1 == 1
+    }
 }
 
 impl std::ops::BitAnd for Modifiers {
@@ -79,7 +94,7 @@ impl std::fmt::Debug for Modifiers {
 impl std::fmt::Display for Modifiers {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let mut seen = false;
-        for (i, repr) in (0..).zip(["Shift", "Ctrl", "Alt"]) {
+        for (i, repr) in (0..).zip(["Shift", "Ctrl", "Alt", "Super", "Hyper", "Meta"]) {
             if (self.0 >> i) & 1 == 1 {
                 if seen {
                     f.write_str(" + ")?
@@ -106,6 +121,9 @@ impl std::str::FromStr for Modifiers {
                 s if s.eq_ignore_ascii_case("shift") => Self::SHIFT,
                 s if s.eq_ignore_ascii_case("ctrl") => Self::CTRL,
                 s if s.eq_ignore_ascii_case("alt") => Self::ALT,
+                s if s.eq_ignore_ascii_case("super") => Self::SUPER,
+                s if s.eq_ignore_ascii_case("hyper") => Self::HYPER,
+                s if s.eq_ignore_ascii_case("meta") => Self::META,
                 modifier => return Err(format!("unknown modifier: {modifier}")),
             }
         }
@@ -125,6 +143,9 @@ impl From<crossterm::event::KeyModifiers> for Modifiers {
             crossterm::event::KeyModifiers::SHIFT,
             crossterm::event::KeyModifiers::CONTROL,
             crossterm::event::KeyModifiers::ALT,
+            crossterm::event::KeyModifiers::SUPER,
+            crossterm::event::KeyModifiers::HYPER,
+            crossterm::event::KeyModifiers::META,
         ]
         .into_iter()
         .fold(Self::NONE, |this, m| this | Self((value & m).bits()))
@@ -0,0 +1,801 @@
+use std::collections::VecDeque;
+
+use crate::geom::{pos2, rect, vec2, Pos2, Vec2};
+
+use super::{
+    Event, Key, KeyKind, MediaKey, ModifierKey, Modifiers, MouseButton, MouseEvent, MouseState,
+    TemporalEvent,
+};
+
+const ESC: u8 = 0x1b;
+const PASTE_END: &[u8] = b"\x1b[201~";
+
+// sequences longer than this without a final byte are garbage
+const MAX_SEQUENCE: usize = 64;
+
+// osc replies carry the clipboard, so they get a lot more room
+const MAX_OSC: usize = 1 << 20;
+
+// decodes the bytes a terminal sends into events.
+//
+// input can be fed in whatever chunks it arrives in, partial sequences are
+// kept until the rest of them shows up
+#[derive(Default)]
+pub struct Parser {
+    buf: Vec<u8>,
+    paste: Option<Vec<u8>>,
+    mouse: MouseState,
+    events: VecDeque<Event>,
+}
+
+impl Parser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn feed(&mut self, bytes: &[u8]) {
+        self.buf.extend_from_slice(bytes);
+        self.parse(false)
+    }
+
+    // a lone escape can't be told apart from the start of a sequence until
+    // nothing else follows it, so this should be called when the input goes quiet
+    pub fn flush(&mut self) {
+        self.parse(true)
+    }
+
+    pub fn next_event(&mut self) -> Option<Event> {
+        self.events.pop_front()
+    }
+
+    fn parse(&mut self, flush: bool) {
+        let mut pos = 0;
+        while pos < self.buf.len() {
+            let input = &self.buf[pos..];
+
+            if let Some(paste) = &mut self.paste {
+                match input.windows(PASTE_END.len()).position(|w| w == PASTE_END) {
+                    Some(index) => {
+                        paste.extend_from_slice(&input[..index]);
+                        let data = String::from_utf8_lossy(paste).into_owned();
+                        self.events.push_back(Event::Paste(data));
+                        self.paste = None;
+                        pos += index + PASTE_END.len();
+                        continue;
+                    }
+                    None => {
+                        // the end marker could be split across reads
+                        let keep = (1..PASTE_END.len())
+                            .rev()
+                            .find(|&n| input.ends_with(&PASTE_END[..n]))
+                            .unwrap_or(0);
+                        paste.extend_from_slice(&input[..input.len() - keep]);
+                        pos += input.len() - keep;
+                        break;
+                    }
+                }
+            }
+
+            let (len, token) = match decode(input) {
+                Some(step) => step,
+                None if flush => decode_escape(input),
+                None => break,
+            };
+            pos += len;
+
+            match token {
+                Token::Key(key, modifiers, kind) => {
+                    self.events.push_back(Event::Keyboard(key, modifiers, kind));
+                }
+                Token::Mouse(report) => {
+                    if let Some(ev) = self.mouse(report) {
+                        self.events.push_back(ev)
+                    }
+                }
+                Token::Focus(true) => self.events.push_back(Event::FocusGained),
+                Token::Focus(false) => self.events.push_back(Event::FocusLost),
+                Token::Resize(size) => self.events.push_back(Event::Resize(rect(size))),
+                Token::PasteStart => self.paste = Some(Vec::new()),
+                Token::Clipboard(data) => self.events.push_back(Event::Paste(data)),
+                Token::Ignore => {}
+            }
+        }
+
+        self.buf.drain(..pos);
+    }
+
+    fn mouse(&mut self, report: MouseReport) -> Option<Event> {
+        let MouseReport {
+            kind,
+            pos,
+            modifiers,
+        } = report;
+
+        let ev = match kind {
+            MouseKind::Down(button) => self.mouse.update(TemporalEvent::Down(pos, button))?,
+            MouseKind::Up(button) => self.mouse.update(TemporalEvent::Up(pos, button))?,
+            MouseKind::Drag(button) => self.mouse.update(TemporalEvent::Drag(pos, button))?,
+            MouseKind::Move => MouseEvent::Move,
+            MouseKind::Scroll(delta) => MouseEvent::Scroll { delta },
+        };
+        Some(Event::Mouse(ev, pos, modifiers))
+    }
+}
+
+enum Token {
+    Key(Key, Modifiers, KeyKind),
+    Mouse(MouseReport),
+    Focus(bool),
+    Resize(Vec2),
+    PasteStart,
+    Clipboard(String),
+    Ignore,
+}
+
+struct MouseReport {
+    kind: MouseKind,
+    pos: Pos2,
+    modifiers: Modifiers,
+}
+
+enum MouseKind {
+    Down(MouseButton),
+    Up(MouseButton),
+    Drag(MouseButton),
+    Move,
+    Scroll(Vec2),
+}
+
+// returns how many bytes were used, or None if more input is needed
+fn decode(input: &[u8]) -> Option<(usize, Token)> {
+    match input {
+        [] | [ESC] => None,
+        [ESC, b'[', ..] => decode_csi(input),
+        [ESC, b'O', ..] => decode_ss3(input),
+        [ESC, b']', ..] => decode_osc(input),
+        [ESC, ESC, ..] => Some((1, key(Key::Escape, Modifiers::NONE))),
+        [ESC, rest @ ..] => {
+            let (len, token) = decode_char(rest)?;
+            let token = match token {
+                Token::Key(key, modifiers, kind) => {
+                    Token::Key(key, modifiers | Modifiers::ALT, kind)
+                }
+                token => token,
+            };
+            Some((len + 1, token))
+        }
+        _ => decode_char(input),
+    }
+}
+
+// what an incomplete sequence means once nothing else is coming
+fn decode_escape(input: &[u8]) -> (usize, Token) {
+    match input {
+        [ESC] => (1, key(Key::Escape, Modifiers::NONE)),
+        [ESC, b'[' | b'O' | b']'] => (2, key(Key::Char(input[1] as char), Modifiers::ALT)),
+        // whatever this was, the rest of it never arrived
+        _ => (input.len(), Token::Ignore),
+    }
+}
+
+fn decode_char(input: &[u8]) -> Option<(usize, Token)> {
+    let ctrl = |ch| key(Key::Char(ch), Modifiers::CTRL);
+
+    let token = match input[0] {
+        b'\r' | b'\n' => key(Key::Enter, Modifiers::NONE),
+        b'\t' => key(Key::Tab, Modifiers::NONE),
+        0x7f | 0x08 => key(Key::Backspace, Modifiers::NONE),
+        0x00 => ctrl(' '),
+        c @ 0x01..=0x1a => ctrl((b'a' + c - 1) as char),
+        c @ 0x1c..=0x1f => ctrl((b'4' + c - 0x1c) as char),
+        ESC => key(Key::Escape, Modifiers::NONE),
+        c if c.is_ascii() => return Some((1, char_key(c as char))),
+        c => {
+            let len = match c {
+                0xc0..=0xdf => 2,
+                0xe0..=0xef => 3,
+                0xf0..=0xf7 => 4,
+                _ => return Some((1, Token::Ignore)),
+            };
+            if input.len() < len {
+                return None;
+            }
+            return match std::str::from_utf8(&input[..len]) {
+                Ok(s) => Some((len, char_key(s.chars().next()?))),
+                Err(..) => Some((1, Token::Ignore)),
+            };
+        }
+    };
+    Some((1, token))
+}
+
+fn char_key(ch: char) -> Token {
+    let modifiers = if ch.is_uppercase() {
+        Modifiers::SHIFT
+    } else {
+        Modifiers::NONE
+    };
+    key(Key::Char(ch), modifiers)
+}
+
+// everything without the kitty protocol is a press
+fn key(key: Key, modifiers: Modifiers) -> Token {
+    Token::Key(key, modifiers, KeyKind::Press)
+}
+
+fn decode_ss3(input: &[u8]) -> Option<(usize, Token)> {
+    let &final_byte = input.get(2)?;
+    let key = match final_byte {
+        b'P' => Key::Function(1),
+        b'Q' => Key::Function(2),
+        b'R' => Key::Function(3),
+        b'S' => Key::Function(4),
+        b'M' => Key::Enter,
+        final_byte => match cursor_key(final_byte) {
+            Some(key) => key,
+            None => return Some((3, Token::Ignore)),
+        },
+    };
+    Some((3, self::key(key, Modifiers::NONE)))
+}
+
+fn cursor_key(final_byte: u8) -> Option<Key> {
+    let key = match final_byte {
+        b'A' => Key::Up,
+        b'B' => Key::Down,
+        b'C' => Key::Right,
+        b'D' => Key::Left,
+        b'H' => Key::Home,
+        b'F' => Key::End,
+        b'E' => Key::KeypadBegin,
+        _ => return None,
+    };
+    Some(key)
+}
+
+// operating system commands end with either BEL or ST
+fn decode_osc(input: &[u8]) -> Option<(usize, Token)> {
+    let body = &input[2..];
+    let (end, len) = match body.iter().position(|&c| c == 0x07 || c == ESC) {
+        Some(i) if body[i] == 0x07 => (i, i + 3),
+        Some(i) => match body.get(i + 1)? {
+            b'\\' => (i, i + 4),
+            // an escape that doesn't end it starts something else
+            _ => return Some((i + 2, Token::Ignore)),
+        },
+        None if body.len() > MAX_OSC => return Some((input.len(), Token::Ignore)),
+        None => return None,
+    };
+
+    // the reply to an osc 52 query is `52;<selection>;<base64>`
+    let token = match &body[..end] {
+        [b'5', b'2', b';', rest @ ..] => match rest.iter().position(|&c| c == b';') {
+            Some(i) if &rest[i + 1..] != b"?" => crate::base64::decode(&rest[i + 1..])
+                .map_or(Token::Ignore, |data| {
+                    Token::Clipboard(String::from_utf8_lossy(&data).into_owned())
+                }),
+            _ => Token::Ignore,
+        },
+        _ => Token::Ignore,
+    };
+    Some((len, token))
+}
+
+fn decode_csi(input: &[u8]) -> Option<(usize, Token)> {
+    // legacy X10 mouse reports carry their data as raw bytes after the final byte
+    if let [ESC, b'[', b'M', rest @ ..] = input {
+        let &[cb, cx, cy, ..] = rest else {
+            return None;
+        };
+        let pos = pos2(cx.saturating_sub(33) as i32, cy.saturating_sub(33) as i32);
+        let token = mouse_report(cb.saturating_sub(32) as u32, pos, false)
+            .map_or(Token::Ignore, Token::Mouse);
+        return Some((6, token));
+    }
+
+    let body = &input[2..];
+    let Some(end) = body.iter().position(|c| (0x40..=0x7e).contains(c)) else {
+        if body.len() > MAX_SEQUENCE {
+            return Some((1, Token::Ignore));
+        }
+        return None;
+    };
+
+    let len = 2 + end + 1;
+    let final_byte = body[end];
+    let params = &body[..end];
+
+    let token = match params.first() {
+        Some(b'<') => decode_sgr_mouse(&params[1..], final_byte),
+        Some(b'?' | b'>' | b'=') => Token::Ignore,
+        _ => decode_csi_params(params, final_byte),
+    };
+    Some((len, token))
+}
+
+fn decode_csi_params(params: &[u8], final_byte: u8) -> Token {
+    let mut numbers = [Param::default(); 5];
+    let count = parse_params(params, &mut numbers);
+    let (modifiers, kind) = modifiers(numbers[1]);
+    let key = |key| Token::Key(key, modifiers, kind);
+
+    match final_byte {
+        b'~' => {
+            let k = match numbers[0][0] {
+                1 | 7 => Key::Home,
+                2 => Key::Insert,
+                3 => Key::Delete,
+                4 | 8 => Key::End,
+                5 => Key::PageUp,
+                6 => Key::PageDown,
+                n @ 11..=15 => Key::Function((n - 10) as u8),
+                n @ 17..=21 => Key::Function((n - 11) as u8),
+                n @ 23..=26 => Key::Function((n - 12) as u8),
+                n @ 28..=29 => Key::Function((n - 13) as u8),
+                n @ 31..=34 => Key::Function((n - 14) as u8),
+                200 => return Token::PasteStart,
+                _ => return Token::Ignore,
+            };
+            key(k)
+        }
+
+        // a cursor position report looks just like a modified F3
+        b'R' if count == 2 && numbers[0][0] != 1 => Token::Ignore,
+        b'P' => key(Key::Function(1)),
+        b'Q' => key(Key::Function(2)),
+        b'R' => key(Key::Function(3)),
+        b'S' => key(Key::Function(4)),
+
+        b'Z' => Token::Key(Key::BackTab, Modifiers::SHIFT | modifiers, kind),
+
+        b'I' if count == 0 => Token::Focus(true),
+        b'O' if count == 0 => Token::Focus(false),
+
+        // urxvt (1015) mouse reports
+        b'M' if count == 3 => {
+            let pos = pos2(numbers[1][0] as i32 - 1, numbers[2][0] as i32 - 1);
+            mouse_report(numbers[0][0].saturating_sub(32), pos, false)
+                .map_or(Token::Ignore, Token::Mouse)
+        }
+
+        // in-band resize notifications (mode 2048)
+        b't' if numbers[0][0] == 48 && count >= 3 => {
+            Token::Resize(vec2(numbers[2][0] as i32, numbers[1][0] as i32))
+        }
+
+        b'u' if count >= 1 => {
+            let caps_lock = numbers[1][0].saturating_sub(1) & 64 != 0;
+            match kitty_key(numbers[0], modifiers, caps_lock) {
+                Some(Key::Tab) if modifiers.is_shift() => key(Key::BackTab),
+                Some(k) => key(k),
+                None => Token::Ignore,
+            }
+        }
+
+        final_byte => match cursor_key(final_byte) {
+            Some(k) => key(k),
+            None => Token::Ignore,
+        },
+    }
+}
+
+fn decode_sgr_mouse(params: &[u8], final_byte: u8) -> Token {
+    let mut numbers = [Param::default(); 3];
+    if parse_params(params, &mut numbers) != 3 || !matches!(final_byte, b'M' | b'm') {
+        return Token::Ignore;
+    }
+
+    let pos = pos2(numbers[1][0] as i32 - 1, numbers[2][0] as i32 - 1);
+    mouse_report(numbers[0][0], pos, final_byte == b'm').map_or(Token::Ignore, Token::Mouse)
+}
+
+fn mouse_report(cb: u32, pos: Pos2, release: bool) -> Option<MouseReport> {
+    let mut modifiers = Modifiers::NONE;
+    if cb & 4 != 0 {
+        modifiers |= Modifiers::SHIFT
+    }
+    if cb & 8 != 0 {
+        modifiers |= Modifiers::ALT
+    }
+    if cb & 16 != 0 {
+        modifiers |= Modifiers::CTRL
+    }
+
+    let button = match cb & 0b11 {
+        0 => Some(MouseButton::Primary),
+        1 => Some(MouseButton::Middle),
+        2 => Some(MouseButton::Secondary),
+        _ => None,
+    };
+
+    let kind = if cb & 64 != 0 {
+        let delta = match cb & 0b11 {
+            0 => vec2(0, -1),
+            1 => vec2(0, 1),
+            2 => vec2(-1, 0),
+            _ => vec2(1, 0),
+        };
+        MouseKind::Scroll(delta)
+    } else if cb & 32 != 0 {
+        button.map_or(MouseKind::Move, MouseKind::Drag)
+    } else if release {
+        MouseKind::Up(button?)
+    } else {
+        match button {
+            Some(button) => MouseKind::Down(button),
+            // X10 and urxvt don't say which button was released
+            None => MouseKind::Up(MouseButton::Primary),
+        }
+    };
+
+    Some(MouseReport {
+        kind,
+        pos,
+        modifiers,
+    })
+}
+
+// the modifier parameter is 1 + a bitmask, kitty adds the event type as a sub-parameter
+fn modifiers(param: Param) -> (Modifiers, KeyKind) {
+    let [mods, event, _] = param;
+    let bits = mods.saturating_sub(1);
+    let modifiers = [
+        Modifiers::SHIFT,
+        Modifiers::ALT,
+        Modifiers::CTRL,
+        Modifiers::SUPER,
+        Modifiers::HYPER,
+        Modifiers::META,
+    ]
+    .into_iter()
+    .enumerate()
+    .filter(|(i, _)| (bits >> i) & 1 == 1)
+    .fold(Modifiers::NONE, |this, (_, m)| this | m);
+
+    let kind = match event {
+        2 => KeyKind::Repeat,
+        3 => KeyKind::Release,
+        _ => KeyKind::Press,
+    };
+    (modifiers, kind)
+}
+
+// kitty sends `code:shifted:base`, the shifted code is only there when shift is held
+fn kitty_key(param: Param, modifiers: Modifiers, caps_lock: bool) -> Option<Key> {
+    let [code, shifted, _] = param;
+    let key = match code {
+        9 => Key::Tab,
+        13 => Key::Enter,
+        27 => Key::Escape,
+        127 => Key::Backspace,
+
+        57358 => Key::CapsLock,
+        57359 => Key::ScrollLock,
+        57360 => Key::NumLock,
+        57361 => Key::PrintScreen,
+        57362 => Key::Pause,
+        57363 => Key::Menu,
+        57376..=57398 => Key::Function((code - 57376 + 13) as u8),
+
+        57399..=57408 => Key::Keypad(char::from_digit(code - 57399, 10)?),
+        57409 => Key::Keypad('.'),
+        57410 => Key::Keypad('/'),
+        57411 => Key::Keypad('*'),
+        57412 => Key::Keypad('-'),
+        57413 => Key::Keypad('+'),
+        57414 => Key::KeypadEnter,
+        57415 => Key::Keypad('='),
+        57416 => Key::Keypad(','),
+        57417 => Key::Left,
+        57418 => Key::Right,
+        57419 => Key::Up,
+        57420 => Key::Down,
+        57421 => Key::PageUp,
+        57422 => Key::PageDown,
+        57423 => Key::Home,
+        57424 => Key::End,
+        57425 => Key::Insert,
+        57426 => Key::Delete,
+        57427 => Key::KeypadBegin,
+
+        57428 => Key::Media(MediaKey::Play),
+        57429 => Key::Media(MediaKey::Pause),
+        57430 => Key::Media(MediaKey::PlayPause),
+        57431 => Key::Media(MediaKey::Reverse),
+        57432 => Key::Media(MediaKey::Stop),
+        57433 => Key::Media(MediaKey::FastForward),
+        57434 => Key::Media(MediaKey::Rewind),
+        57435 => Key::Media(MediaKey::TrackNext),
+        57436 => Key::Media(MediaKey::TrackPrevious),
+        57437 => Key::Media(MediaKey::Record),
+        57438 => Key::Media(MediaKey::LowerVolume),
+        57439 => Key::Media(MediaKey::RaiseVolume),
+        57440 => Key::Media(MediaKey::MuteVolume),
+
+        57441 => Key::Modifier(ModifierKey::LeftShift),
+        57442 => Key::Modifier(ModifierKey::LeftCtrl),
+        57443 => Key::Modifier(ModifierKey::LeftAlt),
+        57444 => Key::Modifier(ModifierKey::LeftSuper),
+        57445 => Key::Modifier(ModifierKey::LeftHyper),
+        57446 => Key::Modifier(ModifierKey::LeftMeta),
+        57447 => Key::Modifier(ModifierKey::RightShift),
+        57448 => Key::Modifier(ModifierKey::RightCtrl),
+        57449 => Key::Modifier(ModifierKey::RightAlt),
+        57450 => Key::Modifier(ModifierKey::RightSuper),
+        57451 => Key::Modifier(ModifierKey::RightHyper),
+        57452 => Key::Modifier(ModifierKey::RightMeta),
+        57453 => Key::Modifier(ModifierKey::IsoLevel3Shift),
+        57454 => Key::Modifier(ModifierKey::IsoLevel5Shift),
+
+        _ if shifted != 0 && modifiers.is_shift() => Key::Char(char::from_u32(shifted)?),
+        // the key code is always the unshifted key, even with caps lock on. shortcuts keep it,
+        // the same as without the kitty protocol, so ctrl-c still quits
+        code if caps_lock && !is_shortcut(modifiers) => {
+            Key::Char(char::from_u32(code)?.to_uppercase().next()?)
+        }
+        code => Key::Char(char::from_u32(code)?),
+    };
+    Some(key)
+}
+
+const fn is_shortcut(modifiers: Modifiers) -> bool {
+    modifiers.is_ctrl() || modifiers.is_alt() || modifiers.is_super()
+}
+
+// a parameter and its : separated sub-parameters, missing ones are 0
+type Param = [u32; 3];
+
+fn parse_params(params: &[u8], out: &mut [Param]) -> usize {
+    if params.is_empty() {
+        return 0;
+    }
+
+    let mut count = 0;
+    for param in params.split(|&c| c == b';') {
+        if let Some(slot) = out.get_mut(count) {
+            for (sub, value) in param.split(|&c| c == b':').zip(slot.iter_mut()) {
+                *value = sub.iter().fold(0u32, |acc, &c| {
+                    acc.saturating_mul(10)
+                        .saturating_add(c.wrapping_sub(b'0') as u32 % 10)
+                });
+            }
+        }
+        count += 1;
+    }
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(chunks: &[&[u8]]) -> Vec<Event> {
+        let mut parser = Parser::new();
+        let mut out = vec![];
+        for chunk in chunks {
+            parser.feed(chunk);
+            out.extend(std::iter::from_fn(|| parser.next_event()));
+        }
+        out
+    }
+
+    fn press(key: Key, modifiers: Modifiers) -> Event {
+        Event::Keyboard(key, modifiers, KeyKind::Press)
+    }
+
+    #[test]
+    fn sequences() {
+        let table: &[(&[u8], Event)] = &[
+            (b"a", press(Key::Char('a'), Modifiers::NONE)),
+            (b"A", press(Key::Char('A'), Modifiers::SHIFT)),
+            (b"\x01", press(Key::Char('a'), Modifiers::CTRL)),
+            (b"\x1bx", press(Key::Char('x'), Modifiers::ALT)),
+            ("é".as_bytes(), press(Key::Char('é'), Modifiers::NONE)),
+            (b"\x1b[A", press(Key::Up, Modifiers::NONE)),
+            (b"\x1b[1;5C", press(Key::Right, Modifiers::CTRL)),
+            (b"\x1b[1;3H", press(Key::Home, Modifiers::ALT)),
+            (b"\x1b[3;2~", press(Key::Delete, Modifiers::SHIFT)),
+            (b"\x1b[15~", press(Key::Function(5), Modifiers::NONE)),
+            (
+                b"\x1b[24;6~",
+                press(Key::Function(12), Modifiers::SHIFT | Modifiers::CTRL),
+            ),
+            (b"\x1b[Z", press(Key::BackTab, Modifiers::SHIFT)),
+            (b"\x1bOP", press(Key::Function(1), Modifiers::NONE)),
+            (b"\x1bOA", press(Key::Up, Modifiers::NONE)),
+            (b"\x1b[1;2P", press(Key::Function(1), Modifiers::SHIFT)),
+            (
+                b"\x1b[<0;5;10M",
+                Event::Mouse(
+                    MouseEvent::Held {
+                        button: MouseButton::Primary,
+                    },
+                    pos2(4, 9),
+                    Modifiers::NONE,
+                ),
+            ),
+            (
+                b"\x1b[<18;1;1M",
+                Event::Mouse(
+                    MouseEvent::Held {
+                        button: MouseButton::Secondary,
+                    },
+                    pos2(0, 0),
+                    Modifiers::CTRL,
+                ),
+            ),
+            (
+                b"\x1b[<65;3;4M",
+                Event::Mouse(
+                    MouseEvent::Scroll { delta: vec2(0, 1) },
+                    pos2(2, 3),
+                    Modifiers::NONE,
+                ),
+            ),
+            (
+                b"\x1b[<35;7;8M",
+                Event::Mouse(MouseEvent::Move, pos2(6, 7), Modifiers::NONE),
+            ),
+            (
+                b"\x1b[M !!",
+                Event::Mouse(
+                    MouseEvent::Held {
+                        button: MouseButton::Primary,
+                    },
+                    pos2(0, 0),
+                    Modifiers::NONE,
+                ),
+            ),
+            (
+                b"\x1b[Ma*+",
+                Event::Mouse(
+                    MouseEvent::Scroll { delta: vec2(0, 1) },
+                    pos2(9, 10),
+                    Modifiers::NONE,
+                ),
+            ),
+            (
+                b"\x1b[33;12;4M",
+                Event::Mouse(
+                    MouseEvent::Held {
+                        button: MouseButton::Middle,
+                    },
+                    pos2(11, 3),
+                    Modifiers::NONE,
+                ),
+            ),
+            (b"\x1b[I", Event::FocusGained),
+            (b"\x1b[O", Event::FocusLost),
+            (
+                b"\x1b[48;24;80;480;1920t",
+                Event::Resize(rect(vec2(80, 24))),
+            ),
+            (b"\x1b[97u", press(Key::Char('a'), Modifiers::NONE)),
+            (b"\x1b[97;5u", press(Key::Char('a'), Modifiers::CTRL)),
+            (b"\x1b[97:65;2u", press(Key::Char('A'), Modifiers::SHIFT)),
+            (b"\x1b[97;65u", press(Key::Char('A'), Modifiers::NONE)),
+            // caps lock with ctrl is still the base key
+            (b"\x1b[99;69u", press(Key::Char('c'), Modifiers::CTRL)),
+            (b"\x1b[122;69u", press(Key::Char('z'), Modifiers::CTRL)),
+            (b"\x1b[120;67u", press(Key::Char('x'), Modifiers::ALT)),
+            (b"\x1b[9;2u", press(Key::BackTab, Modifiers::SHIFT)),
+            (b"\x1b[57399u", press(Key::Keypad('0'), Modifiers::NONE)),
+            (
+                b"\x1b[57441;2:1u",
+                press(Key::Modifier(ModifierKey::LeftShift), Modifiers::SHIFT),
+            ),
+            (
+                b"\x1b[97;1:2u",
+                Event::Keyboard(Key::Char('a'), Modifiers::NONE, KeyKind::Repeat),
+            ),
+            (
+                b"\x1b[13;5:3u",
+                Event::Keyboard(Key::Enter, Modifiers::CTRL, KeyKind::Release),
+            ),
+            (
+                b"\x1b]52;c;aGVsbG8=\x07",
+                Event::Paste(String::from("hello")),
+            ),
+        ];
+
+        for (input, expected) in table {
+            assert_eq!(
+                parse(&[input]),
+                std::slice::from_ref(expected),
+                "{:?}",
+                String::from_utf8_lossy(input)
+            );
+        }
+    }
+
+    #[test]
+    fn replies_are_ignored() {
+        for input in [
+            &b"\x1b[12;40R"[..],
+            b"\x1b[?62;4c",
+            b"\x1b[?1u",
+            b"\x1b]11;rgb:0000/0000/0000\x1b\\",
+        ] {
+            assert_eq!(parse(&[input]), [], "{:?}", String::from_utf8_lossy(input));
+        }
+    }
+
+    #[test]
+    fn split_sequences() {
+        assert_eq!(
+            parse(&[b"\x1b[1;", b"5A"]),
+            [press(Key::Up, Modifiers::CTRL)]
+        );
+        assert_eq!(
+            parse(&[b"\x1b[<0;1", b"0;2m"]),
+            [] as [Event; 0],
+            "a release without a press is dropped"
+        );
+        assert_eq!(
+            parse(&[&[0xc3], &[0xa9]]),
+            [press(Key::Char('é'), Modifiers::NONE)]
+        );
+    }
+
+    #[test]
+    fn click() {
+        assert_eq!(
+            parse(&[b"\x1b[<0;2;3M\x1b[<0;2;3m"]),
+            [
+                Event::Mouse(
+                    MouseEvent::Held {
+                        button: MouseButton::Primary
+                    },
+                    pos2(1, 2),
+                    Modifiers::NONE
+                ),
+                Event::Mouse(
+                    MouseEvent::Click {
+                        button: MouseButton::Primary
+                    },
+                    pos2(1, 2),
+                    Modifiers::NONE
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn bracketed_paste() {
+        assert_eq!(
+            parse(&[b"\x1b[200~hello \x1b[A wor", b"ld\x1b[20", b"1~x"]),
+            [
+                Event::Paste(String::from("hello \x1b[A world")),
+                press(Key::Char('x'), Modifiers::NONE),
+            ]
+        );
+    }
+
+    #[test]
+    fn lone_escape() {
+        let mut parser = Parser::new();
+        parser.feed(b"\x1b");
+        assert_eq!(parser.next_event(), None);
+
+        parser.flush();
+        assert_eq!(
+            parser.next_event(),
+            Some(press(Key::Escape, Modifiers::NONE))
+        );
+        assert_eq!(parser.next_event(), None);
+
+        parser.feed(b"\x1b[");
+        parser.flush();
+        assert_eq!(
+            parser.next_event(),
+            Some(press(Key::Char('['), Modifiers::ALT))
+        );
+
+        parser.feed(b"\x1b\x1b[B");
+        assert_eq!(
+            parser.next_event(),
+            Some(press(Key::Escape, Modifiers::NONE))
+        );
+        assert_eq!(parser.next_event(), Some(press(Key::Down, Modifiers::NONE)));
+    }
+}
@@ -0,0 +1,159 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver},
+        Arc, Condvar, Mutex,
+    },
+    thread::JoinHandle,
+    time::Duration,
+};
+
+use super::{Event, EventSource};
+
+// the reader blocks on the event source in its own thread so the run loop can
+// sleep until either input arrives or something else wakes it up
+pub struct EventReader {
+    events: Receiver<std::io::Result<Event>>,
+    running: Arc<AtomicBool>,
+    pause: Arc<Pause>,
+    handle: Option<JoinHandle<()>>,
+}
+
+#[derive(Default)]
+struct Pause {
+    // (asked to pause, stopped reading)
+    state: Mutex<(bool, bool)>,
+    changed: Condvar,
+}
+
+impl EventReader {
+    const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+    pub fn spawn(mut source: impl EventSource, wake: impl Fn() + Send + 'static) -> Self {
+        let (tx, events) = mpsc::channel();
+        let running = Arc::new(AtomicBool::new(true));
+        let pause = Arc::new(Pause::default());
+
+        let handle = std::thread::Builder::new()
+            .name(String::from("dearsum-input"))
+            .spawn({
+                let running = Arc::clone(&running);
+                let pause = Arc::clone(&pause);
+                move || {
+                    while running.load(Ordering::Acquire) {
+                        let mut state = pause.state.lock().unwrap();
+                        if state.0 {
+                            state.1 = true;
+                            pause.changed.notify_all();
+                            while state.0 {
+                                state = pause.changed.wait(state).unwrap();
+                            }
+                            state.1 = false;
+                        }
+                        drop(state);
+
+                        let ev = match source.poll(Self::POLL_INTERVAL) {
+                            Ok(Some(ev)) => Ok(ev),
+                            Ok(None) => continue,
+                            Err(err) => Err(err),
+                        };
+
+                        let failed = ev.is_err();
+                        if tx.send(ev).is_err() {
+                            break;
+                        }
+                        wake();
+
+                        if failed {
+                            break;
+                        }
+                    }
+                }
+            })
+            .expect("spawn input thread");
+
+        Self {
+            events,
+            running,
+            pause,
+            handle: Some(handle),
+        }
+    }
+
+    // stops reading until the guard is dropped, so a reply to a query can be read elsewhere
+    pub fn pause(&self) -> Paused<'_> {
+        let mut state = self.pause.state.lock().unwrap();
+        state.0 = true;
+        // the thread may be blocked in a poll, or gone if its source failed
+        while !state.1 && self.handle.as_ref().is_some_and(|h| !h.is_finished()) {
+            state = (self.pause.changed)
+                .wait_timeout(state, Self::POLL_INTERVAL)
+                .unwrap()
+                .0;
+        }
+        Paused(self)
+    }
+
+    pub fn try_read(&self) -> Option<std::io::Result<Event>> {
+        // the thread sends the error that stopped it, so there is nothing more to say once it is gone
+        self.events.try_recv().ok()
+    }
+}
+
+impl Drop for EventReader {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Release);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+pub struct Paused<'a>(&'a EventReader);
+
+impl Drop for Paused<'_> {
+    fn drop(&mut self) {
+        self.0.pause.state.lock().unwrap().0 = false;
+        self.0.pause.changed.notify_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+
+    use super::*;
+
+    struct Counting(Arc<AtomicUsize>);
+
+    impl EventSource for Counting {
+        fn poll(&mut self, _: Duration) -> std::io::Result<Option<Event>> {
+            self.0.fetch_add(1, Ordering::AcqRel);
+            std::thread::sleep(Duration::from_millis(1));
+            Ok(None)
+        }
+    }
+
+    #[test]
+    fn paused_reader_stops_polling() {
+        let polls = Arc::new(AtomicUsize::new(0));
+        let reader = EventReader::spawn(Counting(Arc::clone(&polls)), || {});
+
+        let paused = reader.pause();
+        let before = polls.load(Ordering::Acquire);
+        std::thread::sleep(Duration::from_millis(20));
+        assert_eq!(polls.load(Ordering::Acquire), before);
+
+        drop(paused);
+        std::thread::sleep(Duration::from_millis(20));
+        assert!(polls.load(Ordering::Acquire) > before);
+    }
+
+    #[test]
+    fn pausing_a_finished_reader_returns() {
+        let (tx, rx) = mpsc::channel::<Event>();
+        drop(tx);
+        let reader = EventReader::spawn(rx, || {});
+        drop(reader.pause());
+    }
+}
@@ -0,0 +1,468 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use crate::geom::{pos2, vec2, Rect, Vec2};
+
+use super::{Event, Key, KeyKind, MediaKey, ModifierKey, Modifiers, MouseButton, MouseEvent};
+
+// a recording is plain text, one entry per line:
+//
+//   <frame> <time> tick
+//   <frame> <time> key <key> <modifiers> <kind>
+//   <frame> <time> mouse <event> <x> <y> <modifiers>
+//   <frame> <time> resize <left> <top> <right> <bottom>
+//   <frame> <time> paste <hex encoded bytes, or - when empty>
+//   <frame> <time> quit | focus-gained | focus-lost
+//
+// events are written in the order they were handled, and each frame ends with
+// a tick carrying the exact time the ui was ticked with
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Entry {
+    pub frame: u64,
+    // seconds since the start of the session
+    pub time: f32,
+    pub kind: EntryKind,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum EntryKind {
+    Event(Event),
+    Tick,
+}
+
+pub struct Recorder<W: Write = BufWriter<File>> {
+    out: W,
+}
+
+impl Recorder {
+    pub fn create(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        File::create(path).map(BufWriter::new).map(Self::new)
+    }
+}
+
+impl<W: Write> Recorder<W> {
+    pub const fn new(out: W) -> Self {
+        Self { out }
+    }
+
+    pub fn event(&mut self, frame: u64, time: f32, event: &Event) -> std::io::Result<()> {
+        write!(self.out, "{frame} {time} ")?;
+        write_event(&mut self.out, event)?;
+        writeln!(self.out)
+    }
+
+    // the end of a frame, this flushes so a crash doesn't lose the interesting part
+    pub fn tick(&mut self, frame: u64, time: f32) -> std::io::Result<()> {
+        writeln!(self.out, "{frame} {time} tick")?;
+        self.out.flush()
+    }
+
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Recording {
+    entries: Vec<Entry>,
+}
+
+impl Recording {
+    pub fn load(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+
+    pub fn parse(input: &str) -> std::io::Result<Self> {
+        let entries = input
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(i, line)| {
+                parse_entry(line).ok_or_else(|| {
+                    std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        format!("invalid recording entry on line {}: {line}", i + 1),
+                    )
+                })
+            })
+            .collect::<std::io::Result<_>>()?;
+        Ok(Self { entries })
+    }
+
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    // the size of the terminal when the recording started
+    pub fn size(&self) -> Option<Vec2> {
+        self.entries.iter().find_map(|entry| match entry.kind {
+            EntryKind::Event(Event::Resize(rect)) => Some(rect.size()),
+            _ => None,
+        })
+    }
+}
+
+const NAMED_KEYS: &[(&str, Key)] = &[
+    ("left", Key::Left),
+    ("right", Key::Right),
+    ("up", Key::Up),
+    ("down", Key::Down),
+    ("page-up", Key::PageUp),
+    ("page-down", Key::PageDown),
+    ("home", Key::Home),
+    ("end", Key::End),
+    ("insert", Key::Insert),
+    ("enter", Key::Enter),
+    ("delete", Key::Delete),
+    ("backspace", Key::Backspace),
+    ("escape", Key::Escape),
+    ("tab", Key::Tab),
+    ("back-tab", Key::BackTab),
+    ("caps-lock", Key::CapsLock),
+    ("scroll-lock", Key::ScrollLock),
+    ("num-lock", Key::NumLock),
+    ("print-screen", Key::PrintScreen),
+    ("pause", Key::Pause),
+    ("menu", Key::Menu),
+    ("keypad-enter", Key::KeypadEnter),
+    ("keypad-begin", Key::KeypadBegin),
+];
+
+const MEDIA_KEYS: &[(&str, MediaKey)] = &[
+    ("play", MediaKey::Play),
+    ("pause", MediaKey::Pause),
+    ("play-pause", MediaKey::PlayPause),
+    ("reverse", MediaKey::Reverse),
+    ("stop", MediaKey::Stop),
+    ("fast-forward", MediaKey::FastForward),
+    ("rewind", MediaKey::Rewind),
+    ("track-next", MediaKey::TrackNext),
+    ("track-previous", MediaKey::TrackPrevious),
+    ("record", MediaKey::Record),
+    ("lower-volume", MediaKey::LowerVolume),
+    ("raise-volume", MediaKey::RaiseVolume),
+    ("mute-volume", MediaKey::MuteVolume),
+];
+
+const MODIFIER_KEYS: &[(&str, ModifierKey)] = &[
+    ("left-shift", ModifierKey::LeftShift),
+    ("left-ctrl", ModifierKey::LeftCtrl),
+    ("left-alt", ModifierKey::LeftAlt),
+    ("left-super", ModifierKey::LeftSuper),
+    ("left-hyper", ModifierKey::LeftHyper),
+    ("left-meta", ModifierKey::LeftMeta),
+    ("right-shift", ModifierKey::RightShift),
+    ("right-ctrl", ModifierKey::RightCtrl),
+    ("right-alt", ModifierKey::RightAlt),
+    ("right-super", ModifierKey::RightSuper),
+    ("right-hyper", ModifierKey::RightHyper),
+    ("right-meta", ModifierKey::RightMeta),
+    ("iso-level3-shift", ModifierKey::IsoLevel3Shift),
+    ("iso-level5-shift", ModifierKey::IsoLevel5Shift),
+];
+
+const KEY_KINDS: &[(&str, KeyKind)] = &[
+    ("press", KeyKind::Press),
+    ("repeat", KeyKind::Repeat),
+    ("release", KeyKind::Release),
+];
+
+const BUTTONS: &[(&str, MouseButton)] = &[
+    ("primary", MouseButton::Primary),
+    ("secondary", MouseButton::Secondary),
+    ("middle", MouseButton::Middle),
+];
+
+fn name_of<T: PartialEq>(table: &[(&'static str, T)], value: &T) -> &'static str {
+    table
+        .iter()
+        .find_map(|(name, v)| (v == value).then_some(*name))
+        .expect("every value has a name")
+}
+
+fn lookup<T: Copy>(table: &[(&str, T)], name: &str) -> Option<T> {
+    table.iter().find_map(|&(n, v)| (n == name).then_some(v))
+}
+
+fn write_event(out: &mut impl Write, event: &Event) -> std::io::Result<()> {
+    match event {
+        Event::Keyboard(key, modifiers, kind) => {
+            out.write_all(b"key ")?;
+            match key {
+                Key::Char(ch) => write!(out, "char {}", *ch as u32)?,
+                Key::Function(n) => write!(out, "function {n}")?,
+                Key::Keypad(ch) => write!(out, "keypad {}", *ch as u32)?,
+                Key::Media(key) => write!(out, "media {}", name_of(MEDIA_KEYS, key))?,
+                Key::Modifier(key) => write!(out, "modifier {}", name_of(MODIFIER_KEYS, key))?,
+                key => out.write_all(name_of(NAMED_KEYS, key).as_bytes())?,
+            }
+            write!(out, " {} {}", modifiers.0, name_of(KEY_KINDS, kind))
+        }
+
+        Event::Mouse(event, pos, modifiers) => {
+            out.write_all(b"mouse ")?;
+            match event {
+                MouseEvent::Move => out.write_all(b"move")?,
+                MouseEvent::Click { button } => write!(out, "click {}", name_of(BUTTONS, button))?,
+                MouseEvent::Release { button } => {
+                    write!(out, "release {}", name_of(BUTTONS, button))?
+                }
+                MouseEvent::Held { button } => write!(out, "held {}", name_of(BUTTONS, button))?,
+                MouseEvent::DragStart { button } => {
+                    write!(out, "drag-start {}", name_of(BUTTONS, button))?
+                }
+                MouseEvent::DragHeld { delta, button } => write!(
+                    out,
+                    "drag-held {} {} {}",
+                    delta.x,
+                    delta.y,
+                    name_of(BUTTONS, button)
+                )?,
+                MouseEvent::DragRelease { button } => {
+                    write!(out, "drag-release {}", name_of(BUTTONS, button))?
+                }
+                MouseEvent::Scroll { delta } => write!(out, "scroll {} {}", delta.x, delta.y)?,
+            }
+            write!(out, " {} {} {}", pos.x, pos.y, modifiers.0)
+        }
+
+        Event::Resize(rect) => write!(
+            out,
+            "resize {} {} {} {}",
+            rect.min.x, rect.min.y, rect.max.x, rect.max.y
+        ),
+
+        Event::Paste(data) if data.is_empty() => out.write_all(b"paste -"),
+        Event::Paste(data) => {
+            out.write_all(b"paste ")?;
+            data.bytes().try_for_each(|b| write!(out, "{b:02x}"))
+        }
+
+        Event::Quit => out.write_all(b"quit"),
+        Event::FocusGained => out.write_all(b"focus-gained"),
+        Event::FocusLost => out.write_all(b"focus-lost"),
+    }
+}
+
+fn parse_entry(line: &str) -> Option<Entry> {
+    let mut fields = line.split_whitespace();
+    let mut next = || fields.next();
+
+    let frame = next()?.parse().ok()?;
+    let time = next()?
+        .parse::<f32>()
+        .ok()
+        .filter(|t| t.is_finite() && *t >= 0.0)?;
+
+    macro_rules! num {
+        () => {
+            next()?.parse().ok()?
+        };
+    }
+
+    let event = match next()? {
+        "tick" => None,
+
+        "key" => {
+            let key = match next()? {
+                "char" => Key::Char(char::from_u32(num!())?),
+                "function" => Key::Function(num!()),
+                "keypad" => Key::Keypad(char::from_u32(num!())?),
+                "media" => Key::Media(lookup(MEDIA_KEYS, next()?)?),
+                "modifier" => Key::Modifier(lookup(MODIFIER_KEYS, next()?)?),
+                name => lookup(NAMED_KEYS, name)?,
+            };
+            let modifiers = Modifiers(num!());
+            let kind = lookup(KEY_KINDS, next()?)?;
+            Some(Event::Keyboard(key, modifiers, kind))
+        }
+
+        "mouse" => {
+            let event = match next()? {
+                "move" => MouseEvent::Move,
+                "click" => MouseEvent::Click {
+                    button: lookup(BUTTONS, next()?)?,
+                },
+                "release" => MouseEvent::Release {
+                    button: lookup(BUTTONS, next()?)?,
+                },
+                "held" => MouseEvent::Held {
+                    button: lookup(BUTTONS, next()?)?,
+                },
+                "drag-start" => MouseEvent::DragStart {
+                    button: lookup(BUTTONS, next()?)?,
+                },
+                "drag-held" => MouseEvent::DragHeld {
+                    delta: vec2(num!(), num!()),
+                    button: lookup(BUTTONS, next()?)?,
+                },
+                "drag-release" => MouseEvent::DragRelease {
+                    button: lookup(BUTTONS, next()?)?,
+                },
+                "scroll" => MouseEvent::Scroll {
+                    delta: vec2(num!(), num!()),
+                },
+                _ => return None,
+            };
+            let pos = pos2(num!(), num!());
+            Some(Event::Mouse(event, pos, Modifiers(num!())))
+        }
+
+        "resize" => {
+            let min = pos2(num!(), num!());
+            let max = pos2(num!(), num!());
+            Some(Event::Resize(Rect::from_min_max(min, max)))
+        }
+
+        "paste" => match next()? {
+            "-" => Some(Event::Paste(String::new())),
+            hex if hex.len() % 2 == 0 && hex.bytes().all(|b| b.is_ascii_hexdigit()) => {
+                let bytes = (0..hex.len())
+                    .step_by(2)
+                    .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+                    .collect::<Option<Vec<_>>>()?;
+                Some(Event::Paste(String::from_utf8(bytes).ok()?))
+            }
+            _ => return None,
+        },
+
+        "quit" => Some(Event::Quit),
+        "focus-gained" => Some(Event::FocusGained),
+        "focus-lost" => Some(Event::FocusLost),
+        _ => return None,
+    };
+
+    // anything left over means the line isn't what it looks like
+    if next().is_some() {
+        return None;
+    }
+
+    let kind = event.map_or(EntryKind::Tick, EntryKind::Event);
+    Some(Entry { frame, time, kind })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let events = [
+            Event::Keyboard(Key::Char('a'), Modifiers::NONE, KeyKind::Press),
+            Event::Keyboard(Key::Char('A'), Modifiers::SHIFT, KeyKind::Press),
+            Event::Keyboard(
+                Key::Char('日'),
+                Modifiers::CTRL | Modifiers::ALT,
+                KeyKind::Repeat,
+            ),
+            Event::Keyboard(Key::Char(' '), Modifiers::NONE, KeyKind::Release),
+            Event::Keyboard(Key::PageDown, Modifiers::SHIFT, KeyKind::Press),
+            Event::Keyboard(Key::BackTab, Modifiers::SHIFT, KeyKind::Press),
+            Event::Keyboard(Key::Function(12), Modifiers::CTRL, KeyKind::Press),
+            Event::Keyboard(Key::Keypad('5'), Modifiers::NONE, KeyKind::Press),
+            Event::Keyboard(
+                Key::Media(MediaKey::PlayPause),
+                Modifiers::NONE,
+                KeyKind::Press,
+            ),
+            Event::Keyboard(
+                Key::Modifier(ModifierKey::RightSuper),
+                Modifiers::SUPER,
+                KeyKind::Release,
+            ),
+            Event::Mouse(MouseEvent::Move, pos2(3, 4), Modifiers::NONE),
+            Event::Mouse(
+                MouseEvent::Click {
+                    button: MouseButton::Secondary,
+                },
+                pos2(0, 0),
+                Modifiers::CTRL,
+            ),
+            Event::Mouse(
+                MouseEvent::DragHeld {
+                    delta: vec2(-2, 1),
+                    button: MouseButton::Middle,
+                },
+                pos2(7, 1),
+                Modifiers::NONE,
+            ),
+            Event::Mouse(
+                MouseEvent::Scroll { delta: vec2(0, -3) },
+                pos2(1, 2),
+                Modifiers::SHIFT,
+            ),
+            Event::Resize(Rect::from_min_max(pos2(0, 0), pos2(80, 24))),
+            Event::Paste(String::from("two words\nand é 日 \x1b[0m")),
+            Event::Paste(String::new()),
+            Event::FocusGained,
+            Event::FocusLost,
+            Event::Quit,
+        ];
+
+        let mut recorder = Recorder::new(Vec::new());
+        for (i, event) in events.iter().enumerate() {
+            recorder.event(1, i as f32 * 0.25, event).unwrap();
+        }
+        recorder.tick(1, 5.5).unwrap();
+
+        let text = String::from_utf8(recorder.into_inner()).unwrap();
+        let recording = Recording::parse(&text).unwrap();
+
+        let mut expected = events
+            .iter()
+            .enumerate()
+            .map(|(i, event)| Entry {
+                frame: 1,
+                time: i as f32 * 0.25,
+                kind: EntryKind::Event(event.clone()),
+            })
+            .collect::<Vec<_>>();
+        expected.push(Entry {
+            frame: 1,
+            time: 5.5,
+            kind: EntryKind::Tick,
+        });
+        assert_eq!(recording.entries(), expected, "{text}");
+        assert_eq!(recording.size(), Some(vec2(80, 24)));
+    }
+
+    #[test]
+    fn malformed_lines_are_rejected() {
+        let table = [
+            "",
+            "1",
+            "1 0.5",
+            "x 0.5 tick",
+            "1 -1 tick",
+            "1 NaN tick",
+            "1 0.5 tick extra",
+            "1 0.5 nonsense",
+            "1 0.5 key char 97 0",
+            "1 0.5 key char 97 0 pressed",
+            "1 0.5 key char 1114112 0 press",
+            "1 0.5 key sideways 0 press",
+            "1 0.5 mouse click primary 1 2",
+            "1 0.5 mouse click fourth 1 2 0",
+            "1 0.5 mouse wiggle 1 2 0",
+            "1 0.5 resize 0 0 80",
+            "1 0.5 paste",
+            "1 0.5 paste abc",
+            "1 0.5 paste zz",
+            "1 0.5 paste +a",
+            "1 0.5 paste ff",
+            "1 0.5 paste 6869 6869",
+            "1 0.5 quit now",
+        ];
+        for line in table {
+            assert!(parse_entry(line).is_none(), "{line:?}");
+        }
+
+        let err = Recording::parse("1 0 tick\n\n1 0.5 paste\n").unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        assert!(err.to_string().contains("line 3"), "{err}");
+    }
+}
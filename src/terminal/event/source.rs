@@ -0,0 +1,437 @@
+use std::{
+    io::Read,
+    sync::mpsc::{self, Receiver, RecvTimeoutError},
+    time::Duration,
+};
+
+use super::{Event, Parser};
+
+// something the input thread can block on
+pub trait EventSource: Send + 'static {
+    // waits up to `timeout` for the next event
+    fn poll(&mut self, timeout: Duration) -> std::io::Result<Option<Event>>;
+}
+
+impl EventSource for Receiver<Event> {
+    fn poll(&mut self, timeout: Duration) -> std::io::Result<Option<Event>> {
+        match self.recv_timeout(timeout) {
+            Ok(ev) => Ok(Some(ev)),
+            Err(RecvTimeoutError::Timeout) => Ok(None),
+            Err(RecvTimeoutError::Disconnected) => Err(std::io::ErrorKind::BrokenPipe.into()),
+        }
+    }
+}
+
+// decodes the bytes of any reader, e.g. a socket or a pipe, on any platform.
+//
+// there's no portable way to wait on a reader, so it's read on its own thread.
+// that thread ends the next time the reader returns after this is dropped
+pub struct ReadEvents {
+    bytes: Receiver<std::io::Result<Vec<u8>>>,
+    parser: Parser,
+}
+
+impl ReadEvents {
+    pub fn new(mut reader: impl Read + Send + 'static) -> Self {
+        let (tx, bytes) = mpsc::channel();
+        std::thread::spawn(move || {
+            let mut buf = [0; 1024];
+            loop {
+                let read = match reader.read(&mut buf) {
+                    Ok(0) => Err(std::io::ErrorKind::UnexpectedEof.into()),
+                    Ok(n) => Ok(buf[..n].to_vec()),
+                    Err(err) if err.kind() == std::io::ErrorKind::Interrupted => continue,
+                    Err(err) => Err(err),
+                };
+                let done = read.is_err();
+                if tx.send(read).is_err() || done {
+                    break;
+                }
+            }
+        });
+
+        Self {
+            bytes,
+            parser: Parser::new(),
+        }
+    }
+}
+
+impl EventSource for ReadEvents {
+    fn poll(&mut self, timeout: Duration) -> std::io::Result<Option<Event>> {
+        if let Some(ev) = self.parser.next_event() {
+            return Ok(Some(ev));
+        }
+
+        match self.bytes.recv_timeout(timeout) {
+            Ok(bytes) => self.parser.feed(&bytes?),
+            // a lone escape is only known to be one once nothing follows it
+            Err(RecvTimeoutError::Timeout) => self.parser.flush(),
+            Err(RecvTimeoutError::Disconnected) => {
+                return Err(std::io::ErrorKind::BrokenPipe.into())
+            }
+        }
+        Ok(self.parser.next_event())
+    }
+}
+
+#[cfg(not(unix))]
+#[derive(Default)]
+pub struct CrosstermEvents {
+    mouse: super::MouseState,
+}
+
+#[cfg(not(unix))]
+impl EventSource for CrosstermEvents {
+    fn poll(&mut self, timeout: Duration) -> std::io::Result<Option<Event>> {
+        if !crossterm::event::poll(timeout)? {
+            return Ok(None);
+        }
+        Ok(super::translate(crossterm::event::read()?, &mut self.mouse))
+    }
+}
+
+#[cfg(unix)]
+pub(crate) use self::unix::read_reply;
+#[cfg(unix)]
+pub use self::unix::StreamEvents;
+
+#[cfg(unix)]
+mod unix {
+    use std::{
+        io::Read,
+        os::fd::{AsRawFd, FromRawFd as _},
+        sync::{
+            atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+            Mutex,
+        },
+        time::{Duration, Instant},
+    };
+
+    use crate::{
+        geom::{rect, vec2, Vec2},
+        terminal::event::{Event, EventSource, Parser},
+    };
+
+    // SIGWINCH belongs to the whole process. ours is put in front of whatever handler was there
+    // while any stream is watching, and the old one is put back when the last stream goes away.
+    //
+    // the handler only counts, each stream remembers the last count it saw
+    static RESIZES: AtomicU64 = AtomicU64::new(0);
+    static PREVIOUS: AtomicUsize = AtomicUsize::new(libc::SIG_DFL);
+    static PREVIOUS_SIGINFO: AtomicBool = AtomicBool::new(false);
+    // how many streams are watching, and the handler to put back
+    static WATCHING: Mutex<(usize, Option<libc::sigaction>)> = Mutex::new((0, None));
+
+    extern "C" fn on_resize(sig: libc::c_int, info: *mut libc::siginfo_t, ctx: *mut libc::c_void) {
+        RESIZES.fetch_add(1, Ordering::AcqRel);
+
+        let previous = PREVIOUS.load(Ordering::Acquire);
+        if previous == libc::SIG_DFL || previous == libc::SIG_IGN {
+            return;
+        }
+
+        // SAFETY: this is what the previous handler was installed as
+        unsafe {
+            if PREVIOUS_SIGINFO.load(Ordering::Acquire) {
+                let handler: extern "C" fn(libc::c_int, *mut libc::siginfo_t, *mut libc::c_void) =
+                    std::mem::transmute(previous);
+                handler(sig, info, ctx)
+            } else {
+                let handler: extern "C" fn(libc::c_int) = std::mem::transmute(previous);
+                handler(sig)
+            }
+        }
+    }
+
+    fn handler_address() -> libc::sighandler_t {
+        on_resize as extern "C" fn(libc::c_int, *mut libc::siginfo_t, *mut libc::c_void)
+            as libc::sighandler_t
+    }
+
+    fn watch_resizes() -> std::io::Result<()> {
+        let mut watching = WATCHING.lock().unwrap_or_else(|err| err.into_inner());
+        if watching.0 == 0 {
+            // SAFETY: both are plain c structs, and the old handler is known before ours can run
+            unsafe {
+                let mut previous: libc::sigaction = std::mem::zeroed();
+                if libc::sigaction(libc::SIGWINCH, std::ptr::null(), &mut previous) == -1 {
+                    return Err(std::io::Error::last_os_error());
+                }
+                PREVIOUS.store(previous.sa_sigaction, Ordering::Release);
+                PREVIOUS_SIGINFO
+                    .store(previous.sa_flags & libc::SA_SIGINFO != 0, Ordering::Release);
+
+                let mut action: libc::sigaction = std::mem::zeroed();
+                action.sa_sigaction = handler_address();
+                action.sa_flags = libc::SA_SIGINFO | libc::SA_RESTART;
+                libc::sigemptyset(&mut action.sa_mask);
+                if libc::sigaction(libc::SIGWINCH, &action, std::ptr::null_mut()) == -1 {
+                    return Err(std::io::Error::last_os_error());
+                }
+                watching.1 = Some(previous);
+            }
+        }
+        watching.0 += 1;
+        Ok(())
+    }
+
+    fn unwatch_resizes() {
+        let mut watching = WATCHING.lock().unwrap_or_else(|err| err.into_inner());
+        watching.0 -= 1;
+        if watching.0 > 0 {
+            return;
+        }
+
+        let Some(previous) = watching.1.take() else {
+            return;
+        };
+        // SAFETY: as above. if someone put their own handler in after ours, it stays
+        unsafe {
+            let mut current: libc::sigaction = std::mem::zeroed();
+            libc::sigaction(libc::SIGWINCH, std::ptr::null(), &mut current);
+            if current.sa_sigaction == handler_address() {
+                libc::sigaction(libc::SIGWINCH, &previous, std::ptr::null_mut());
+            }
+        }
+    }
+
+    // decodes the input of a tty, pty, pipe or socket with our own parser
+    pub struct StreamEvents<R> {
+        reader: R,
+        parser: Parser,
+        size: Option<Vec2>,
+        // the last resize this stream saw, if it is a terminal
+        resizes: Option<u64>,
+    }
+
+    impl<R: Read + AsRawFd + Send + 'static> StreamEvents<R> {
+        pub fn new(reader: R) -> Self {
+            let size = window_size(reader.as_raw_fd());
+            // without the handler it can still be used, it just won't see resizes
+            let resizes = size
+                .and_then(|_| watch_resizes().ok())
+                .map(|_| RESIZES.load(Ordering::Acquire));
+
+            Self {
+                reader,
+                parser: Parser::new(),
+                size,
+                resizes,
+            }
+        }
+    }
+
+    impl<R> Drop for StreamEvents<R> {
+        fn drop(&mut self) {
+            if self.resizes.is_some() {
+                unwatch_resizes()
+            }
+        }
+    }
+
+    impl StreamEvents<std::fs::File> {
+        pub fn tty() -> std::io::Result<Self> {
+            open_tty().map(Self::new)
+        }
+    }
+
+    // stdin if it is a terminal, otherwise the controlling terminal
+    fn open_tty() -> std::io::Result<std::fs::File> {
+        if unsafe { libc::isatty(libc::STDIN_FILENO) } == 1 {
+            return match unsafe { libc::dup(libc::STDIN_FILENO) } {
+                -1 => Err(std::io::Error::last_os_error()),
+                fd => Ok(unsafe { std::fs::File::from_raw_fd(fd) }),
+            };
+        }
+        std::fs::File::open("/dev/tty")
+    }
+
+    // collects the answer to a query until `done` has all of it, or the time runs out
+    pub fn read_reply(timeout: Duration, done: fn(&[u8]) -> bool) -> std::io::Result<Vec<u8>> {
+        let mut file = open_tty()?;
+        let deadline = Instant::now() + timeout;
+
+        let mut reply = Vec::new();
+        while !done(&reply) {
+            let left = deadline.saturating_duration_since(Instant::now());
+            if left.is_zero() {
+                break;
+            }
+
+            let mut fd = libc::pollfd {
+                fd: file.as_raw_fd(),
+                events: libc::POLLIN,
+                revents: 0,
+            };
+            let timeout = left.as_millis().clamp(1, libc::c_int::MAX as _) as libc::c_int;
+            match unsafe { libc::poll(&mut fd, 1, timeout) } {
+                -1 => {
+                    let err = std::io::Error::last_os_error();
+                    if err.kind() == std::io::ErrorKind::Interrupted {
+                        continue;
+                    }
+                    return Err(err);
+                }
+                0 => break,
+                _ => {}
+            }
+
+            let mut buf = [0; 1024];
+            match file.read(&mut buf)? {
+                0 => break,
+                n => reply.extend_from_slice(&buf[..n]),
+            }
+        }
+        Ok(reply)
+    }
+
+    impl<R: Read + AsRawFd + Send + 'static> EventSource for StreamEvents<R> {
+        fn poll(&mut self, timeout: Duration) -> std::io::Result<Option<Event>> {
+            if let Some(ev) = self.parser.next_event() {
+                return Ok(Some(ev));
+            }
+
+            let resizes = RESIZES.load(Ordering::Acquire);
+            if self.resizes.is_some_and(|seen| seen != resizes) {
+                self.resizes = Some(resizes);
+                let size = window_size(self.reader.as_raw_fd());
+                if size.is_some() && size != self.size {
+                    self.size = size;
+                    return Ok(size.map(|size| Event::Resize(rect(size))));
+                }
+            }
+
+            let mut fd = libc::pollfd {
+                fd: self.reader.as_raw_fd(),
+                events: libc::POLLIN,
+                revents: 0,
+            };
+
+            let timeout = timeout.as_millis().min(libc::c_int::MAX as _) as libc::c_int;
+            match unsafe { libc::poll(&mut fd, 1, timeout) } {
+                -1 => {
+                    let err = std::io::Error::last_os_error();
+                    if err.kind() == std::io::ErrorKind::Interrupted {
+                        return Ok(None);
+                    }
+                    return Err(err);
+                }
+                0 => {
+                    self.parser.flush();
+                    return Ok(self.parser.next_event());
+                }
+                _ => {}
+            }
+
+            let mut buf = [0; 1024];
+            match self.reader.read(&mut buf)? {
+                0 => Err(std::io::ErrorKind::UnexpectedEof.into()),
+                n => {
+                    self.parser.feed(&buf[..n]);
+                    Ok(self.parser.next_event())
+                }
+            }
+        }
+    }
+
+    fn window_size(fd: libc::c_int) -> Option<Vec2> {
+        let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+        if unsafe { libc::ioctl(fd, libc::TIOCGWINSZ, &mut size) } == -1 {
+            return None;
+        }
+        Some(vec2(size.ws_col as _, size.ws_row as _))
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        static CALLED: AtomicUsize = AtomicUsize::new(0);
+
+        extern "C" fn host_handler(_: libc::c_int) {
+            CALLED.fetch_add(1, Ordering::AcqRel);
+        }
+
+        // a pty, with the size set from the other end
+        fn pty(size: Vec2) -> (std::fs::File, std::fs::File) {
+            unsafe {
+                let master = libc::posix_openpt(libc::O_RDWR | libc::O_NOCTTY);
+                assert!(master >= 0);
+                assert_eq!(libc::grantpt(master), 0);
+                assert_eq!(libc::unlockpt(master), 0);
+                let name = std::ffi::CStr::from_ptr(libc::ptsname(master));
+                let slave = std::fs::OpenOptions::new()
+                    .read(true)
+                    .write(true)
+                    .open(name.to_str().unwrap())
+                    .unwrap();
+                let master = std::fs::File::from_raw_fd(master);
+                set_size(&master, size);
+                (master, slave)
+            }
+        }
+
+        fn set_size(pty: &std::fs::File, size: Vec2) {
+            let size = libc::winsize {
+                ws_col: size.x as _,
+                ws_row: size.y as _,
+                ws_xpixel: 0,
+                ws_ypixel: 0,
+            };
+            assert_ne!(
+                unsafe { libc::ioctl(pty.as_raw_fd(), libc::TIOCSWINSZ, &size) },
+                -1
+            );
+        }
+
+        fn current_handler() -> libc::sighandler_t {
+            let mut current: libc::sigaction = unsafe { std::mem::zeroed() };
+            unsafe { libc::sigaction(libc::SIGWINCH, std::ptr::null(), &mut current) };
+            current.sa_sigaction
+        }
+
+        #[test]
+        fn every_stream_sees_a_resize_and_the_old_handler_is_kept() {
+            let host = host_handler as extern "C" fn(libc::c_int) as libc::sighandler_t;
+            unsafe { libc::signal(libc::SIGWINCH, host) };
+
+            let (master, slave) = pty(vec2(80, 24));
+            let mut a = StreamEvents::new(slave.try_clone().unwrap());
+            let mut b = StreamEvents::new(slave);
+            assert_eq!(current_handler(), handler_address());
+
+            set_size(&master, vec2(100, 30));
+            unsafe { libc::raise(libc::SIGWINCH) };
+
+            let resized = Some(Event::Resize(rect(vec2(100, 30))));
+            assert_eq!(a.poll(Duration::ZERO).unwrap(), resized);
+            assert_eq!(b.poll(Duration::ZERO).unwrap(), resized);
+            assert_eq!(CALLED.load(Ordering::Acquire), 1);
+
+            drop(a);
+            assert_eq!(current_handler(), handler_address());
+            drop(b);
+            assert_eq!(current_handler(), host);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::terminal::event::{Key, KeyKind, Modifiers};
+
+    #[test]
+    fn bytes_from_any_reader_are_decoded() {
+        let mut events = ReadEvents::new(std::io::Cursor::new(b"a\x1b[A\x1b".to_vec()));
+        let timeout = Duration::from_secs(5);
+
+        let key = |key| Some(Event::Keyboard(key, Modifiers::NONE, KeyKind::Press));
+        assert_eq!(events.poll(timeout).unwrap(), key(Key::Char('a')));
+        assert_eq!(events.poll(timeout).unwrap(), key(Key::Up));
+        // the reader ran out with half an escape sequence left
+        let err = events.poll(timeout).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+    }
+}
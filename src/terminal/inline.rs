@@ -0,0 +1,246 @@
+use crate::{
+    color::{ColorDepth, Rgba},
+    geom::{pos2, Pos2, Rect},
+    paint::{Attribute, CursorShape, Image, Link, Renderer},
+};
+
+use super::InlineHeight;
+
+// the rows below the cursor on the normal screen that we draw into.
+//
+// everything is done with relative movement so the region stays put
+// when the terminal scrolls it up
+pub struct Inline {
+    pub height: i32,
+    // the terminal cursor, relative to the top left of the region
+    cursor: Pos2,
+    // the screen row of the region, if the terminal told us where the cursor was
+    origin: Option<i32>,
+}
+
+impl Inline {
+    pub fn initial_height(inline_height: InlineHeight, rows: i32) -> i32 {
+        match inline_height {
+            InlineHeight::Fixed(height) => (height as i32).clamp(1, rows.max(1)),
+            InlineHeight::Content => 1,
+        }
+    }
+
+    // newlines scroll the screen if there isn't enough room below the cursor,
+    // whatever was already on the rows we got is cleared so it can't show through
+    pub fn reserve(
+        renderer: &mut impl Renderer,
+        height: i32,
+        origin: Option<i32>,
+        rows: i32,
+    ) -> std::io::Result<Self> {
+        renderer.move_to_column(0)?;
+        for _ in 1..height {
+            renderer.write('\n')?;
+        }
+        if height > 1 {
+            renderer.move_up(height - 1)?;
+        }
+        renderer.clear_below()?;
+
+        Ok(Self {
+            height,
+            cursor: Pos2::ZERO,
+            origin: origin.map(|y| y.min(rows - height)),
+        })
+    }
+
+    pub fn resize(
+        &mut self,
+        renderer: &mut impl Renderer,
+        height: i32,
+        rows: i32,
+    ) -> std::io::Result<()> {
+        if height > self.height {
+            self.move_to(renderer, pos2(0, self.height - 1))?;
+            for _ in self.height..height {
+                renderer.write('\n')?;
+            }
+            self.cursor = pos2(0, height - 1);
+            self.origin = self.origin.map(|y| y.min(rows - height));
+        } else if height < self.height {
+            self.move_to(renderer, pos2(0, height))?;
+            renderer.clear_below()?;
+        }
+
+        self.height = height;
+        Ok(())
+    }
+
+    pub fn clear(&mut self, renderer: &mut impl Renderer) -> std::io::Result<()> {
+        self.move_to(renderer, Pos2::ZERO)?;
+        renderer.clear_below()
+    }
+
+    // put the cursor on the line after the region so the last frame stays in the scrollback
+    pub fn leave(&mut self, renderer: &mut impl Renderer) -> std::io::Result<()> {
+        self.move_to(renderer, pos2(0, self.height - 1))?;
+        renderer.write('\n')
+    }
+
+    pub const fn origin(&self) -> Option<i32> {
+        self.origin
+    }
+
+    // mouse events are reported in screen coordinates
+    pub fn translate(&self, pos: Pos2) -> Option<Pos2> {
+        self.origin.map(|y| pos2(pos.x, pos.y - y))
+    }
+
+    fn move_to(&mut self, renderer: &mut impl Renderer, pos: Pos2) -> std::io::Result<()> {
+        match pos.y - self.cursor.y {
+            dy if dy < 0 => renderer.move_up(-dy)?,
+            dy if dy > 0 => renderer.move_down(dy)?,
+            _ => {}
+        }
+        renderer.move_to_column(pos.x)?;
+        self.cursor = pos;
+        Ok(())
+    }
+}
+
+pub struct InlineRenderer<'a, R> {
+    renderer: R,
+    inline: &'a mut Inline,
+}
+
+impl<'a, R: Renderer> InlineRenderer<'a, R> {
+    pub fn new(renderer: R, inline: &'a mut Inline) -> Self {
+        Self { renderer, inline }
+    }
+}
+
+impl<'a, R: Renderer> Renderer for InlineRenderer<'a, R> {
+    fn begin(&mut self) -> std::io::Result<()> {
+        self.renderer.begin()
+    }
+
+    fn end(&mut self) -> std::io::Result<()> {
+        self.renderer.end()
+    }
+
+    fn move_to(&mut self, pos: Pos2) -> std::io::Result<()> {
+        self.inline.move_to(&mut self.renderer, pos)
+    }
+
+    fn write(&mut self, ch: char) -> std::io::Result<()> {
+        self.inline.cursor.x += 1;
+        self.renderer.write(ch)
+    }
+
+    fn write_glyph(&mut self, glyph: &str, width: i32) -> std::io::Result<()> {
+        self.inline.cursor.x += width;
+        self.renderer.write_glyph(glyph, width)
+    }
+
+    fn erase_chars(&mut self, count: usize) -> std::io::Result<()> {
+        self.renderer.erase_chars(count)
+    }
+
+    fn erase_line(&mut self) -> std::io::Result<()> {
+        self.renderer.erase_line()
+    }
+
+    fn repeat(&mut self, glyph: &str, width: i32, count: usize) -> std::io::Result<()> {
+        self.inline.cursor.x += width * count as i32;
+        self.renderer.repeat(glyph, width, count)
+    }
+
+    fn color_depth(&self) -> ColorDepth {
+        self.renderer.color_depth()
+    }
+
+    fn set_fg(&mut self, rgb: Rgba) -> std::io::Result<()> {
+        self.renderer.set_fg(rgb)
+    }
+
+    fn set_bg(&mut self, rgb: Rgba) -> std::io::Result<()> {
+        self.renderer.set_bg(rgb)
+    }
+
+    fn set_attr(&mut self, attr: Attribute) -> std::io::Result<()> {
+        self.renderer.set_attr(attr)
+    }
+
+    fn reset_fg(&mut self) -> std::io::Result<()> {
+        self.renderer.reset_fg()
+    }
+
+    fn reset_bg(&mut self) -> std::io::Result<()> {
+        self.renderer.reset_bg()
+    }
+
+    fn reset_attr(&mut self) -> std::io::Result<()> {
+        self.renderer.reset_attr()
+    }
+
+    fn set_underline_color(&mut self, rgb: Rgba) -> std::io::Result<()> {
+        self.renderer.set_underline_color(rgb)
+    }
+
+    fn reset_underline_color(&mut self) -> std::io::Result<()> {
+        self.renderer.reset_underline_color()
+    }
+
+    fn start_link(&mut self, link: Link) -> std::io::Result<()> {
+        self.renderer.start_link(link)
+    }
+
+    fn end_link(&mut self) -> std::io::Result<()> {
+        self.renderer.end_link()
+    }
+
+    fn draw_image(&mut self, image: &Image, rect: Rect) -> std::io::Result<()> {
+        self.renderer.draw_image(image, rect)
+    }
+
+    fn remove_image(&mut self, image: &Image, rect: Rect) -> std::io::Result<()> {
+        self.renderer.remove_image(image, rect)
+    }
+
+    fn load_image(&mut self, image: &Image) -> std::io::Result<()> {
+        self.renderer.load_image(image)
+    }
+
+    fn unload_image(&mut self, image: &Image) -> std::io::Result<()> {
+        self.renderer.unload_image(image)
+    }
+
+    fn show_cursor(&mut self) -> std::io::Result<()> {
+        self.renderer.show_cursor()
+    }
+
+    fn hide_cursor(&mut self) -> std::io::Result<()> {
+        self.renderer.hide_cursor()
+    }
+
+    fn set_cursor_shape(&mut self, shape: CursorShape, blinking: bool) -> std::io::Result<()> {
+        self.renderer.set_cursor_shape(shape, blinking)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::paint::TermRenderer;
+
+    #[test]
+    fn reserve_clears_the_region() {
+        let mut out = Vec::new();
+        let inline = Inline::reserve(&mut TermRenderer::new(&mut out), 3, Some(5), 24).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "\x1b[1G\n\n\x1b[2A\x1b[J");
+        assert_eq!(inline.origin(), Some(5));
+    }
+
+    #[test]
+    fn reserve_at_the_bottom_moves_the_origin_up() {
+        let inline = Inline::reserve(&mut TermRenderer::new(Vec::new()), 3, Some(23), 24).unwrap();
+        assert_eq!(inline.origin(), Some(21));
+        assert_eq!(inline.translate(pos2(4, 22)), Some(pos2(4, 1)));
+    }
+}
@@ -1,99 +1,327 @@
 use crate::{
+    color::ColorDepth,
     geom::{rect, vec2, Rect, Vec2},
-    paint::{Renderer as _, Surface, TermRenderer},
+    paint::{CastWriter, Renderer as _, Screenshot, Surface, TermRenderer},
+};
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex, MutexGuard, Once, PoisonError,
+    },
+    time::Instant,
 };
-use std::io::{BufWriter, Write as _};
 
 pub mod event;
-use self::event::{read_next_event, Event, Key, Modifiers, MouseState};
+use self::event::{read_next_event, Event, EventReader, Key, KeyKind, Modifiers};
 
 mod config;
-pub use config::Config;
 use config::ShareableConfig;
+pub use config::{Config, InlineHeight};
+
+mod backend;
+pub use backend::{Backend, ChannelBackend, StreamBackend, TtyBackend};
+
+mod capabilities;
+pub use capabilities::Capabilities;
 
-pub struct Terminal {
+mod inline;
+use inline::{Inline, InlineRenderer};
+
+pub struct Terminal<B: Backend = TtyBackend> {
+    backend: B,
     config: ShareableConfig,
-    out: BufWriter<std::io::Stdout>,
-    mouse: MouseState,
+    out: FrameWriter<B::Writer>,
+    reader: Option<EventReader>,
     surface: Surface,
-    _guard: Guard,
+    size: Vec2,
+    inline: Option<Inline>,
+    color_depth: ColorDepth,
+    capabilities: Capabilities,
+    // which entry the panic hook restores this from
+    panic_hook: u64,
 }
 
-impl Terminal {
-    pub fn new(config: Config) -> std::io::Result<Self> {
-        let (rect, out, _guard, config) = Self::setup(config)?;
-        Self::install_panic_hook(config.clone());
+impl<B: Backend> Terminal<B> {
+    pub fn new(mut backend: B, mut config: Config) -> std::io::Result<Self> {
+        let size = backend.size()?;
+
+        let color_depth = config.color_depth.unwrap_or_else(ColorDepth::detect);
+
+        let mut out = FrameWriter::new(backend.writer()?);
+        if let Some(path) = &config.cast {
+            let file = BufWriter::new(File::create(path)?);
+            let term = std::env::var("TERM").ok();
+            let cast = CastWriter::new(file, size, term.as_deref(), color_depth)?;
+            out.cast = Some((cast, Instant::now()));
+        }
+        // the keyboard flags are only pushed once the terminal says it knows about them,
+        // and turning the option off here means they're only popped if they were pushed
+        let kitty_keyboard = std::mem::take(&mut config.kitty_keyboard);
+        Self::enter(&mut backend, &config, &mut out)?;
+        let capabilities = Capabilities::detect(&mut backend, config.query_timeout);
+
+        config.kitty_keyboard = kitty_keyboard && capabilities.supports_kitty_keyboard();
+        if config.kitty_keyboard {
+            TermRenderer::new(&mut out).push_keyboard_enhancement()?;
+            out.flush()?;
+        }
+
+        let config = ShareableConfig::from(config);
+        let panic_hook = Self::install_panic_hook(config.clone());
+
+        let mut surface = Surface::new(size);
+        surface.set_cursor_visible(!config.get(|c| c.hide_cursor));
 
-        Ok(Self {
+        let mut this = Self {
+            backend,
             config,
-            mouse: MouseState::default(),
-            surface: Surface::new(rect.size()),
-            out: BufWriter::with_capacity((rect.area() as usize * 21).next_power_of_two(), out),
-            _guard,
-        })
+            out,
+            reader: None,
+            surface,
+            size,
+            inline: None,
+            color_depth,
+            capabilities,
+            panic_hook,
+        };
+
+        if !this.is_in_alt_screen() {
+            this.start_inline()?;
+        }
+        Ok(this)
     }
 
+    // the rect the ui lays out in. when fitting the content this is the whole
+    // screen, and the viewport shrinks to whatever was used
     pub fn rect(&self) -> Rect {
-        self.surface.current().rect()
+        match &self.inline {
+            Some(inline) if !self.fits_content() => rect(vec2(self.size.x, inline.height)),
+            _ => rect(self.size),
+        }
     }
 
-    pub fn read_next_event(&mut self) -> Option<Event> {
-        let ev = read_next_event(&mut self.mouse)?;
+    pub fn listen(&mut self, wake: impl Fn() + Send + 'static) -> std::io::Result<()> {
+        if self.reader.is_none() {
+            let source = self.backend.events()?;
+            self.reader = Some(EventReader::spawn(source, wake));
+        }
+        Ok(())
+    }
 
-        match ev {
-            Event::Keyboard(Key::Char('c'), Modifiers::CTRL) => {
-                if self.config.get(|c| c.ctrl_c_quits) {
-                    return Some(Event::Quit);
+    pub fn read_next_event(&mut self) -> Option<Event> {
+        loop {
+            let ev = match read_next_event(self.reader.as_ref()?)? {
+                Event::Keyboard(Key::Char('c'), Modifiers::CTRL, KeyKind::Press)
+                    if self.config.get(|c| c.ctrl_c_quits) =>
+                {
+                    Event::Quit
                 }
-            }
-            Event::Resize(rect) => self.resize(rect.size()),
-            _ => {}
-        }
+                #[cfg(unix)]
+                Event::Keyboard(Key::Char('z'), Modifiers::CTRL, KeyKind::Press)
+                    if self.config.get(|c| c.ctrl_z_switches) =>
+                {
+                    match self.suspend() {
+                        Ok(()) => Event::Resize(self.rect()),
+                        Err(..) => Event::Quit,
+                    }
+                }
+                Event::Resize(rect) => match self.resize(rect.size()) {
+                    Ok(()) => Event::Resize(self.rect()),
+                    Err(..) => Event::Quit,
+                },
+                Event::Mouse(ev, pos, modifiers) if self.inline.is_some() => {
+                    let inline = self.inline.as_ref().unwrap();
+                    // the mouse is only captured inline once the origin is known, this is a stray
+                    let Some(pos) = inline.translate(pos) else {
+                        continue;
+                    };
+                    Event::Mouse(ev, pos, modifiers)
+                }
+                ev => ev,
+            };
 
-        Some(ev)
+            return Some(ev);
+        }
     }
 
     pub fn paint(&mut self, mut draw: impl FnMut(&mut Surface)) -> std::io::Result<()> {
         draw(&mut self.surface);
-        self.surface.render(&mut TermRenderer::new(&mut self.out))
+
+        let mut renderer = TermRenderer::new(&mut self.out)
+            .with_color_depth(self.color_depth)
+            .with_synchronized_output(self.capabilities.supports_synchronized_output())
+            .with_repeat(self.capabilities.supports_repeat())
+            .with_scrolling(self.capabilities.supports_scrolling())
+            .with_graphics(
+                self.capabilities.graphics(),
+                self.capabilities.cell_size.unwrap_or(vec2(10, 20)),
+            );
+        match &mut self.inline {
+            Some(inline) => self
+                .surface
+                .render(&mut InlineRenderer::new(renderer, inline)),
+            None => self.surface.render(&mut renderer),
+        }
     }
 
-    fn resize(&mut self, size: Vec2) {
-        let (out, _) = std::mem::replace(
-            &mut self.out,
-            BufWriter::with_capacity(0, std::io::stdout()),
-        )
-        .into_parts();
+    pub const fn color_depth(&self) -> ColorDepth {
+        self.color_depth
+    }
+
+    pub const fn capabilities(&self) -> &Capabilities {
+        &self.capabilities
+    }
+
+    pub fn fits_content(&self) -> bool {
+        self.inline.is_some()
+            && self
+                .config
+                .get(|c| c.inline_height == InlineHeight::Content)
+    }
+
+    pub fn fit_content(&mut self, height: i32) -> std::io::Result<()> {
+        if !self.fits_content() {
+            return Ok(());
+        }
 
-        let cap = size.x as usize * size.y as usize * 21;
-        self.out = BufWriter::with_capacity(cap.next_power_of_two(), out);
+        let Some(inline) = &mut self.inline else {
+            return Ok(());
+        };
+
+        let height = height.clamp(1, self.size.y.max(1));
+        if height == inline.height {
+            return Ok(());
+        }
+
+        inline.resize(&mut TermRenderer::new(&mut self.out), height, self.size.y)?;
+        self.out.flush()?;
+        self.resize_surface(vec2(self.size.x, height));
+        Ok(())
+    }
+
+    // the terminal can't be made any bigger than it is, so a larger size is cut down to it
+    pub(crate) fn resize_to(&mut self, size: Vec2) -> std::io::Result<Rect> {
+        let size = size.min(self.backend.size()?);
+        self.resize(size)?;
+        Ok(self.rect())
+    }
+
+    fn resize(&mut self, size: Vec2) -> std::io::Result<()> {
+        self.size = size;
+        if let Some((cast, start)) = &mut self.out.cast {
+            cast.resize(start.elapsed().as_secs_f32(), size)?;
+        }
+
+        let Some(inline) = &mut self.inline else {
+            self.resize_surface(size);
+            return Ok(());
+        };
+
+        // the terminal may have reflowed the region, so start over from its top
+        let height = match self.config.get(|c| c.inline_height) {
+            InlineHeight::Fixed(height) => height as i32,
+            InlineHeight::Content => inline.height,
+        }
+        .clamp(1, size.y.max(1));
+
+        let mut renderer = TermRenderer::new(&mut self.out);
+        inline.clear(&mut renderer)?;
+        *inline = Inline::reserve(&mut renderer, height, inline.origin(), size.y)?;
+        self.out.flush()?;
+
+        self.resize_surface(vec2(size.x, height));
+        Ok(())
+    }
+
+    fn resize_surface(&mut self, size: Vec2) {
+        self.out.reserve(size.x as usize * size.y as usize * 21);
         self.surface.resize(size)
     }
+
+    fn start_inline(&mut self) -> std::io::Result<()> {
+        let height = Inline::initial_height(self.config.get(|c| c.inline_height), self.size.y);
+
+        // we only need to know where we are on the screen to make sense of the mouse.
+        // this also runs on resume, when the input thread would swallow the answer
+        let mouse_capture = self.config.get(|c| c.mouse_capture);
+        let origin = if mouse_capture {
+            let _paused = self.reader.as_ref().map(EventReader::pause);
+            self.backend.cursor_row()
+        } else {
+            None
+        };
+
+        let mut renderer = TermRenderer::new(&mut self.out);
+        let inline = Inline::reserve(&mut renderer, height, origin, self.size.y)?;
+        // without an origin there is no telling what was clicked, so the terminal keeps the mouse
+        match (mouse_capture, origin) {
+            (true, Some(..)) => renderer.capture_mouse()?,
+            (true, None) => renderer.release_mouse()?,
+            _ => {}
+        }
+        self.out.flush()?;
+
+        self.inline = Some(inline);
+        self.resize_surface(vec2(self.size.x, height));
+        Ok(())
+    }
+
+    fn finish_inline(&mut self, keep: bool) -> std::io::Result<()> {
+        let Some(mut inline) = self.inline.take() else {
+            return Ok(());
+        };
+
+        let mut renderer = TermRenderer::new(&mut self.out);
+        if keep {
+            inline.leave(&mut renderer)?;
+        } else {
+            inline.clear(&mut renderer)?;
+        }
+        self.out.flush()
+    }
 }
 
-impl Terminal {
+impl<B: Backend> Terminal<B> {
     pub fn is_in_alt_screen(&self) -> bool {
         self.config.get(|c| c.use_alt_screen)
     }
 
-    pub fn set_title(&self, title: impl AsRef<str>) -> std::io::Result<()> {
+    pub fn screenshot(&self, path: impl AsRef<std::path::Path>) -> std::io::Result<()> {
+        Screenshot::new(self.surface.screen()).save(path)
+    }
+
+    pub fn set_title(&mut self, title: impl AsRef<str>) -> std::io::Result<()> {
         self.immediate(|mut p| p.set_title(title.as_ref()))
     }
 
-    pub fn enter_alt_screen(&self) -> std::io::Result<()> {
+    pub fn set_clipboard(&mut self, data: impl AsRef<str>) -> std::io::Result<()> {
+        self.immediate(|mut p| p.set_clipboard(data.as_ref()))
+    }
+
+    pub fn request_clipboard(&mut self) -> std::io::Result<()> {
+        self.immediate(|mut p| p.request_clipboard())
+    }
+
+    pub fn enter_alt_screen(&mut self) -> std::io::Result<()> {
         if self.is_in_alt_screen() {
             return Ok(());
         }
 
+        self.finish_inline(false)?;
         self.config.mutate(|c| c.use_alt_screen = true);
         self.immediate(|mut p| {
             p.enter_alt_screen()?;
             p.disable_line_wrap()?;
             p.capture_mouse()
-        })
+        })?;
+
+        self.resize_surface(self.size);
+        Ok(())
     }
 
-    pub fn leave_alt_screen(&self) -> std::io::Result<()> {
+    pub fn leave_alt_screen(&mut self) -> std::io::Result<()> {
         if !self.is_in_alt_screen() {
             return Ok(());
         }
@@ -103,86 +331,328 @@ impl Terminal {
             p.leave_alt_screen()?;
             p.release_mouse()?;
             p.enable_line_wrap()
-        })
+        })?;
+
+        self.start_inline()
     }
 
-    fn immediate<F>(&self, apply: F) -> std::io::Result<()>
+    fn immediate<F>(&mut self, apply: F) -> std::io::Result<()>
     where
-        F: Fn(TermRenderer<&std::io::Stdout>) -> std::io::Result<()>,
+        F: Fn(TermRenderer<&mut FrameWriter<B::Writer>>) -> std::io::Result<()>,
     {
-        let mut out = self.out.get_ref();
-        apply(TermRenderer::new(out))?;
-        out.flush()
+        apply(TermRenderer::new(&mut self.out))?;
+        self.out.flush()
     }
 }
 
-impl Terminal {
-    fn setup(config: Config) -> std::io::Result<(Rect, std::io::Stdout, Guard, ShareableConfig)> {
-        use crossterm::terminal;
-
-        let mut out = std::io::stdout();
-
-        // TOOD get rid of these and we get rid of crossterm from here
-        terminal::enable_raw_mode()?;
+impl<B: Backend> Terminal<B> {
+    fn enter(backend: &mut B, config: &Config, out: &mut impl Write) -> std::io::Result<()> {
+        backend.enable_raw_mode()?;
 
+        let mut p = TermRenderer::new(&mut *out);
         if config.use_alt_screen {
-            crossterm::execute!(&mut out, crossterm::terminal::EnterAlternateScreen)?;
-            crossterm::execute!(&mut out, crossterm::terminal::DisableLineWrap)?;
+            p.enter_alt_screen()?;
+            p.disable_line_wrap()?;
         }
 
         if config.hide_cursor {
-            crossterm::execute!(&mut out, crossterm::cursor::Hide)?;
+            p.hide_cursor()?;
         }
 
         if config.mouse_capture {
-            crossterm::execute!(&mut out, crossterm::event::EnableMouseCapture)?;
+            p.capture_mouse()?;
         }
 
-        out.flush()?;
+        if config.bracketed_paste {
+            p.enable_bracketed_paste()?;
+        }
+        p.enable_focus_reporting()?;
 
-        let config = ShareableConfig::from(config);
-        // TOOD get rid of these and we get rid of crossterm from here
-        let size = terminal::size().map(|(w, h)| vec2(w as _, h as _))?;
+        if config.kitty_keyboard {
+            p.push_keyboard_enhancement()?;
+        }
+
+        out.flush()
+    }
 
-        Ok((rect(size), out, Guard(config.clone()), config))
+    fn reset(&mut self) -> std::io::Result<()> {
+        Self::write_reset(&self.config.get(Config::clone), &mut self.out)?;
+        self.backend.disable_raw_mode()
     }
 
-    fn reset(config: ShareableConfig) -> std::io::Result<()> {
-        use crossterm::terminal;
-        let mut out = std::io::stdout();
+    pub(crate) fn write_reset(config: &Config, out: &mut impl Write) -> std::io::Result<()> {
+        let mut p = TermRenderer::new(&mut *out);
+        if config.use_alt_screen {
+            p.clear_screen()?;
+            p.leave_alt_screen()?;
+            p.enable_line_wrap()?;
+        }
 
-        if config.get(|c| c.use_alt_screen) {
-            crossterm::execute!(
-                &mut out,
-                crossterm::terminal::Clear(crossterm::terminal::ClearType::All)
-            )?;
-            crossterm::execute!(&mut out, crossterm::terminal::LeaveAlternateScreen)?;
-            crossterm::execute!(&mut out, crossterm::terminal::EnableLineWrap)?;
+        if config.mouse_capture {
+            p.release_mouse()?;
         }
 
-        if config.get(|c| c.mouse_capture) {
-            crossterm::execute!(&mut out, crossterm::event::DisableMouseCapture)?;
+        if config.bracketed_paste {
+            p.disable_bracketed_paste()?;
         }
-        crossterm::execute!(&mut out, crossterm::cursor::Show)?;
+        p.disable_focus_reporting()?;
 
-        out.flush()?;
-        // TODO get rid of this
-        terminal::disable_raw_mode()
+        if config.kitty_keyboard {
+            p.pop_keyboard_enhancement()?;
+        }
+        p.reset_cursor_shape()?;
+        p.show_cursor()?;
+
+        out.flush()
     }
 
-    fn install_panic_hook(config: ShareableConfig) {
-        let old = std::panic::take_hook();
-        std::panic::set_hook(Box::new(move |info| {
-            let _ = Self::reset(config.clone());
-            old(info)
-        }));
+    // gives the terminal back to the shell and stops the process, like any other program would on ctrl-z
+    #[cfg(unix)]
+    fn suspend(&mut self) -> std::io::Result<()> {
+        self.finish_inline(false)?;
+        self.reset()?;
+
+        // this blocks until the shell sends us SIGCONT
+        if unsafe { libc::raise(libc::SIGTSTP) } != 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+
+        Self::enter(
+            &mut self.backend,
+            &self.config.get(Config::clone),
+            &mut self.out,
+        )?;
+        // resetting showed the cursor, and entering again only hid it if the config says to
+        self.surface
+            .set_cursor_visible(!self.config.get(|c| c.hide_cursor));
+
+        self.size = self.backend.size()?;
+        if self.is_in_alt_screen() {
+            // the front buffer no longer matches what is on the screen
+            self.resize_surface(self.size);
+            return Ok(());
+        }
+        self.start_inline()
+    }
+
+    // the hook is only installed once, each terminal adds how it's restored to what it runs
+    fn install_panic_hook(config: ShareableConfig) -> u64 {
+        static HOOK: Once = Once::new();
+        static NEXT: AtomicU64 = AtomicU64::new(0);
+
+        HOOK.call_once(|| {
+            let old = std::panic::take_hook();
+            std::panic::set_hook(Box::new(move |info| {
+                // a panic while the list is held can't wait on it
+                if let Ok(restores) = PANIC_RESTORES.try_lock() {
+                    for (_, restore) in restores.iter() {
+                        restore()
+                    }
+                }
+                old(info)
+            }));
+        });
+
+        let id = NEXT.fetch_add(1, Ordering::Relaxed);
+        let restore = move || B::restore_after_panic(&config.get(Config::clone));
+        lock_restores().push((id, Box::new(restore)));
+        id
     }
 }
 
-struct Guard(ShareableConfig);
+type Restore = Box<dyn Fn() + Send>;
+
+// how to restore every terminal that's still around
+static PANIC_RESTORES: Mutex<Vec<(u64, Restore)>> = Mutex::new(Vec::new());
+
+fn lock_restores() -> MutexGuard<'static, Vec<(u64, Restore)>> {
+    PANIC_RESTORES
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+}
 
-impl Drop for Guard {
+impl<B: Backend> Drop for Terminal<B> {
     fn drop(&mut self) {
-        let _ = Terminal::reset(self.0.clone());
+        let _ = self.finish_inline(self.config.get(|c| c.keep_final_frame));
+        let _ = self.reset();
+        lock_restores().retain(|&(id, _)| id != self.panic_hook);
+    }
+}
+
+// frames are written out all at once, no matter how large they get
+struct FrameWriter<W> {
+    buf: Vec<u8>,
+    out: W,
+    // a copy of everything written, with when it was written
+    cast: Option<(CastWriter<BufWriter<File>>, Instant)>,
+}
+
+impl<W> FrameWriter<W> {
+    const fn new(out: W) -> Self {
+        Self {
+            buf: Vec::new(),
+            out,
+            cast: None,
+        }
+    }
+
+    fn reserve(&mut self, capacity: usize) {
+        self.buf.reserve(capacity.saturating_sub(self.buf.len()))
+    }
+}
+
+impl<W: Write> Write for FrameWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.buf.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        if let Some((cast, start)) = &mut self.cast {
+            cast.output(start.elapsed().as_secs_f32(), &self.buf)?;
+        }
+        self.out.write_all(&self.buf)?;
+        self.buf.clear();
+        self.out.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{mpsc::Receiver, Arc, Mutex};
+
+    use super::*;
+
+    #[derive(Clone, Default)]
+    struct Shared(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    // a terminal that answers the startup query with `reply`
+    struct Replying {
+        out: Shared,
+        reply: &'static [u8],
+    }
+
+    impl Backend for Replying {
+        type Writer = Shared;
+        type Events = Receiver<Event>;
+
+        fn size(&mut self) -> std::io::Result<Vec2> {
+            Ok(vec2(10, 4))
+        }
+
+        fn enable_raw_mode(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+
+        fn disable_raw_mode(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+
+        fn writer(&mut self) -> std::io::Result<Self::Writer> {
+            Ok(self.out.clone())
+        }
+
+        fn events(&mut self) -> std::io::Result<Self::Events> {
+            Err(std::io::ErrorKind::Unsupported.into())
+        }
+
+        fn query(
+            &mut self,
+            _: &[u8],
+            _: std::time::Duration,
+            _: fn(&[u8]) -> bool,
+        ) -> Option<Vec<u8>> {
+            Some(self.reply.to_vec())
+        }
+    }
+
+    fn session(reply: &'static [u8]) -> String {
+        let out = Shared::default();
+        let backend = Replying {
+            out: out.clone(),
+            reply,
+        };
+        drop(Terminal::new(backend, Config::default()).unwrap());
+        let out = out.0.lock().unwrap();
+        String::from_utf8_lossy(&out).into_owned()
+    }
+
+    #[test]
+    fn kitty_keyboard_is_pushed_when_supported() {
+        let out = session(b"\x1b[?0u\x1b[?62;4c");
+        assert_eq!(out.matches("\x1b[>15u").count(), 1, "{out:?}");
+        assert_eq!(out.matches("\x1b[<u").count(), 1, "{out:?}");
+    }
+
+    #[test]
+    fn kitty_keyboard_is_left_alone_when_unsupported() {
+        let out = session(b"\x1b[?62;4c");
+        assert!(!out.contains("\x1b[>15u"), "{out:?}");
+        assert!(!out.contains("\x1b[<u"), "{out:?}");
+    }
+
+    static RESTORED: AtomicU64 = AtomicU64::new(0);
+
+    // counts how often a panic restored it
+    struct Restoring;
+
+    impl Backend for Restoring {
+        type Writer = Vec<u8>;
+        type Events = Receiver<Event>;
+
+        fn size(&mut self) -> std::io::Result<Vec2> {
+            Ok(vec2(10, 4))
+        }
+
+        fn enable_raw_mode(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+
+        fn disable_raw_mode(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+
+        fn writer(&mut self) -> std::io::Result<Self::Writer> {
+            Ok(Vec::new())
+        }
+
+        fn events(&mut self) -> std::io::Result<Self::Events> {
+            Err(std::io::ErrorKind::Unsupported.into())
+        }
+
+        fn restore_after_panic(_: &Config) {
+            RESTORED.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn a_panic_restores_each_live_terminal_once() {
+        let config = || Config::default().query_timeout(std::time::Duration::ZERO);
+        for _ in 0..3 {
+            drop(Terminal::new(Restoring, config()).unwrap());
+        }
+        let terminal = Terminal::new(Restoring, config()).unwrap();
+
+        let panic = || {
+            let _ = std::panic::catch_unwind(|| panic!("on purpose"));
+        };
+
+        panic();
+        assert_eq!(RESTORED.swap(0, Ordering::SeqCst), 1);
+
+        drop(terminal);
+        panic();
+        assert_eq!(RESTORED.load(Ordering::SeqCst), 0);
     }
 }
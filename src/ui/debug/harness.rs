@@ -0,0 +1,522 @@
+use std::{collections::VecDeque, io::Write, path::PathBuf, time::Duration};
+
+use unicode_width::UnicodeWidthStr as _;
+
+use crate::{
+    color::ColorDepth,
+    debug_fmt::short_name,
+    geom::{pos2, rect, Pos2, Rect, Vec2},
+    input::{Key, KeyKind, Keybind, Modifiers, MouseButton},
+    node::WidgetId,
+    paint::{
+        Attribute, Buffer, CastRenderer, CastWriter, CellAttr, DebugRenderer, RenderStats,
+        Screenshot, StatsRenderer, Surface,
+    },
+    terminal::event::{EntryKind, Event, MouseEvent, MouseState, Recording, TemporalEvent},
+    ui::{Command, Ui},
+    Capabilities,
+};
+
+pub struct Harness {
+    ui: Ui,
+    surface: Surface,
+    mouse: MouseState,
+    pending: VecDeque<Event>,
+    frame_time: Duration,
+    elapsed: Duration,
+    title: Option<String>,
+    clipboard: Option<String>,
+    cast: Option<CastWriter<Box<dyn Write>>>,
+    color_depth: ColorDepth,
+    screenshots: Vec<PathBuf>,
+}
+
+impl Harness {
+    pub fn new(size: Vec2) -> Self {
+        let surface = Surface::new(size);
+        Self {
+            ui: Ui::new(surface.rect()),
+            surface,
+            mouse: MouseState::default(),
+            pending: VecDeque::new(),
+            frame_time: Duration::from_secs_f32(1.0 / 60.0),
+            elapsed: Duration::ZERO,
+            title: None,
+            clipboard: None,
+            cast: None,
+            color_depth: ColorDepth::TrueColor,
+            screenshots: Vec::new(),
+        }
+    }
+
+    pub fn frame_time(mut self, frame_time: Duration) -> Self {
+        self.frame_time = frame_time;
+        self
+    }
+
+    // writes every rendered frame to an asciinema cast, timed by the frame time.
+    // the header records the color depth, so set that first
+    pub fn cast(mut self, out: impl Write + 'static) -> std::io::Result<Self> {
+        let out = Box::new(out) as Box<dyn Write>;
+        let cast = CastWriter::new(out, self.size(), None, self.color_depth)?;
+        self.cast = Some(cast);
+        Ok(self)
+    }
+
+    // pretends the terminal answered with these, e.g. a light background
+    pub fn capabilities(self, capabilities: Capabilities) -> Self {
+        self.ui.set_capabilities(capabilities);
+        self
+    }
+
+    // lets widgets be checked the way they look without colors
+    pub fn monochrome(self, monochrome: bool) -> Self {
+        let depth = match monochrome {
+            true => ColorDepth::Monochrome,
+            false => ColorDepth::TrueColor,
+        };
+        self.color_depth(depth)
+    }
+
+    // the render stats are counted as if the terminal had this many colors
+    pub fn color_depth(mut self, color_depth: ColorDepth) -> Self {
+        self.ui
+            .set_monochrome(color_depth == ColorDepth::Monochrome);
+        self.color_depth = color_depth;
+        self
+    }
+
+    pub fn ui(&self) -> &Ui {
+        &self.ui
+    }
+
+    pub fn size(&self) -> Vec2 {
+        self.surface.rect().size()
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    pub fn title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    pub fn clipboard(&self) -> Option<&str> {
+        self.clipboard.as_deref()
+    }
+
+    pub fn quit(&self) -> bool {
+        self.ui.quit()
+    }
+}
+
+impl Harness {
+    pub fn event(&mut self, event: Event) -> &mut Self {
+        self.pending.push_back(event);
+        self
+    }
+
+    pub fn key(&mut self, keybind: impl Into<Keybind>) -> &mut Self {
+        self.key_kind(keybind, KeyKind::Press)
+    }
+
+    pub fn key_release(&mut self, keybind: impl Into<Keybind>) -> &mut Self {
+        self.key_kind(keybind, KeyKind::Release)
+    }
+
+    pub fn key_kind(&mut self, keybind: impl Into<Keybind>, kind: KeyKind) -> &mut Self {
+        let Keybind { key, modifiers } = keybind.into();
+        self.event(Event::Keyboard(key, modifiers, kind))
+    }
+
+    pub fn type_str(&mut self, input: &str) -> &mut Self {
+        for ch in input.chars() {
+            // the same keys a terminal would send for this
+            let keybind = match ch {
+                '\n' => Keybind::key(Key::Enter),
+                '\t' => Keybind::key(Key::Tab),
+                ch if ch.is_uppercase() => Keybind::key(Key::Char(ch)).shift(),
+                ch => Keybind::key(Key::Char(ch)),
+            };
+            self.key(keybind);
+        }
+        self
+    }
+
+    pub fn paste(&mut self, data: impl ToString) -> &mut Self {
+        self.event(Event::Paste(data.to_string()))
+    }
+
+    pub fn focus(&mut self, focused: bool) -> &mut Self {
+        self.event(if focused {
+            Event::FocusGained
+        } else {
+            Event::FocusLost
+        })
+    }
+
+    pub fn move_to(&mut self, pos: Pos2) -> &mut Self {
+        self.event(Event::Mouse(MouseEvent::Move, pos, Modifiers::NONE))
+    }
+
+    pub fn click(&mut self, pos: Pos2) -> &mut Self {
+        self.click_button(pos, MouseButton::Primary)
+    }
+
+    pub fn click_button(&mut self, pos: Pos2, button: MouseButton) -> &mut Self {
+        self.move_to(pos);
+        self.mouse(TemporalEvent::Down(pos, button));
+        self.mouse(TemporalEvent::Up(pos, button))
+    }
+
+    pub fn drag(&mut self, from: Pos2, to: Pos2) -> &mut Self {
+        self.drag_button(from, to, MouseButton::Primary)
+    }
+
+    // this walks the mouse one cell at a time, like a terminal would report it
+    pub fn drag_button(&mut self, from: Pos2, to: Pos2, button: MouseButton) -> &mut Self {
+        self.move_to(from);
+        self.mouse(TemporalEvent::Down(from, button));
+
+        let mut pos = from;
+        while pos != to {
+            pos.x += (to.x - pos.x).signum();
+            pos.y += (to.y - pos.y).signum();
+            self.mouse(TemporalEvent::Drag(pos, button));
+        }
+
+        self.mouse(TemporalEvent::Up(to, button))
+    }
+
+    pub fn scroll(&mut self, pos: Pos2, delta: Vec2) -> &mut Self {
+        self.move_to(pos);
+        self.event(Event::Mouse(
+            MouseEvent::Scroll { delta },
+            pos,
+            Modifiers::NONE,
+        ))
+    }
+
+    pub fn resize(&mut self, size: Vec2) -> &mut Self {
+        self.event(Event::Resize(rect(size)))
+    }
+
+    fn mouse(&mut self, ev: TemporalEvent) -> &mut Self {
+        let pos = match ev {
+            TemporalEvent::Down(pos, ..)
+            | TemporalEvent::Up(pos, ..)
+            | TemporalEvent::Drag(pos, ..) => pos,
+        };
+
+        if let Some(ev) = self.mouse.update(ev) {
+            self.event(Event::Mouse(ev, pos, Modifiers::NONE));
+        }
+        self
+    }
+}
+
+impl Harness {
+    // panics if the cast or a screenshot can't be written, `try_frame` returns that instead
+    pub fn frame(&mut self, app: impl FnMut(&Ui)) -> Snapshot {
+        self.try_frame(app)
+            .expect("cannot write the cast or a screenshot")
+    }
+
+    // the frame is finished even if writing failed, so the harness can keep going
+    pub fn try_frame(&mut self, app: impl FnMut(&Ui)) -> std::io::Result<Snapshot> {
+        let time = self.elapsed + self.frame_time;
+        self.frame_at(time.as_secs_f32(), app)
+    }
+
+    pub fn frames(&mut self, frames: usize, mut app: impl FnMut(&Ui)) -> Snapshot {
+        assert!(frames > 0, "must run atleast one frame");
+        for _ in 1..frames {
+            self.frame(&mut app);
+        }
+        self.frame(app)
+    }
+
+    // feeds a recorded session through the ui, ticking it with the recorded times.
+    //
+    // this returns the last frame, if there was one
+    pub fn replay(&mut self, recording: &Recording, mut app: impl FnMut(&Ui)) -> Option<Snapshot> {
+        let mut last = None;
+        for entry in recording.entries() {
+            match &entry.kind {
+                EntryKind::Event(ev) => self.handle(ev),
+                EntryKind::Tick => {
+                    let snapshot = self.frame_at(entry.time, &mut app);
+                    last = Some(snapshot.expect("cannot write the cast or a screenshot"))
+                }
+            }
+        }
+        last
+    }
+
+    fn frame_at(&mut self, time: f32, mut app: impl FnMut(&Ui)) -> std::io::Result<Snapshot> {
+        for ev in std::mem::take(&mut self.pending) {
+            self.handle(&ev);
+        }
+
+        self.ui
+            .scope(|| app(&self.ui))
+            .expect("scope is infallible for the harness");
+        self.handle_commands();
+
+        self.ui.paint(&mut self.surface);
+
+        let snapshot = Snapshot {
+            cursor: self.surface.cursor().map(|cursor| cursor.pos),
+            ..Snapshot::new(&self.ui, self.surface.current())
+        };
+
+        // the stats go first so colors are quantized the way the terminal would be sent them
+        let capabilities = self.ui.inner.capabilities.borrow().clone();
+        let stats = StatsRenderer::default()
+            .with_color_depth(self.color_depth)
+            .with_repeat(capabilities.supports_repeat())
+            .with_scrolling(capabilities.supports_scrolling());
+        let mut debug = (stats, DebugRenderer::default());
+        let mut result = Ok(());
+        match &mut self.cast {
+            Some(cast) => {
+                let mut both = (debug, CastRenderer::new(cast, self.elapsed.as_secs_f32()));
+                result = self.surface.render(&mut both);
+                debug = both.0;
+            }
+            None => self
+                .surface
+                .render(&mut debug)
+                .expect("debug renderer is infallible"),
+        }
+
+        for path in std::mem::take(&mut self.screenshots) {
+            let saved = Screenshot::new(self.surface.screen()).save(path);
+            result = result.and(saved);
+        }
+
+        self.elapsed = Duration::from_secs_f32(time);
+        self.ui.tick(time);
+
+        result.map(|()| Snapshot {
+            render: debug.1.out,
+            stats: debug.0.stats(),
+            ..snapshot
+        })
+    }
+
+    fn handle(&mut self, ev: &Event) {
+        match ev {
+            Event::Quit => self.ui.set_quit(),
+            Event::Resize(rect) => {
+                self.surface.resize(rect.size());
+                if let Some(cast) = &mut self.cast {
+                    let _ = cast.resize(self.elapsed.as_secs_f32(), rect.size());
+                }
+            }
+            _ => {}
+        }
+        self.ui.handle_event(ev);
+    }
+
+    fn handle_commands(&mut self) {
+        for cmd in self.ui.inner.commands.borrow_mut().drain(..) {
+            match cmd {
+                Command::SetTitle(title) => self.title = Some(title),
+                Command::Screenshot(path) => self.screenshots.push(path),
+                Command::SetClipboard(data) => self.clipboard = Some(data),
+                // behaves like a terminal that answers osc 52 queries
+                Command::RequestClipboard => {
+                    if let Some(data) = &self.clipboard {
+                        self.pending.push_back(Event::Paste(data.clone()))
+                    }
+                }
+                Command::Quit => self.ui.set_quit(),
+                Command::LeaveAltScreen | Command::EnterAltScreen => {}
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct WidgetRect {
+    pub id: WidgetId,
+    pub name: String,
+    pub rect: Rect,
+}
+
+#[derive(Clone, Debug)]
+pub struct Snapshot {
+    pub frame: u64,
+    pub size: Vec2,
+    pub text: String,
+    pub render: String,
+    // what the render would have cost a real terminal
+    pub stats: RenderStats,
+    // where a widget asked for the terminal's cursor
+    pub cursor: Option<Pos2>,
+    pub rects: Vec<WidgetRect>,
+    attrs: Vec<Attribute>,
+}
+
+impl Snapshot {
+    fn new(ui: &Ui, buffer: &Buffer) -> Self {
+        let size = buffer.rect().size();
+        let mut text = String::with_capacity((size.x as usize + 1) * size.y as usize);
+        let mut attrs = Vec::with_capacity(size.x as usize * size.y as usize);
+        for y in 0..size.y {
+            if y > 0 {
+                text.push('\n');
+            }
+            for x in 0..size.x {
+                // wide glyphs already cover the continuation after them
+                let cell = buffer.get(pos2(x, y));
+                match cell {
+                    Some(cell) => cell.glyph.with_str(|s| text.push_str(s)),
+                    None => text.push(' '),
+                }
+                attrs.push(match cell.map(|cell| cell.attr) {
+                    Some(CellAttr::Attr(attr)) => attr,
+                    _ => Attribute::RESET,
+                });
+            }
+        }
+
+        let nodes = ui.nodes();
+        let computed = ui.computed();
+
+        let mut rects = vec![];
+        let mut queue = VecDeque::from_iter([ui.root()]);
+        while let Some(id) = queue.pop_front() {
+            let Some(node) = nodes.get(id) else { continue };
+            if let Some(layout) = computed.get(id) {
+                rects.push(WidgetRect {
+                    id,
+                    name: short_name(node.widget.type_name()),
+                    rect: layout.rect,
+                });
+            }
+            queue.extend(node.children());
+        }
+
+        Self {
+            frame: ui.current_frame(),
+            size,
+            text,
+            render: String::new(),
+            stats: RenderStats::default(),
+            cursor: None,
+            rects,
+            attrs,
+        }
+    }
+
+    pub fn lines(&self) -> impl Iterator<Item = &str> + '_ {
+        self.text.lines()
+    }
+
+    pub fn line(&self, y: usize) -> Option<&str> {
+        self.lines().nth(y)
+    }
+
+    pub fn find(&self, needle: &str) -> Option<Pos2> {
+        self.lines().enumerate().find_map(|(y, line)| {
+            let index = line.find(needle)?;
+            Some(pos2(line[..index].width() as i32, y as i32))
+        })
+    }
+
+    pub fn contains(&self, needle: &str) -> bool {
+        self.find(needle).is_some()
+    }
+
+    // the attributes of the cell, which is all there is to go on without colors
+    pub fn attr(&self, pos: Pos2) -> Attribute {
+        if !rect(self.size).contains(pos) {
+            return Attribute::RESET;
+        }
+        self.attrs[(pos.y * self.size.x + pos.x) as usize]
+    }
+
+    pub fn rect(&self, id: WidgetId) -> Option<Rect> {
+        self.rects.iter().find(|w| w.id == id).map(|w| w.rect)
+    }
+
+    pub fn widgets_named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a WidgetRect> {
+        self.rects.iter().filter(move |w| w.name == name)
+    }
+}
+
+impl std::fmt::Display for Snapshot {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        geom::vec2,
+        widgets::{button, column, label},
+    };
+
+    #[test]
+    fn snapshot_of_a_click() {
+        let mut harness = Harness::new(vec2(10, 2));
+        let mut clicks = 0;
+        let mut app = |_: &Ui| {
+            column(|| {
+                label(format!("clicks: {clicks}"));
+                if button("hit").clicked {
+                    clicks += 1;
+                }
+            });
+        };
+
+        let snapshot = harness.frame(&mut app);
+        assert_eq!(snapshot.to_string(), "clicks: 0 \n  hit     ");
+        let pos = snapshot.find("hit").unwrap();
+        assert_eq!(pos, pos2(2, 1));
+
+        harness.click(pos);
+        harness.frame(&mut app);
+        let snapshot = harness.frame(&mut app);
+        assert_eq!(snapshot.line(0), Some("clicks: 1 "));
+    }
+
+    #[test]
+    fn a_failed_screenshot_is_returned() {
+        let mut harness = Harness::new(vec2(4, 1));
+        let missing = std::env::temp_dir().join("dearsum-missing-dir/shot.html");
+
+        let err = harness.try_frame(|ui| {
+            label("hi");
+            ui.command(Command::Screenshot(missing.clone()));
+        });
+        assert!(err.is_err());
+
+        // the frame still happened, and the next one is fine
+        assert_eq!(harness.elapsed(), harness.frame_time);
+        let snapshot = harness.try_frame(|_| _ = label("ok")).unwrap();
+        assert_eq!(snapshot.text, "ok  ");
+    }
+
+    #[test]
+    fn typed_capitals_are_shifted() {
+        let mut harness = Harness::new(vec2(4, 1));
+        harness.type_str("Hi!\n");
+
+        let press = |key, modifiers| Event::Keyboard(key, modifiers, KeyKind::Press);
+        assert_eq!(
+            Vec::from(harness.pending.clone()),
+            [
+                press(Key::Char('H'), Modifiers::SHIFT),
+                press(Key::Char('i'), Modifiers::NONE),
+                press(Key::Char('!'), Modifiers::NONE),
+                press(Key::Enter, Modifiers::NONE),
+            ]
+        );
+    }
+}
@@ -2,12 +2,15 @@ use crate::{
     debug_fmt::short_name,
     geom::{vec2, Align, Rect},
     node::{LayoutNode, Node, WidgetId},
-    paint::{DebugRenderer, Surface},
+    paint::{DebugRenderer, Screenshot, Surface},
     ui::Ui,
 };
 
 use slotmap::{SecondaryMap, SlotMap};
 
+mod harness;
+pub use harness::{Harness, Snapshot, WidgetRect};
+
 #[derive(Default, Clone)]
 pub struct DebugNode {
     pub id: WidgetId,
@@ -201,14 +204,11 @@ impl DebugNode {
                 let left = origin.x + self.center.saturating_sub((self.width - 1) / 2);
                 let right = left + self.width;
 
-                for x in left + 1..right - 1 {
-                    buffer[origin.y][x] = '─';
-                    buffer[origin.y + self.height - 1][x] = '─';
-                }
-                #[allow(clippy::needless_range_loop)]
-                for y in origin.y + 1..origin.y + self.height - 1 {
-                    buffer[y][left] = '│';
-                    buffer[y][right - 1] = '│';
+                buffer[origin.y][left + 1..right - 1].fill('─');
+                buffer[origin.y + self.height - 1][left + 1..right - 1].fill('─');
+                for row in &mut buffer[origin.y + 1..origin.y + self.height - 1] {
+                    row[left] = '│';
+                    row[right - 1] = '│';
                 }
 
                 // ╭
@@ -297,13 +297,13 @@ impl DebugNode {
                     let end =
                         child_origin.x + child.total_width + spacing + self.children[id + 1].center;
 
-                    for x in start..end {
-                        let arrow = if x != origin.x + self.center {
+                    let row = &mut buffer[origin.y + self.height][start..end];
+                    for (x, cell) in (start..).zip(row) {
+                        *cell = if x != origin.x + self.center {
                             '╌' // '─'
                         } else {
                             '┴'
                         };
-                        buffer[origin.y + self.height][x] = arrow
                     }
 
                     if id == 0 {
@@ -387,6 +387,8 @@ pub struct DebugOutput {
     pub nodes: String,
     pub layout: String,
     pub render: String,
+    pub svg: String,
+    pub html: String,
     pub node: DebugNode,
 }
 
@@ -405,6 +407,9 @@ pub fn debug_app<R>(mut app: impl FnMut(&Ui) -> R) -> DebugOutput {
     ui.scope(|| app(&ui)).unwrap();
     ui.paint(&mut surface);
 
+    let screenshot = Screenshot::new(surface.current());
+    let (svg, html) = (screenshot.svg(), screenshot.html());
+
     let mut debug = DebugRenderer::default();
     surface.render(&mut debug).unwrap();
 
@@ -413,6 +418,8 @@ pub fn debug_app<R>(mut app: impl FnMut(&Ui) -> R) -> DebugOutput {
         nodes: format!("{:#?}", slot_map(&ui.nodes())),
         layout: format!("{:#?}", secondary_map(&ui.computed())),
         render: debug.out,
+        svg,
+        html,
         node: DebugNode::build(&ui),
     }
 }
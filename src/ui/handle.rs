@@ -0,0 +1,182 @@
+use std::{
+    any::{Any, TypeId},
+    collections::{HashMap, VecDeque},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Condvar, Mutex,
+    },
+    time::Duration,
+};
+
+use super::Ui;
+
+type Callback = Box<dyn FnOnce(&Ui) + Send>;
+type Value = Box<dyn Any + Send>;
+
+#[derive(Default)]
+struct Messages {
+    callbacks: Vec<Callback>,
+    // by type, they're kept until they're taken
+    values: HashMap<TypeId, VecDeque<Value>>,
+    // types that only keep the newest few
+    bounds: HashMap<TypeId, usize>,
+}
+
+#[derive(Default)]
+pub(crate) struct Shared {
+    messages: Mutex<Messages>,
+    woken: Mutex<bool>,
+    wake: Condvar,
+    pending: AtomicBool,
+}
+
+impl Shared {
+    pub fn wake(&self) {
+        *self.woken.lock().unwrap() = true;
+        self.wake.notify_all();
+    }
+
+    // returns true if something woke us up before the timeout
+    pub fn wait(&self, timeout: Option<Duration>) -> bool {
+        let woken = self.woken.lock().unwrap();
+        let mut woken = match timeout {
+            Some(timeout) => {
+                self.wake
+                    .wait_timeout_while(woken, timeout, |woken| !*woken)
+                    .unwrap()
+                    .0
+            }
+            None => self.wake.wait_while(woken, |woken| !*woken).unwrap(),
+        };
+        std::mem::take(&mut *woken)
+    }
+
+    // whether anything was posted since the last time this was called
+    pub fn take_pending(&self) -> bool {
+        self.pending.swap(false, Ordering::AcqRel)
+    }
+
+    pub fn take_callbacks(&self) -> Vec<Callback> {
+        std::mem::take(&mut self.messages.lock().unwrap().callbacks)
+    }
+
+    pub fn take_messages<T: Any + Send>(&self) -> Vec<T> {
+        let mut messages = self.messages.lock().unwrap();
+        let Some(values) = messages.values.remove(&TypeId::of::<T>()) else {
+            return vec![];
+        };
+        values
+            .into_iter()
+            .filter_map(|value| value.downcast().ok())
+            .map(|value| *value)
+            .collect()
+    }
+
+    // only the newest `max` of these are kept, older ones are dropped as new ones arrive
+    pub fn bound_messages<T: Any + Send>(&self, max: usize) {
+        let mut messages = self.messages.lock().unwrap();
+        let id = TypeId::of::<T>();
+        messages.bounds.insert(id, max);
+        if let Some(values) = messages.values.get_mut(&id) {
+            values.drain(..values.len().saturating_sub(max));
+        }
+    }
+
+    fn send(&self, value: Value) {
+        {
+            let mut messages = self.messages.lock().unwrap();
+            let id = (*value).type_id();
+            let bound = messages.bounds.get(&id).copied();
+            let values = messages.values.entry(id).or_default();
+            values.push_back(value);
+            if let Some(max) = bound {
+                values.drain(..values.len().saturating_sub(max));
+            }
+        }
+        self.notify()
+    }
+
+    fn run(&self, callback: Callback) {
+        self.messages.lock().unwrap().callbacks.push(callback);
+        self.notify()
+    }
+
+    pub fn notify(&self) {
+        self.pending.store(true, Ordering::Release);
+        self.wake()
+    }
+}
+
+#[derive(Clone)]
+pub struct UiHandle {
+    shared: Arc<Shared>,
+}
+
+impl std::fmt::Debug for UiHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("UiHandle").finish_non_exhaustive()
+    }
+}
+
+impl UiHandle {
+    pub(crate) fn new(shared: Arc<Shared>) -> Self {
+        Self { shared }
+    }
+
+    pub fn send<T: Any + Send>(&self, msg: T) {
+        self.shared.send(Box::new(msg))
+    }
+
+    pub fn run(&self, f: impl FnOnce(&Ui) + Send + 'static) {
+        self.shared.run(Box::new(f))
+    }
+
+    pub fn request_repaint(&self) {
+        self.shared.notify()
+    }
+
+    pub(crate) fn wake(&self) {
+        self.shared.wake()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn messages_are_taken_by_type() {
+        let handle = UiHandle::new(Arc::default());
+        handle.send(1_u32);
+        handle.send("a");
+        handle.send(2_u32);
+
+        assert_eq!(handle.shared.take_messages::<u32>(), [1, 2]);
+        assert!(handle.shared.take_messages::<u32>().is_empty());
+        assert_eq!(handle.shared.take_messages::<&str>(), ["a"]);
+    }
+
+    #[test]
+    fn messages_are_kept_until_taken() {
+        let handle = UiHandle::new(Arc::default());
+        handle.send(1_u32);
+        handle.send(2_u32);
+        assert!(handle.shared.take_messages::<&str>().is_empty());
+        assert_eq!(handle.shared.take_messages::<u32>(), [1, 2]);
+    }
+
+    #[test]
+    fn bounded_messages_keep_the_newest() {
+        let handle = UiHandle::new(Arc::default());
+        handle.send(1_u32);
+        handle.send(2_u32);
+        handle.send(3_u32);
+        handle.shared.bound_messages::<u32>(2);
+        assert_eq!(handle.shared.take_messages::<u32>(), [2, 3]);
+
+        (4..10_u32).for_each(|i| handle.send(i));
+        handle.send("a");
+        assert_eq!(handle.shared.take_messages::<u32>(), [8, 9]);
+        assert_eq!(handle.shared.take_messages::<&str>(), ["a"]);
+    }
+}
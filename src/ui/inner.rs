@@ -2,18 +2,20 @@ use slotmap::{SecondaryMap, SlotMap};
 use std::{
     any::TypeId,
     cell::{Cell, Ref, RefCell, RefMut},
-    collections::VecDeque,
+    collections::{HashSet, VecDeque},
+    path::PathBuf,
+    sync::Arc,
     time::{Duration, Instant},
 };
 
 use crate::{
     animation,
     context::LayoutCtx,
-    geom::{Constraints, Pos2, Rect, Vec2},
-    input::{Handled, Input},
+    geom::{math::almost_eq, size, Constraints, Pos2, Rect, Size, Vec2},
+    input::Input,
     node::{LayoutNode, Node, WidgetId},
     paint::Surface,
-    terminal::{event::Event, Terminal},
+    terminal::{event::Event, Backend, Capabilities, Terminal},
     ui::{context, paint::Paint, Command, Response, Ui},
     widget::{ErasedWidget, PlaceholderWidget, RootWidget, Widget},
 };
@@ -33,16 +35,28 @@ pub struct Inner {
     pub stack: RefCell<Vec<WidgetId>>,
     pub removed: RefCell<Vec<WidgetId>>,
 
+    // retained widgets that have to be painted again, and where everything was painted last frame
+    pub dirty: RefCell<HashSet<WidgetId>>,
+    pub painted: RefCell<SecondaryMap<WidgetId, (Rect, Rect)>>,
+
     pub rect: Cell<Rect>,
     pub time: Cell<f32>,
     pub current_frame: Cell<u64>,
     pub mouse_pos: Cell<Pos2>,
+    pub fit_content: Cell<bool>,
+    pub terminal_focused: Cell<bool>,
+    pub monochrome: Cell<bool>,
+    pub capabilities: RefCell<Capabilities>,
 
     pub repaint: RefCell<super::Repaint>,
     pub animation: RefCell<animation::Manager>,
     pub commands: RefCell<VecDeque<Command>>,
+    // these wait until the frame is on the screen
+    pub screenshots: RefCell<Vec<PathBuf>>,
+    pub shared: Arc<super::handle::Shared>,
 
     pub debug: RefCell<Vec<String>>,
+    pub debugged: Cell<bool>,
     pub quit: Cell<bool>,
 }
 
@@ -60,6 +74,7 @@ impl Inner {
             }),
             nodes: RefCell::new(nodes),
             rect: Cell::new(rect),
+            terminal_focused: Cell::new(true),
             ..Self::default()
         }
     }
@@ -71,6 +86,10 @@ impl Inner {
         this.nodes.borrow_mut()[this.root].next = 0;
         this.input.borrow_mut().start();
 
+        for callback in this.shared.take_callbacks() {
+            callback(ui)
+        }
+
         Ok(())
     }
 
@@ -113,8 +132,13 @@ impl Inner {
             layout: &mut layout,
         };
 
-        let value = this.rect.get().size() + Vec2::splat(1);
-        ctx.compute(this.root, Constraints::tight(value.into()));
+        let value: Size = (this.rect.get().size() + Vec2::splat(1)).into();
+        let input = if this.fit_content.get() {
+            Constraints::new(size(value.x, 0.0), value)
+        } else {
+            Constraints::tight(value)
+        };
+        ctx.compute(this.root, input);
 
         Self::resolve(this.root, layout.nodes, layout.computed);
 
@@ -122,12 +146,106 @@ impl Inner {
     }
 
     pub fn paint(&self, surface: &mut Surface) {
+        let damage = self.damage();
+
         let mut paint = Paint::default();
+        let mut debugged = false;
         for debug in self.debug.borrow_mut().drain(..) {
             paint.debug(debug);
+            debugged = true;
+        }
+
+        // debug text isn't a widget, so it (and whatever it covered) needs everything redrawn
+        if debugged || self.debugged.replace(debugged) || !surface.erase_damaged(&damage) {
+            surface.erase();
+        } else {
+            paint.damaged(damage, self.bounds());
         }
         paint.paint_all(self, &mut surface.crop(self.rect.get()));
     }
+
+    // where things could look different from the last frame
+    fn damage(&self) -> Vec<Rect> {
+        const MAX_DAMAGE: usize = 64;
+
+        let nodes = self.nodes.borrow();
+        let computed = self.computed.borrow();
+        let mut painted = self.painted.borrow_mut();
+        let mut dirty = self.dirty.borrow_mut();
+
+        let mut damage = Vec::new();
+        painted.retain(|id, &mut (_, rect)| {
+            let alive = nodes.contains_key(id) && computed.contains_key(id);
+            if !alive {
+                damage.push(rect)
+            }
+            alive
+        });
+
+        for (id, node) in nodes.iter() {
+            let Some(layout) = computed.get(id) else {
+                continue;
+            };
+
+            // the same rect `Paint` gives the widget. a clipped widget can move under its clip
+            let rect = match layout.clipped_by.and_then(|parent| computed.get(parent)) {
+                Some(parent) => parent.rect.intersection(layout.rect),
+                None => layout.rect,
+            };
+            match painted.insert(id, (layout.rect, rect)) {
+                Some(old) if old == (layout.rect, rect) => {}
+                Some((_, old)) => damage.extend([old, rect]),
+                None => damage.push(rect),
+            }
+            if !node.widget.retained() || dirty.contains(&id) {
+                damage.push(rect)
+            }
+        }
+        dirty.clear();
+
+        damage.retain(|rect| rect.width() > 0 && rect.height() > 0);
+        damage.dedup();
+
+        // lots of little rects cost more to check than just painting a bit more
+        if damage.len() > MAX_DAMAGE {
+            let union = damage.iter().fold(damage[0], |a, b| a.union(*b));
+            damage = vec![union];
+        }
+        damage
+    }
+
+    // each widget's rect, grown to cover everything its children paint
+    fn bounds(&self) -> SecondaryMap<WidgetId, Rect> {
+        fn visit(
+            id: WidgetId,
+            nodes: &SlotMap<WidgetId, Node>,
+            painted: &SecondaryMap<WidgetId, (Rect, Rect)>,
+            bounds: &mut SecondaryMap<WidgetId, Rect>,
+        ) -> Option<Rect> {
+            let mut rect = painted.get(id).map(|&(_, rect)| rect);
+            for &child in nodes[id].children() {
+                if let Some(child) = visit(child, nodes, painted, bounds) {
+                    rect = Some(rect.map_or(child, |rect| rect.union(child)))
+                }
+            }
+            let rect = rect?;
+            bounds.insert(id, rect);
+            Some(rect)
+        }
+
+        let mut bounds = SecondaryMap::new();
+        visit(
+            self.root,
+            &self.nodes.borrow(),
+            &self.painted.borrow(),
+            &mut bounds,
+        );
+        bounds
+    }
+
+    pub fn invalidate(&self, id: WidgetId) {
+        self.dirty.borrow_mut().insert(id);
+    }
 }
 
 impl Inner {
@@ -144,8 +262,35 @@ impl Inner {
         self.rect.get()
     }
 
-    pub fn remaining(&self, clock: Instant) -> Duration {
-        self.repaint.borrow().remaining(clock)
+    // how far down the top level widgets reach
+    pub fn content_height(&self) -> i32 {
+        let computed = self.computed.borrow();
+        self.nodes.borrow()[self.root]
+            .children
+            .iter()
+            .filter_map(|&id| computed.get(id))
+            .map(|node| node.rect.max.y)
+            .max()
+            .unwrap_or(0)
+    }
+
+    pub fn repaint_after(&self, clock: Instant) -> Option<Duration> {
+        let repaint = self.repaint.borrow();
+        repaint
+            .has_requested_repaint()
+            .then(|| repaint.remaining(clock))
+    }
+
+    pub fn reset_repaint(&self) {
+        self.repaint.borrow_mut().reset()
+    }
+
+    pub fn take_pending(&self) -> bool {
+        self.shared.take_pending()
+    }
+
+    pub fn wait(&self, timeout: Option<Duration>) -> bool {
+        self.shared.wait(timeout)
     }
 
     pub fn tick(&self, t: f32) {
@@ -165,34 +310,61 @@ impl Inner {
         self.debug.borrow_mut().push(debug.to_string())
     }
 
-    pub fn handle_external_commands(&self, terminal: &mut Terminal) -> std::io::Result<()> {
+    pub fn handle_external_commands<B: Backend>(
+        &self,
+        terminal: &mut Terminal<B>,
+    ) -> std::io::Result<()> {
         for cmd in self.commands.borrow_mut().drain(..) {
             match cmd {
                 Command::SetTitle(title) => terminal.set_title(&title)?,
+                Command::Screenshot(path) => self.screenshots.borrow_mut().push(path),
+                Command::SetClipboard(data) => terminal.set_clipboard(&data)?,
+                Command::RequestClipboard => terminal.request_clipboard()?,
                 Command::Quit => self.quit.set(true),
-                Command::LeaveAltScreen => terminal.leave_alt_screen()?,
-                Command::EnterAltScreen => terminal.enter_alt_screen()?,
+                Command::LeaveAltScreen => {
+                    terminal.leave_alt_screen()?;
+                    self.rect.set(terminal.rect())
+                }
+                Command::EnterAltScreen => {
+                    terminal.enter_alt_screen()?;
+                    self.rect.set(terminal.rect())
+                }
             }
         }
         Ok(())
     }
+
+    // a screenshot that can't be saved shouldn't end the app, so it's shown on the next frame
+    pub fn save_screenshots<B: Backend>(&self, terminal: &Terminal<B>) {
+        for path in self.screenshots.take() {
+            if let Err(err) = terminal.screenshot(&path) {
+                self.debug(format!("cannot save {}: {err}", path.display()));
+                self.shared.notify()
+            }
+        }
+    }
 }
 
 impl Inner {
-    pub fn handle_event(&self, event: &Event) -> bool {
-        if let &Event::Resize(rect) = event {
-            self.rect.set(rect);
-            return true;
+    pub fn handle_event(&self, event: &Event) {
+        match *event {
+            Event::Resize(rect) => {
+                self.rect.set(rect);
+                return;
+            }
+            Event::FocusGained => self.terminal_focused.set(true),
+            Event::FocusLost => self.terminal_focused.set(false),
+            _ => {}
         }
 
-        let resp = self.input.borrow_mut().handle(
+        self.input.borrow_mut().handle(
             event, //
             &mut self.nodes.borrow_mut(),
             &mut self.computed.borrow_mut(),
+            &self.dirty,
         );
 
         self.mouse_pos.set(self.input.borrow().mouse.pos);
-        resp == Handled::Sink
     }
 
     pub fn root(&self) -> WidgetId {
@@ -241,12 +413,17 @@ impl Inner {
         value: bool,
         animation_time: f32,
     ) -> f32 {
-        self.animation.borrow_mut().animate_bool(
+        let current = self.animation.borrow_mut().animate_bool(
             animation::Id::new(source),
             self.time(),
             value,
             animation_time,
-        )
+        );
+
+        if !almost_eq(current, if value { 1.0 } else { 0.0 }) {
+            self.request_repaint()
+        }
+        current
     }
 
     pub fn animate_value(
@@ -255,9 +432,16 @@ impl Inner {
         value: f32,
         animation_time: f32,
     ) -> f32 {
-        self.animation
-            .borrow_mut()
-            .animate_value(animation::Id::new(source), value, animation_time)
+        let current = self.animation.borrow_mut().animate_value(
+            animation::Id::new(source),
+            value,
+            animation_time,
+        );
+
+        if !almost_eq(current, value) {
+            self.request_repaint()
+        }
+        current
     }
 
     pub fn begin_widget<W: Widget>(&self, props: W::Props<'_>) -> Response<W::Response> {
@@ -312,6 +496,8 @@ impl Inner {
 
         let widget = std::mem::replace(&mut node.widget, Box::new(PlaceholderWidget));
         if widget.as_ref().type_id() != TypeId::of::<W>() {
+            // the new widget takes the old one's place
+            nodes[parent].next -= 1;
             Self::remove_widget(&mut nodes, &mut self.removed.borrow_mut(), id);
             return Self::allocate_widget::<W>(&mut nodes, parent);
         }
@@ -339,11 +525,7 @@ impl Inner {
         });
 
         let parent = &mut nodes[parent];
-        if parent.next < parent.children.len() {
-            parent.children[parent.next] = id;
-        } else {
-            parent.children.push(id)
-        }
+        parent.children.insert(parent.next, id);
         parent.next += 1;
         (id, <Box<W>>::default() as Box<dyn ErasedWidget>)
     }
@@ -406,3 +588,99 @@ impl Inner {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use crate::{
+        debug::Harness,
+        geom::{pos2, vec2},
+        widgets::{column, label, mapped_label, offset, separator},
+    };
+
+    #[test]
+    fn replaced_widget_keeps_its_place() {
+        let mut harness = Harness::new(vec2(3, 3));
+        let app = |swapped: bool| {
+            move |_: &crate::Ui| {
+                column(|| {
+                    if swapped {
+                        separator();
+                    } else {
+                        label("a");
+                    }
+                    label("b");
+                    label("c");
+                });
+            }
+        };
+
+        let snapshot = harness.frame(app(false));
+        assert_eq!(snapshot.to_string(), "a  \nb  \nc  ");
+
+        let snapshot = harness.frame(app(true));
+        assert_eq!(snapshot.to_string(), "───\nb  \nc  ");
+        let labels = snapshot
+            .rects
+            .iter()
+            .filter(|w| w.name.starts_with("Label"));
+        assert_eq!(labels.count(), 2);
+    }
+
+    #[test]
+    fn unchanged_retained_widget_is_not_repainted() {
+        static PAINTED: AtomicUsize = AtomicUsize::new(0);
+        let mut harness = Harness::new(vec2(4, 2));
+        let mut app = |_: &crate::Ui| {
+            column(|| {
+                mapped_label("hi", |_, cell| {
+                    PAINTED.fetch_add(1, Ordering::Relaxed);
+                    cell
+                });
+            });
+        };
+
+        harness.frame(&mut app);
+        let painted = PAINTED.load(Ordering::Relaxed);
+        assert!(painted > 0);
+
+        let snapshot = harness.frame(&mut app);
+        assert_eq!(PAINTED.load(Ordering::Relaxed), painted);
+        assert_eq!(snapshot.stats.glyphs, 0);
+        assert_eq!(snapshot.to_string(), "hi  \n    ");
+    }
+
+    #[test]
+    fn moved_widget_is_erased_where_it_was() {
+        let mut harness = Harness::new(vec2(4, 2));
+        let app = |pos| move |_: &crate::Ui| _ = offset(pos, || label("x"));
+
+        let snapshot = harness.frame(app(pos2(0, 0)));
+        assert_eq!(snapshot.to_string(), "x   \n    ");
+
+        let snapshot = harness.frame(app(pos2(2, 1)));
+        assert_eq!(snapshot.to_string(), "    \n  x ");
+    }
+
+    #[test]
+    fn removed_widget_is_erased() {
+        let mut harness = Harness::new(vec2(4, 2));
+        let app = |both: bool| {
+            move |_: &crate::Ui| {
+                column(|| {
+                    label("a");
+                    if both {
+                        label("b");
+                    }
+                });
+            }
+        };
+
+        let snapshot = harness.frame(app(true));
+        assert_eq!(snapshot.to_string(), "a   \nb   ");
+
+        let snapshot = harness.frame(app(false));
+        assert_eq!(snapshot.to_string(), "a   \n    ");
+    }
+}
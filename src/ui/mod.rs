@@ -6,7 +6,10 @@ use crate::{
     node::{LayoutNode, Node, WidgetId},
     widget::Widget,
 };
-use crate::{paint::Surface, terminal::Terminal};
+use crate::{
+    paint::Surface,
+    terminal::{Backend, Capabilities, Terminal},
+};
 
 use slotmap::{SecondaryMap, SlotMap};
 use std::{
@@ -30,12 +33,21 @@ use repaint::Repaint;
 mod inner;
 pub(crate) use inner::Inner;
 
+mod handle;
+pub use handle::UiHandle;
+
 #[non_exhaustive]
 #[derive(Clone, Debug, PartialEq, PartialOrd, Eq, Ord, Hash)]
 pub enum Command {
     SetTitle(String),
     LeaveAltScreen,
     EnterAltScreen,
+    // saves what is on the screen, as html if the path ends in .html and as an svg otherwise
+    Screenshot(std::path::PathBuf),
+    // copies the text to the system clipboard with osc 52
+    SetClipboard(String),
+    // asks the terminal for the clipboard, it arrives as an `Event::Paste` if the terminal answers
+    RequestClipboard,
     Quit,
 }
 
@@ -72,9 +84,31 @@ impl Ui {
         self.command(Command::SetTitle(title.to_string()))
     }
 
+    pub fn set_clipboard(&self, data: impl ToString) {
+        self.command(Command::SetClipboard(data.to_string()))
+    }
+
+    pub fn request_clipboard(&self) {
+        self.command(Command::RequestClipboard)
+    }
+
     pub fn debug(&self, debug: impl ToString) {
         self.inner.debug(debug)
     }
+
+    pub fn handle(&self) -> UiHandle {
+        UiHandle::new(self.inner.shared.clone())
+    }
+
+    // everything sent with `UiHandle::send` waits here until it's taken
+    pub fn take_messages<T: std::any::Any + Send>(&self) -> Vec<T> {
+        self.inner.shared.take_messages()
+    }
+
+    // for messages that are only taken sometimes, keeps the newest `max` of them
+    pub fn bound_messages<T: std::any::Any + Send>(&self, max: usize) {
+        self.inner.shared.bound_messages::<T>(max)
+    }
 }
 
 impl Ui {
@@ -151,6 +185,21 @@ impl Ui {
         self.inner.mouse_over_widget(id)
     }
 
+    // whether the terminal window has focus, if it reports that
+    pub fn terminal_focused(&self) -> bool {
+        self.inner.terminal_focused.get()
+    }
+
+    // what the terminal said it supports when it started, see `Capabilities::is_dark`
+    pub fn capabilities(&self) -> Ref<'_, Capabilities> {
+        self.inner.capabilities.borrow()
+    }
+
+    // colors aren't shown, so widgets should use glyphs or attributes to show their state
+    pub fn is_monochrome(&self) -> bool {
+        self.inner.monochrome.get()
+    }
+
     pub fn request_repaint(&self) {
         self.request_repaint_after(Duration::ZERO)
     }
@@ -159,6 +208,11 @@ impl Ui {
         self.inner.request_repaint_after(after)
     }
 
+    // paints the current widget again, even if it's retained
+    pub fn invalidate(&self) {
+        self.inner.invalidate(self.current())
+    }
+
     pub fn animate_bool(
         &self,
         source: impl std::hash::Hash,
@@ -225,22 +279,57 @@ impl Ui {
         self.inner.end_widget(id)
     }
 
-    pub(crate) fn remaining(&self, clock: Instant) -> Duration {
-        self.inner.remaining(clock)
+    pub(crate) fn repaint_after(&self, clock: Instant) -> Option<Duration> {
+        self.inner.repaint_after(clock)
+    }
+
+    pub(crate) fn reset_repaint(&self) {
+        self.inner.reset_repaint()
+    }
+
+    pub(crate) fn take_pending(&self) -> bool {
+        self.inner.take_pending()
+    }
+
+    pub(crate) fn wait(&self, timeout: Option<Duration>) -> bool {
+        self.inner.wait(timeout)
+    }
+
+    pub(crate) fn set_fit_content(&self, fit_content: bool) {
+        self.inner.fit_content.set(fit_content)
+    }
+
+    pub(crate) fn set_capabilities(&self, capabilities: Capabilities) {
+        *self.inner.capabilities.borrow_mut() = capabilities
+    }
+
+    pub(crate) fn set_monochrome(&self, monochrome: bool) {
+        self.inner.monochrome.set(monochrome)
+    }
+
+    pub(crate) fn content_height(&self) -> i32 {
+        self.inner.content_height()
     }
 
     pub(crate) fn tick(&self, t: f32) {
         self.inner.tick(t)
     }
 
-    pub(crate) fn handle_event(&self, event: &Event) -> bool {
+    pub(crate) fn handle_event(&self, event: &Event) {
         self.inner.handle_event(event)
     }
 
-    pub(crate) fn handle_external_commands(&self, terminal: &mut Terminal) -> std::io::Result<()> {
+    pub(crate) fn handle_external_commands<B: Backend>(
+        &self,
+        terminal: &mut Terminal<B>,
+    ) -> std::io::Result<()> {
         self.inner.handle_external_commands(terminal)
     }
 
+    pub(crate) fn save_screenshots<B: Backend>(&self, terminal: &Terminal<B>) {
+        self.inner.save_screenshots(terminal)
+    }
+
     pub(crate) fn paint(&self, surface: &mut Surface) {
         self.inner.paint(surface)
     }
@@ -1,3 +1,5 @@
+use slotmap::SecondaryMap;
+
 use crate::context::PaintCtx;
 use crate::geom::{pos2, Rect};
 use crate::node::WidgetId;
@@ -7,6 +9,9 @@ use crate::paint::{CroppedSurface as Canvas, Styled};
 pub struct Paint {
     clip_stack: Vec<Rect>,
     debug: Vec<String>,
+    // only widgets overlapping these are painted, everything is if this is None
+    damage: Option<Vec<Rect>>,
+    bounds: SecondaryMap<WidgetId, Rect>,
 }
 
 impl Paint {
@@ -15,6 +20,11 @@ impl Paint {
         self.paint_debug(canvas)
     }
 
+    pub(crate) fn damaged(&mut self, damage: Vec<Rect>, bounds: SecondaryMap<WidgetId, Rect>) {
+        self.damage = Some(damage);
+        self.bounds = bounds;
+    }
+
     pub fn debug(&mut self, label: impl ToString) {
         self.debug
             .extend(label.to_string().lines().map(|s| s.to_string()))
@@ -34,6 +44,10 @@ impl Paint {
     }
 
     pub(crate) fn paint(&mut self, ui: &super::Inner, canvas: &mut Canvas<'_>, id: WidgetId) {
+        if !self.is_damaged(id) {
+            return;
+        }
+
         let computed = ui.computed.borrow();
         let Some(layout) = computed.get(id) else {
             return;
@@ -69,6 +83,15 @@ impl Paint {
         }
     }
 
+    fn is_damaged(&self, id: WidgetId) -> bool {
+        let Some(damage) = &self.damage else {
+            return true;
+        };
+        self.bounds
+            .get(id)
+            .is_some_and(|bounds| damage.iter().any(|rect| rect.intersects(*bounds)))
+    }
+
     fn push_clip(&mut self, mut rect: Rect) {
         if let Some(previous) = self.clip_stack.last() {
             rect = rect.intersection(*previous);
@@ -14,13 +14,10 @@ pub trait ErasedWidget: std::any::Any + std::fmt::Debug {
     fn paint(&self, ctx: PaintCtx);
 
     fn interest(&self) -> Interest;
+    fn retained(&self) -> bool;
     fn event(&mut self, ctx: EventCtx, event: Event) -> Handled;
 
-    fn default_layout(&self, ctx: LayoutCtx, input: Constraints) -> Size;
-    fn default_paint(&self, ctx: PaintCtx);
-
     fn type_name(&self) -> &'static str;
-    fn as_any(&self) -> &dyn std::any::Any;
     fn as_any_mut(&mut self) -> &mut dyn std::any::Any;
 }
 
@@ -45,26 +42,18 @@ impl<T: Widget> ErasedWidget for T {
         <Self as Widget>::interest(self)
     }
 
-    fn event(&mut self, ctx: EventCtx, event: Event) -> Handled {
-        <Self as Widget>::event(self, ctx, event)
-    }
-
-    fn default_layout(&self, ctx: LayoutCtx, input: Constraints) -> Size {
-        <Self as Widget>::default_layout(self, ctx, input)
+    fn retained(&self) -> bool {
+        <Self as Widget>::retained(self)
     }
 
-    fn default_paint(&self, ctx: PaintCtx) {
-        <Self as Widget>::default_paint(self, ctx)
+    fn event(&mut self, ctx: EventCtx, event: Event) -> Handled {
+        <Self as Widget>::event(self, ctx, event)
     }
 
     fn type_name(&self) -> &'static str {
         std::any::type_name::<T>()
     }
 
-    fn as_any(&self) -> &dyn std::any::Any {
-        self as _
-    }
-
     fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
         self as _
     }
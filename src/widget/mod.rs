@@ -60,6 +60,13 @@ pub trait Widget: Default + std::fmt::Debug + 'static + Serialize {
         Interest::NONE
     }
 
+    // retained widgets are only painted again when their rect changes or they're invalidated,
+    // so they have to call `Ui::invalidate` whenever they would look different.
+    // widgets that change on their own, like animations or hover states, return false
+    fn retained(&self) -> bool {
+        true
+    }
+
     fn event(&mut self, ctx: EventCtx, event: Event) -> Handled {
         let _ = ctx;
         let _ = event;
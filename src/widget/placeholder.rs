@@ -1,7 +1,6 @@
 use super::{NoResponse, Widget};
 
 #[derive(Default, Debug)]
-#[cfg_attr(feature = "serde", derive(::serde::Serialize,))]
 pub(crate) struct PlaceholderWidget;
 
 impl Widget for PlaceholderWidget {
@@ -9,4 +8,8 @@ impl Widget for PlaceholderWidget {
     type Props<'a> = ();
 
     fn update(&mut self, _: Self::Props<'_>) -> Self::Response {}
+
+    fn retained(&self) -> bool {
+        false
+    }
 }
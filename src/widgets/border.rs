@@ -2,6 +2,7 @@ use crate::{
     context::PaintCtx,
     geom::{vec2, Margin},
     paint::{shape, Label, Styled},
+    ui,
     widget::Response,
     NoResponse, Widget, WidgetExt as _,
 };
@@ -24,6 +25,14 @@ impl<T: Label + 'static> Default for Border<T> {
 }
 
 impl<T: Label> Border<T> {
+    fn same_as(&self, other: &Self) -> bool {
+        self.style == other.style
+            && match (&self.title, &other.title) {
+                (Some(a), Some(b)) => a.same_as(b),
+                (a, b) => a.is_none() && b.is_none(),
+            }
+    }
+
     pub fn show<R>(self, children: impl FnOnce() -> R) -> Response {
         <BorderWidget<T>>::show_children(self, children)
     }
@@ -62,6 +71,9 @@ impl<T: Label + 'static> Widget for BorderWidget<T> {
     type Props<'a> = Border<T>;
 
     fn update(&mut self, props: Self::Props<'_>) -> Self::Response {
+        if !self.props.same_as(&props) {
+            ui().invalidate()
+        }
         self.props = props;
     }
 
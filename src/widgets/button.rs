@@ -3,7 +3,7 @@ use crate::{
     context::{EventCtx, PaintCtx},
     geom::Margin,
     input::{Event, Handled, Interest},
-    paint::{shape, Label, Styled},
+    paint::{shape, Attribute, Label, Styled},
     widget::Response,
     Widget, WidgetExt as _,
 };
@@ -85,6 +85,10 @@ impl Widget for ButtonWidget {
         }
     }
 
+    fn retained(&self) -> bool {
+        false
+    }
+
     fn interest(&self) -> Interest {
         Interest::MOUSE & !Interest::MOUSE_MOVE
     }
@@ -125,8 +129,24 @@ impl Widget for ButtonWidget {
             bg = Rgba::from_u32(0x333333);
         }
 
-        ctx.draw(shape::Filled::bg(bg));
-        self.default_paint(ctx)
+        if !ctx.is_monochrome() {
+            ctx.draw(shape::Filled::bg(bg));
+            return self.default_paint(ctx);
+        }
+
+        ctx.draw(shape::Filled::new(' '));
+        let attr = match self.state {
+            _ if self.disabled => Attribute::FAINT,
+            ButtonState::Hovered => Attribute::REVERSE,
+            ButtonState::Held => Attribute::REVERSE | Attribute::BOLD,
+            ButtonState::None => return self.default_paint(ctx),
+        };
+
+        // the label resets the attributes of what it paints, so these go on top of it
+        for &child in ctx.children {
+            ctx.paint(child)
+        }
+        ctx.add_attr(attr)
     }
 }
 
@@ -147,3 +167,52 @@ pub fn color_button<T: Label>(
 ) -> Response<ButtonResponse> {
     Button::new(label).bg(bg).show()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        debug::Harness,
+        geom::{pos2, vec2},
+        input::{Modifiers, MouseButton},
+        terminal::event::{Event as TermEvent, MouseEvent},
+        widgets::row,
+        Ui,
+    };
+
+    fn buttons(_: &Ui) {
+        row(|| {
+            button("ok");
+            disabled_button(true, "no");
+        });
+    }
+
+    #[test]
+    fn monochrome_shows_the_state_with_attributes() {
+        let mut harness = Harness::new(vec2(14, 1)).monochrome(true);
+
+        let snapshot = harness.frame(buttons);
+        assert_eq!(snapshot.text, "  ok    no    ");
+        for x in 0..6 {
+            assert_eq!(snapshot.attr(pos2(x, 0)), Attribute::RESET, "{x}");
+        }
+        for x in 6..12 {
+            assert_eq!(snapshot.attr(pos2(x, 0)), Attribute::FAINT, "{x}");
+        }
+
+        let held = MouseEvent::Held {
+            button: MouseButton::Primary,
+        };
+        harness.move_to(pos2(1, 0));
+        harness.event(TermEvent::Mouse(held, pos2(1, 0), Modifiers::NONE));
+        let snapshot = harness.frame(buttons);
+        assert_eq!(snapshot.text, "  ok    no    ");
+        for x in 0..6 {
+            let attr = Attribute::REVERSE | Attribute::BOLD;
+            assert_eq!(snapshot.attr(pos2(x, 0)), attr, "{x}");
+        }
+        for x in 6..12 {
+            assert_eq!(snapshot.attr(pos2(x, 0)), Attribute::FAINT, "{x}");
+        }
+    }
+}
@@ -3,6 +3,7 @@ use crate::{
     context::{LayoutCtx, PaintCtx},
     geom::{Constraints, Size},
     paint::{shape::Filled, Cell},
+    ui,
     widget::Response,
     NoResponse, Widget, WidgetExt as _,
 };
@@ -18,6 +19,9 @@ impl Widget for FilledWidget {
     type Props<'a> = (Filled, Size);
 
     fn update(&mut self, props: Self::Props<'_>) -> Self::Response {
+        if (self.props, self.min_size) != props {
+            ui().invalidate()
+        }
         (self.props, self.min_size) = props;
     }
 
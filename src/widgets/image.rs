@@ -0,0 +1,213 @@
+use crate::{
+    context::{LayoutCtx, PaintCtx},
+    geom::{vec2, Constraints, Rect, Size, Vec2},
+    paint::{shape::Pixels, Graphics, Image},
+    ui,
+    widget::Response,
+    NoResponse, Widget, WidgetExt as _,
+};
+
+// how an image fills the space it was given. cells are taken to be twice as tall as they are wide
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Scaling {
+    // stretched over all of it
+    #[default]
+    Fill,
+    // as large as it can be without cropping or changing its shape, centered
+    Contain,
+    // the same as contain, but never larger than its natural size
+    Fit,
+}
+
+// images can be loaded with `Image::load`, they should be kept around rather than loaded every frame
+#[derive(Clone, Debug, PartialEq)]
+pub struct ImageView {
+    image: Image,
+    size: Option<Vec2>,
+    scaling: Scaling,
+    quadrants: bool,
+}
+
+impl ImageView {
+    pub fn new(image: &Image) -> Self {
+        Self {
+            image: image.clone(),
+            size: None,
+            scaling: Scaling::Fill,
+            quadrants: false,
+        }
+    }
+
+    // how many cells to reserve, otherwise it's one per pixel (two down with half blocks)
+    pub const fn size(mut self, size: Vec2) -> Self {
+        self.size = Some(size);
+        self
+    }
+
+    pub const fn scaling(mut self, scaling: Scaling) -> Self {
+        self.scaling = scaling;
+        self
+    }
+
+    // when drawn with cells, use quadrant blocks instead of half blocks
+    pub const fn quadrants(mut self, quadrants: bool) -> Self {
+        self.quadrants = quadrants;
+        self
+    }
+
+    pub fn show(self) -> Response {
+        ImageWidget::show(self)
+    }
+
+    fn natural_size(&self) -> Vec2 {
+        let size = self.image.size();
+        let across = if self.quadrants { 2 } else { 1 };
+        vec2((size.x + across - 1) / across, (size.y + 1) / 2)
+    }
+
+    // the part of `rect` the image covers
+    fn scale(&self, rect: Rect) -> Rect {
+        let image = self.image.size();
+        if self.scaling == Scaling::Fill || image.x == 0 || image.y == 0 {
+            return rect;
+        }
+
+        // in half cells, so a pixel is square
+        let (w, h) = (rect.width() as f32, rect.height() as f32 * 2.0);
+        let mut scale = (w / image.x as f32).min(h / image.y as f32);
+        if self.scaling == Scaling::Fit {
+            let natural = self.natural_size();
+            scale = scale.min(natural.x as f32 / image.x as f32);
+        }
+
+        let size = vec2(
+            ((image.x as f32 * scale).round() as i32).clamp(1, rect.width()),
+            ((image.y as f32 * scale / 2.0).round() as i32).clamp(1, rect.height()),
+        );
+        let offset = vec2((rect.width() - size.x) / 2, (rect.height() - size.y) / 2);
+        Rect::from_min_size(rect.left_top() + offset, size)
+    }
+}
+
+#[derive(Debug, Default)]
+struct ImageWidget {
+    props: Option<ImageView>,
+}
+
+impl Widget for ImageWidget {
+    type Response = NoResponse;
+    type Props<'a> = ImageView;
+
+    fn update(&mut self, props: Self::Props<'_>) -> Self::Response {
+        if self.props.as_ref() != Some(&props) {
+            ui().invalidate()
+        }
+        self.props = Some(props);
+    }
+
+    fn layout(&self, _: LayoutCtx, input: Constraints) -> Size {
+        let Some(props) = &self.props else {
+            return input.constrain_min(Size::ZERO);
+        };
+        let size = props.size.unwrap_or_else(|| props.natural_size());
+        input.constrain(size.into())
+    }
+
+    fn paint(&self, mut ctx: PaintCtx) {
+        let Some(props) = &self.props else { return };
+
+        let rect = props.scale(ctx.get_layout_node(ctx.current_id).rect);
+        if props.quadrants && ctx.graphics() == Graphics::HalfBlocks {
+            ctx.draw_clipped(rect, Pixels::new(&props.image).quadrants(true));
+            return;
+        }
+        ctx.draw_image_cropped(rect, &props.image)
+    }
+}
+
+pub fn image(image: &Image) -> Response {
+    ImageView::new(image).show()
+}
+
+pub fn image_sized(image: &Image, size: Vec2) -> Response {
+    ImageView::new(image).size(size).show()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        color::Rgba,
+        debug::Harness,
+        geom::{pos2, rect},
+        widgets::row,
+    };
+
+    fn blank(width: u32, height: u32) -> Image {
+        Image::from_fn(width, height, |_, _| Rgba::new(255, 255, 255))
+    }
+
+    #[test]
+    fn natural_size_is_two_pixels_down_a_cell() {
+        let table = [
+            ((4, 6), false, vec2(4, 3)),
+            ((5, 5), false, vec2(5, 3)),
+            ((4, 6), true, vec2(2, 3)),
+            ((5, 5), true, vec2(3, 3)),
+            ((0, 0), false, vec2(0, 0)),
+        ];
+        for ((width, height), quadrants, expected) in table {
+            let view = ImageView::new(&blank(width, height)).quadrants(quadrants);
+            assert_eq!(
+                view.natural_size(),
+                expected,
+                "{width}x{height} {quadrants}"
+            );
+        }
+    }
+
+    #[test]
+    fn scaling_keeps_the_shape() {
+        // a square image is twice as wide as it is tall in cells
+        let square = blank(4, 4);
+        let space = rect(vec2(10, 4));
+        let table = [
+            (Scaling::Fill, space),
+            (
+                Scaling::Contain,
+                Rect::from_min_size(pos2(1, 0), vec2(8, 4)),
+            ),
+            (Scaling::Fit, Rect::from_min_size(pos2(3, 1), vec2(4, 2))),
+        ];
+        for (scaling, expected) in table {
+            let view = ImageView::new(&square).scaling(scaling);
+            assert_eq!(view.scale(space), expected, "{scaling:?}");
+        }
+    }
+
+    #[test]
+    fn layout_uses_the_natural_or_given_size() {
+        let image = blank(6, 4);
+        let mut harness = Harness::new(vec2(20, 5));
+        let rects = |snapshot: crate::debug::Snapshot| {
+            snapshot
+                .widgets_named("ImageWidget")
+                .map(|w| w.rect)
+                .collect::<Vec<_>>()
+        };
+
+        let snapshot = harness.frame(|_| {
+            row(|| {
+                super::image(&image);
+                image_sized(&image, vec2(3, 1));
+            });
+        });
+        assert_eq!(
+            rects(snapshot),
+            [
+                Rect::from_min_size(pos2(0, 0), vec2(6, 2)),
+                Rect::from_min_size(pos2(6, 0), vec2(3, 1)),
+            ]
+        );
+    }
+}
@@ -1,13 +1,17 @@
 use std::{
     cell::{Ref, RefCell},
+    ops::Range,
     rc::Rc,
 };
 
+use unicode_width::{UnicodeWidthChar as _, UnicodeWidthStr as _};
+
 use crate::{
     context::{EventCtx, LayoutCtx, PaintCtx},
-    geom::{math::remap, pos2, size, Constraints, Pos2, Size},
+    geom::{math::remap, pos2, size, Constraints, Size},
     input::{Event, Handled, Interest, Key},
-    paint::{shape::Filled, Attribute, Styled},
+    paint::{shape::Filled, Attribute, CursorShape, Styled},
+    ui::{ui, Command},
     widget::Response,
     Widget, WidgetExt as _,
 };
@@ -23,6 +27,7 @@ impl InputBuffer {
         Self {
             inner: Rc::new(RefCell::new(Inner {
                 cursor: buffer.len(),
+                anchor: None,
                 buffer,
             })),
         }
@@ -32,6 +37,7 @@ impl InputBuffer {
         let buffer = data.to_string();
         let data = Inner {
             cursor: buffer.len(),
+            anchor: None,
             buffer,
         };
         let _ = std::mem::replace(&mut *self.inner.borrow_mut(), data);
@@ -41,6 +47,7 @@ impl InputBuffer {
         let mut inner = self.inner.borrow_mut();
         inner.buffer.clear();
         inner.cursor = 0;
+        inner.anchor = None;
     }
 
     pub fn len(&self) -> usize {
@@ -61,10 +68,16 @@ impl InputBuffer {
     fn take(&mut self) -> String {
         let mut inner = self.inner.borrow_mut();
         inner.cursor = 0;
+        inner.anchor = None;
         std::mem::take(&mut inner.buffer)
     }
 
     fn insert(&mut self, ch: char) {
+        self.insert_str(ch.encode_utf8(&mut [0; 4]))
+    }
+
+    fn insert_str(&mut self, data: &str) {
+        self.delete_selection();
         let mut inner = self.inner.borrow_mut();
 
         inner.cursor = inner.cursor.min(inner.buffer.len());
@@ -72,27 +85,68 @@ impl InputBuffer {
             inner.cursor = inner.cursor.saturating_sub(1);
         }
 
-        if inner.buffer.is_empty() {
-            inner.buffer.push(ch)
-        } else {
-            let cursor = inner.cursor;
-            inner.buffer.insert(cursor, ch)
-        }
+        let cursor = inner.cursor;
+        inner.buffer.insert_str(cursor, data);
+        inner.cursor += data.len()
+    }
+
+    // nothing is copied without a selection
+    fn copy(&self) -> Option<Command> {
+        let inner = self.inner.borrow();
+        let range = inner.selection()?;
+        Some(Command::SetClipboard(inner.buffer[range].to_string()))
+    }
 
-        inner.cursor += ch.len_utf8()
+    fn cut(&mut self) -> Option<Command> {
+        let cmd = self.copy()?;
+        self.delete_selection();
+        Some(cmd)
     }
 
-    fn select(&mut self, delta: i32) {}
-    fn select_word(&mut self, delta: i32) {}
-    fn delete_word(&mut self, delta: i32) {}
+    // returns whether there was anything to delete
+    fn delete_selection(&mut self) -> bool {
+        let mut inner = self.inner.borrow_mut();
+        let Some(range) = inner.selection() else {
+            inner.anchor = None;
+            return false;
+        };
+        inner.cursor = range.start;
+        inner.anchor = None;
+        inner.buffer.replace_range(range, "");
+        true
+    }
+
+    // the selection runs from where it was started to the cursor
+    fn select(&mut self, delta: i32) {
+        let anchor = self.start_selection();
+        self.move_by(delta);
+        self.inner.borrow_mut().anchor = Some(anchor);
+    }
+
+    fn select_word(&mut self, _delta: i32) {}
+    fn delete_word(&mut self, _delta: i32) {}
 
     fn select_home(&mut self) {
-        // how should we do anchors?
+        let anchor = self.start_selection();
+        self.home();
+        self.inner.borrow_mut().anchor = Some(anchor);
+    }
+
+    fn select_end(&mut self) {
+        let anchor = self.start_selection();
+        self.end();
+        self.inner.borrow_mut().anchor = Some(anchor);
+    }
+
+    fn start_selection(&mut self) -> usize {
+        let mut inner = self.inner.borrow_mut();
+        let cursor = inner.cursor;
+        *inner.anchor.get_or_insert(cursor)
     }
-    fn select_end(&mut self) {}
 
     fn move_by(&mut self, delta: i32) {
         let mut inner = self.inner.borrow_mut();
+        inner.anchor = None;
         let mut cursor = inner.cursor as i32;
         let mut remaining = delta.abs();
 
@@ -107,6 +161,10 @@ impl InputBuffer {
     }
 
     fn delete(&mut self, delta: i32) {
+        if self.delete_selection() {
+            return;
+        }
+
         let mut inner = self.inner.borrow_mut();
         let anchor = inner.cursor as i32;
         let mut end = anchor;
@@ -131,12 +189,15 @@ impl InputBuffer {
     }
 
     fn home(&mut self) {
-        self.inner.borrow_mut().cursor = 0;
+        let mut inner = self.inner.borrow_mut();
+        inner.cursor = 0;
+        inner.anchor = None;
     }
 
     fn end(&mut self) {
         let mut inner = self.inner.borrow_mut();
         inner.cursor = inner.buffer.len();
+        inner.anchor = None;
     }
 }
 
@@ -144,6 +205,15 @@ impl InputBuffer {
 struct Inner {
     buffer: String,
     cursor: usize,
+    // the other end of the selection, the cursor is always one end of it
+    anchor: Option<usize>,
+}
+
+impl Inner {
+    fn selection(&self) -> Option<Range<usize>> {
+        let anchor = self.anchor.filter(|&anchor| anchor != self.cursor)?;
+        Some(anchor.min(self.cursor)..anchor.max(self.cursor))
+    }
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -155,6 +225,8 @@ pub struct InputResponse {
 pub struct InputWidget {
     props: InputBuffer,
     consume: bool,
+    // events are handled outside of a frame, so this waits for the next update
+    clipboard: Option<Command>,
 }
 
 impl Widget for InputWidget {
@@ -162,6 +234,10 @@ impl Widget for InputWidget {
     type Props<'a> = (&'a InputBuffer, &'a mut String);
 
     fn update(&mut self, (props, entered): Self::Props<'_>) -> Self::Response {
+        if let Some(cmd) = self.clipboard.take() {
+            ui().command(cmd)
+        }
+
         let consumed = std::mem::take(&mut self.consume);
         if consumed {
             *entered = self.props.take();
@@ -171,6 +247,10 @@ impl Widget for InputWidget {
         Self::Response { consumed }
     }
 
+    fn retained(&self) -> bool {
+        false
+    }
+
     fn layout(&self, _ctx: LayoutCtx, input: Constraints) -> Size {
         // TODO calculate height
         input.constrain_min(size(20.0, 1.0))
@@ -186,32 +266,62 @@ impl Widget for InputWidget {
         let inner = self.props.inner.borrow();
         ctx.draw(Styled::new(&inner.buffer));
 
-        let cursor_pos = pos2(inner.cursor as _, 0) + Pos2::from(ctx.rect.left_top());
-        if let Some(cell) = ctx.canvas.get_mut(cursor_pos) {
-            *cell = cell.attr(Attribute::UNDERLINE).fg(0xFF0000)
+        // the cursor is a byte offset, but wide and multi-byte chars don't take up that many columns
+        let column = |index: usize| inner.buffer.get(..index).map_or(0, |s| s.width()) as i32;
+
+        if let Some(range) = inner.selection() {
+            let rect = ctx.rect;
+            let mut canvas = ctx.crop(rect);
+            for x in rect.left() + column(range.start)..rect.left() + column(range.end) {
+                if let Some(cell) = canvas.get_mut(pos2(x, rect.top())) {
+                    cell.add_attr(Attribute::REVERSE)
+                }
+            }
         }
+
+        let column = column(inner.cursor);
+        let cursor_pos = pos2(column as _, 0) + ctx.rect.left_top();
+        ctx.set_cursor(cursor_pos, CursorShape::Bar, true);
     }
 
     fn event(&mut self, ctx: EventCtx, event: Event) -> Handled {
         let key = match event {
             Event::MouseClick(ev) => {
                 let mut inner = self.props.inner.borrow_mut();
-                let pos = remap(
+                let width = inner.buffer.width();
+                let column = remap(
                     ev.pos.x as f32,
                     (
                         ctx.rect.left() as f32,
-                        ctx.rect.left() as f32 + inner.buffer.len() as f32,
+                        ctx.rect.left() as f32 + width as f32,
                     ),
-                    (0.0, inner.buffer.len() as f32),
-                );
-                inner.cursor = pos as usize;
+                    (0.0, width as f32),
+                ) as usize;
+
+                // the char that covers the column
+                let mut x = 0;
+                inner.anchor = None;
+                inner.cursor = inner
+                    .buffer
+                    .char_indices()
+                    .find(|(_, ch)| {
+                        x += ch.width().unwrap_or(0);
+                        x > column
+                    })
+                    .map_or(inner.buffer.len(), |(i, _)| i);
                 return Handled::Sink;
             }
             Event::MouseDrag(_) => {
                 // TODO this
                 return Handled::Bubble;
             }
-            Event::KeyInput(key) => key,
+            Event::Paste(data) => {
+                // the buffer is a single line
+                let data = data.chars().filter(|c| !c.is_control()).collect::<String>();
+                self.props.insert_str(&data);
+                return Handled::Sink;
+            }
+            Event::KeyInput(key) if key.is_down() => key,
             _ => return Handled::Bubble,
         };
 
@@ -227,6 +337,18 @@ impl Widget for InputWidget {
         // }
 
         match key.key {
+            // ctrl-c only reaches here when it doesn't quit, ctrl-insert always does
+            Key::Char('c') if key.modifiers.is_ctrl_only() => self.clipboard = self.props.copy(),
+            Key::Insert if key.modifiers.is_ctrl_only() => self.clipboard = self.props.copy(),
+            Key::Char('x') if key.modifiers.is_ctrl_only() => self.clipboard = self.props.cut(),
+            // the contents come back as a paste
+            Key::Char('v') if key.modifiers.is_ctrl_only() => {
+                self.clipboard = Some(Command::RequestClipboard)
+            }
+            Key::Insert if key.modifiers.is_shift_only() => {
+                self.clipboard = Some(Command::RequestClipboard)
+            }
+
             Key::Char(ch) if !ch.is_control() => self.props.insert(ch),
 
             Key::Left if key.modifiers.is_shift() && key.modifiers.is_ctrl() => {
@@ -257,7 +379,7 @@ impl Widget for InputWidget {
 
             Key::Enter => self.consume = true,
 
-            Key::Insert | Key::Tab | Key::BackTab | _ => return Handled::Bubble,
+            _ => return Handled::Bubble,
         }
 
         Handled::Sink
@@ -268,3 +390,122 @@ impl Widget for InputWidget {
 pub fn text_input(input: &InputBuffer, entered: &mut String) -> Response<InputResponse> {
     InputWidget::show((input, entered))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        debug::Harness,
+        geom::{pos2, vec2},
+        input::Keybind,
+    };
+
+    #[test]
+    fn cursor_is_placed_by_width() {
+        let input = InputBuffer::default();
+        let mut entered = String::new();
+        let mut harness = Harness::new(vec2(20, 1));
+        let mut app = |_: &crate::Ui| {
+            text_input(&input, &mut entered);
+        };
+
+        harness.frame(&mut app);
+        harness.type_str("é日x");
+        let snapshot = harness.frame(&mut app);
+        assert_eq!(snapshot.line(0).map(str::trim_end), Some("é日x"));
+        assert_eq!(snapshot.cursor, Some(pos2(4, 0)));
+
+        harness.key(Key::Left).key(Key::Left);
+        let snapshot = harness.frame(&mut app);
+        assert_eq!(snapshot.cursor, Some(pos2(1, 0)));
+
+        // clicking on the right half of the wide char puts the cursor before it
+        harness.click(pos2(2, 0));
+        let snapshot = harness.frame(&mut app);
+        assert_eq!(snapshot.cursor, Some(pos2(1, 0)));
+    }
+
+    #[test]
+    fn clipboard_needs_a_selection() {
+        let input = InputBuffer::new("hello world");
+        let mut entered = String::new();
+        let mut harness = Harness::new(vec2(20, 1));
+        let mut app = |_: &crate::Ui| {
+            text_input(&input, &mut entered);
+        };
+        harness.frame(&mut app);
+
+        // without a selection nothing is copied, and nothing is lost
+        harness
+            .key(Keybind::char('x').ctrl())
+            .key(Keybind::char('c').ctrl());
+        harness.frame(&mut app);
+        harness.frame(&mut app);
+        assert_eq!(harness.clipboard(), None);
+        assert_eq!(&*input.as_str(), "hello world");
+
+        for _ in 0..5 {
+            harness.key(Keybind::key(Key::Left).shift());
+        }
+        harness.key(Keybind::char('x').ctrl());
+        harness.frame(&mut app);
+        harness.frame(&mut app);
+        assert_eq!(harness.clipboard(), Some("world"));
+        assert_eq!(&*input.as_str(), "hello ");
+
+        harness.key(Keybind::key(Key::Home).shift());
+        harness.key(Keybind::char('c').ctrl());
+        harness.frame(&mut app);
+        harness.frame(&mut app);
+        assert_eq!(harness.clipboard(), Some("hello "));
+        assert_eq!(&*input.as_str(), "hello ");
+
+        // typing replaces the selection
+        harness.type_str("bye");
+        harness.frame(&mut app);
+        assert_eq!(&*input.as_str(), "bye");
+    }
+
+    #[test]
+    fn paste_goes_into_the_input_on_one_line() {
+        let input = InputBuffer::default();
+        let mut entered = String::new();
+        let mut harness = Harness::new(vec2(20, 1));
+        let mut app = |_: &crate::Ui| {
+            text_input(&input, &mut entered);
+        };
+        harness.frame(&mut app);
+
+        harness.type_str("ab").key(Key::Left);
+        harness.paste("x\r\ny\tz\x1b\x07!\n");
+        harness.frame(&mut app);
+        harness.frame(&mut app);
+        // line breaks and other control characters are dropped, so nothing is submitted
+        assert_eq!(&*input.as_str(), "axyz!b");
+
+        // pastes replace the selection like typing does
+        harness.key(Keybind::key(Key::Home).shift()).paste("new");
+        harness.frame(&mut app);
+        assert_eq!(&*input.as_str(), "newb");
+    }
+
+    #[test]
+    fn paste_only_reaches_one_input() {
+        let first = InputBuffer::default();
+        let second = InputBuffer::default();
+        let (mut a, mut b) = (String::new(), String::new());
+        let mut harness = Harness::new(vec2(20, 2));
+        let mut app = |_: &crate::Ui| {
+            crate::widgets::column(|| {
+                text_input(&first, &mut a);
+                text_input(&second, &mut b);
+            });
+        };
+        harness.frame(&mut app);
+
+        harness.paste("hi");
+        harness.frame(&mut app);
+        let pasted = [&*first.as_str(), &*second.as_str()];
+        assert!(pasted == ["hi", ""] || pasted == ["", "hi"], "{pasted:?}");
+    }
+}
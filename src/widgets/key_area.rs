@@ -33,6 +33,9 @@ impl Widget for KeyAreaWidget {
 
     fn event(&mut self, _ctx: EventCtx, event: Event) -> Handled {
         if let Event::KeyInput(ev) = event {
+            if !ev.is_down() {
+                return Handled::Bubble;
+            }
             self.last_modifiers = Some(ev.modifiers);
             self.last_key = Some(ev.key);
         }
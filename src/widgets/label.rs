@@ -2,6 +2,7 @@ use crate::{
     context::{LayoutCtx, PaintCtx},
     geom::{Constraints, Pos2, Size},
     paint::{Cell, Label, MappedStyle, Styled},
+    ui,
     widget::Response,
     NoResponse, Widget, WidgetExt as _,
 };
@@ -45,6 +46,9 @@ where
     type Props<'a> = MappedStyle<T, F>;
 
     fn update(&mut self, props: Self::Props<'_>) -> Self::Response {
+        if !self.props.as_ref().is_some_and(|old| old.same_as(&props)) {
+            ui().invalidate()
+        }
         self.props = Some(props);
     }
 
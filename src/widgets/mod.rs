@@ -7,6 +7,7 @@ mod filled;
 mod flex;
 mod float;
 mod flow;
+mod image;
 mod input;
 mod key_area;
 mod label;
@@ -58,6 +59,11 @@ pub use self::float::float;
 
 pub use self::flow::flow;
 
+pub use self::image::image;
+pub use self::image::image_sized;
+pub use self::image::ImageView;
+pub use self::image::Scaling;
+
 pub use self::input::text_input;
 pub use self::input::InputBuffer;
 
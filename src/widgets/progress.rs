@@ -6,13 +6,14 @@ use crate::{
     geom::{math::remap, size, Constraints, Size},
     paint::{
         shape::{Filled, Line},
-        Cell,
+        Attribute, Cell,
     },
+    ui,
     widget::Response,
     NoResponse, Widget, WidgetExt as _,
 };
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, PartialEq)]
 pub struct Progress {
     pos: f32,
     min: f32,
@@ -62,6 +63,9 @@ impl Widget for ProgressWidget {
     type Props<'a> = Progress;
 
     fn update(&mut self, props: Self::Props<'_>) -> Self::Response {
+        if self.props != props {
+            ui().invalidate()
+        }
         self.props = props;
     }
 
@@ -71,15 +75,61 @@ impl Widget for ProgressWidget {
 
     fn paint(&self, mut ctx: PaintCtx) {
         let rect = ctx.rect;
+        let monochrome = ctx.is_monochrome();
 
-        ctx.draw(Filled::bg(self.props.bg));
+        if monochrome {
+            ctx.draw(Filled::new('░'));
+        } else {
+            ctx.draw(Filled::bg(self.props.bg));
+        }
 
         let (min, max) = (rect.left(), rect.right() + 1);
         let x = remap(self.props.pos, (self.props.min, self.props.max), (min, max)) - min;
-        ctx.draw(Line::horizontal(x).custom_cell(|_| Cell::new(' ').bg(self.props.filled)));
+
+        let filled = if monochrome {
+            Cell::new(' ').attr(Attribute::REVERSE)
+        } else {
+            Cell::new(' ').bg(self.props.filled)
+        };
+        ctx.draw(Line::horizontal(x).custom_cell(|_| filled));
     }
 }
 
 pub fn progress(pos: f32, range: RangeInclusive<f32>) -> Response {
     Progress::new(pos, range).show()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        debug::Harness,
+        geom::{pos2, vec2},
+        widgets::row,
+    };
+
+    #[test]
+    fn monochrome_fills_with_reversed_cells() {
+        let mut harness = Harness::new(vec2(20, 1)).monochrome(true);
+
+        let snapshot = harness.frame(|_| _ = row(|| progress(0.5, 0.0..=1.0)));
+        assert_eq!(
+            snapshot.text,
+            format!("{}{}", " ".repeat(10), "░".repeat(10))
+        );
+        for x in 0..20 {
+            let expected = if x < 10 {
+                Attribute::REVERSE
+            } else {
+                Attribute::RESET
+            };
+            assert_eq!(snapshot.attr(pos2(x, 0)), expected, "{x}");
+        }
+
+        let snapshot = harness.frame(|_| _ = row(|| progress(1.0, 0.0..=1.0)));
+        assert_eq!(snapshot.text, " ".repeat(20));
+        for x in 0..20 {
+            assert_eq!(snapshot.attr(pos2(x, 0)), Attribute::REVERSE, "{x}");
+        }
+    }
+}
@@ -66,6 +66,10 @@ impl Widget for ScrollableWidget {
         }
     }
 
+    fn retained(&self) -> bool {
+        false
+    }
+
     fn layout(&self, mut ctx: LayoutCtx, mut input: Constraints) -> Size {
         ctx.enable_clipping();
 
@@ -101,7 +105,6 @@ impl Widget for ScrollableWidget {
     fn paint(&self, mut ctx: PaintCtx) {
         if self.pos < 0 && self.show_scrollbar {
             let area = ctx.rect;
-            let rect = Rect::from_min_size(area.right_top(), vec2(1, area.height()));
 
             ctx.draw(Filled::new(Cell::new('│').fg(0x111111)));
 
@@ -130,7 +133,7 @@ impl Widget for ScrollableWidget {
             Event::MouseScroll(scroll) => -scroll.delta.y,
             // TODO if we have focus
             // TODO focus
-            Event::KeyInput(KeyPressed { key, .. }) => match key {
+            Event::KeyInput(ev @ KeyPressed { key, .. }) if ev.is_down() => match key {
                 Key::Up => 1,
                 Key::Down => -1,
                 Key::PageUp => self.our_rect.height(),
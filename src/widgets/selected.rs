@@ -27,6 +27,13 @@ impl Radio {
 
     pub fn show<R, V: PartialEq>(self, value: &mut V, selected: V, show: impl FnOnce() -> R) {
         let resp = on_click(|| {
+            if ui().is_monochrome() {
+                return List::row().spacing(1).show(|| {
+                    label(if *value == selected { '◉' } else { '○' });
+                    show();
+                });
+            }
+
             let bg = if *value == selected {
                 self.active_fill
             } else {
@@ -146,3 +153,34 @@ impl Selected {
 pub fn selected<R>(value: &mut bool, show: impl FnOnce() -> R) {
     Selected::default().show(value, show)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        debug::Harness,
+        geom::{pos2, vec2},
+        widgets::column,
+    };
+
+    #[test]
+    fn monochrome_radio_marks_the_selection() {
+        let mut harness = Harness::new(vec2(4, 2)).monochrome(true);
+        let mut value = 1;
+        let app = |value: &mut i32| {
+            column(|| {
+                radio(value, 1, || label("a"));
+                radio(value, 2, || label("b"));
+            });
+        };
+
+        let snapshot = harness.frame(|_| app(&mut value));
+        assert_eq!(snapshot.text, "◉ a \n○ b ");
+
+        harness.click(pos2(2, 1));
+        harness.frame(|_| app(&mut value));
+        let snapshot = harness.frame(|_| app(&mut value));
+        assert_eq!(value, 2);
+        assert_eq!(snapshot.text, "○ a \n◉ b ");
+    }
+}
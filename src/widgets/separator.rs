@@ -2,7 +2,7 @@ use crate::{
     context::{LayoutCtx, PaintCtx},
     geom::{size, Constraints, Size},
     paint::{shape::Filled, Cell},
-    NoResponse, Widget, WidgetExt as _,
+    ui, NoResponse, Widget, WidgetExt as _,
 };
 
 #[derive(Debug, Default)]
@@ -15,6 +15,9 @@ impl Widget for SeparatorWidget {
     type Props<'a> = Separator;
 
     fn update(&mut self, props: Self::Props<'_>) -> Self::Response {
+        if self.props != props {
+            ui().invalidate()
+        }
         self.props = props;
     }
 
@@ -27,7 +30,7 @@ impl Widget for SeparatorWidget {
     }
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub struct Separator {
     cell: Cell,
 }
@@ -10,7 +10,7 @@ use crate::{
     input::{Event, Handled, Interest},
     paint::{
         shape::{Filled, Line},
-        Cell,
+        Attribute, Cell,
     },
     widget::Response,
     NoResponse, Widget, WidgetExt as _,
@@ -144,6 +144,10 @@ impl Widget for SliderWidget {
         }
     }
 
+    fn retained(&self) -> bool {
+        false
+    }
+
     fn interest(&self) -> Interest {
         Interest::MOUSE
     }
@@ -173,7 +177,11 @@ impl Widget for SliderWidget {
         let props = self.props;
 
         // TODO these names don't match up
-        let track_cell = Cell::new(props.style.track).fg(props.filled);
+        let mut track_cell = Cell::new(props.style.track).fg(props.filled);
+        // the two sides of the knob can't be told apart by color
+        if ctx.is_monochrome() {
+            track_cell = track_cell.attr(Attribute::FAINT);
+        }
         let remaining_cell = Cell::new(props.style.track).fg(props.track);
         let knob_cell = Cell::new(props.style.knob).fg(props.knob);
 
@@ -205,3 +213,33 @@ impl Widget for SliderWidget {
 pub fn slider(current: &mut f32, range: RangeInclusive<f32>) -> Response {
     Slider::new(range).show(current)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{debug::Harness, geom::vec2, widgets::row};
+
+    #[test]
+    fn monochrome_dims_the_rest_of_the_track() {
+        let mut harness = Harness::new(vec2(20, 1)).monochrome(true);
+
+        let mut value = 0.5;
+        let snapshot = harness.frame(|_| _ = row(|| slider(&mut value, 0.0..=1.0)));
+        assert_eq!(snapshot.find("●"), Some(pos2(10, 0)));
+        for x in 0..20 {
+            let expected = if x > 10 {
+                Attribute::FAINT
+            } else {
+                Attribute::RESET
+            };
+            assert_eq!(snapshot.attr(pos2(x, 0)), expected, "{x}");
+        }
+
+        let mut value = 0.0;
+        let snapshot = harness.frame(|_| _ = row(|| slider(&mut value, 0.0..=1.0)));
+        assert_eq!(snapshot.find("●"), Some(pos2(0, 0)));
+        for x in 1..20 {
+            assert_eq!(snapshot.attr(pos2(x, 0)), Attribute::FAINT, "{x}");
+        }
+    }
+}
@@ -54,6 +54,10 @@ impl Widget for SplitterWidget {
         self.pos.take()
     }
 
+    fn retained(&self) -> bool {
+        false
+    }
+
     fn interest(&self) -> Interest {
         Interest::MOUSE
     }
@@ -6,7 +6,7 @@ use crate::{
         math::{lerp, remap},
         pos2, size, Constraints, Size,
     },
-    paint::{shape::Filled, Cell},
+    paint::{shape::Filled, Attribute, Cell},
     NoResponse, Widget, WidgetExt as _,
 };
 
@@ -98,6 +98,10 @@ impl Widget for ToggleSwitchWidget {
         self.props = props
     }
 
+    fn retained(&self) -> bool {
+        false
+    }
+
     fn layout(&self, _: LayoutCtx, input: Constraints) -> Size {
         input.constrain_min(size(5.0, 1.0))
     }
@@ -111,9 +115,14 @@ impl Widget for ToggleSwitchWidget {
         let x = remap(pos, (0.0, 1.0), (ctx.rect.left(), ctx.rect.right()))
             .clamp(ctx.rect.left(), ctx.rect.right());
 
-        ctx.draw(Filled::new(
-            Cell::new(self.props.track).fg(self.props.track_color),
-        ));
+        // without colors the track is dimmed and only the knob is bold when its on
+        let monochrome = ctx.is_monochrome();
+        let track = Cell::new(self.props.track).fg(self.props.track_color);
+        ctx.draw(Filled::new(if monochrome {
+            track.attr(Attribute::FAINT)
+        } else {
+            track
+        }));
 
         let blend = lerp(0.0, 0.4, 1.0 - pos);
         let off = pos < 0.5;
@@ -127,7 +136,10 @@ impl Widget for ToggleSwitchWidget {
             self.props.active_knob
         };
 
-        let cell = Cell::new(self.props.knob).fg(fg);
+        let mut cell = Cell::new(self.props.knob).fg(fg);
+        if monochrome && !off {
+            cell = cell.attr(Attribute::BOLD)
+        }
         ctx.put(pos2(x, ctx.rect.top()), cell);
     }
 }
@@ -135,3 +147,32 @@ impl Widget for ToggleSwitchWidget {
 pub fn toggle_switch(value: &mut bool) {
     ToggleSwitch::default().show(value)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{debug::Harness, geom::vec2, widgets::row};
+
+    #[test]
+    fn monochrome_shows_the_state_with_attributes() {
+        let mut harness = Harness::new(vec2(5, 1)).monochrome(true);
+        let mut value = false;
+
+        let snapshot = harness.frame(|_| _ = row(|| toggle_switch(&mut value)));
+        assert_eq!(snapshot.text, "█■■■■");
+        assert_eq!(snapshot.attr(pos2(0, 0)), Attribute::RESET);
+        for x in 1..5 {
+            assert_eq!(snapshot.attr(pos2(x, 0)), Attribute::FAINT);
+        }
+
+        // long enough for the knob to get to the other side
+        harness.click(pos2(0, 0));
+        let snapshot = harness.frames(30, |_| _ = row(|| toggle_switch(&mut value)));
+        assert!(value);
+        assert_eq!(snapshot.text, "■■■■█");
+        assert_eq!(snapshot.attr(pos2(4, 0)), Attribute::BOLD);
+        for x in 0..4 {
+            assert_eq!(snapshot.attr(pos2(x, 0)), Attribute::FAINT);
+        }
+    }
+}
@@ -59,6 +59,10 @@ impl<T: Label + 'static> Widget for WindowWidget<T> {
         Self::Response { closed: !self.open }
     }
 
+    fn retained(&self) -> bool {
+        false
+    }
+
     fn layout(&self, ctx: LayoutCtx, input: Constraints) -> Size {
         let mut size = Size::ZERO;
         let constraints = Constraints::tight(input.min);